@@ -0,0 +1,75 @@
+use annotate_snippets::{
+    display_list::{DisplayList, FormatOptions},
+    snippet::{Annotation, AnnotationType, Slice, Snippet, SourceAnnotation},
+};
+use snowc_parse::Span;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CodeGenError {
+    #[error("{0} are not supported by the {1} target")]
+    Unsupported(String, &'static str, Span),
+    #[error("undefined identifier {0}")]
+    Undefined(String, Span),
+    #[error("{0} does not fit in the vm's 30 bit Int")]
+    IntOutOfRange(i64, Span),
+    #[error("expression needs more registers than the vm has")]
+    OutOfRegisters(Span),
+    #[error("missing main function")]
+    MissingMainFunction,
+    #[error("failed to assemble program: {0}")]
+    Assembler(String),
+}
+
+impl CodeGenError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::Unsupported(_, _, span)
+            | Self::Undefined(_, span)
+            | Self::IntOutOfRange(_, span)
+            | Self::OutOfRegisters(span) => Some(*span),
+            Self::MissingMainFunction | Self::Assembler(..) => None,
+        }
+    }
+
+    pub fn report(&self, filename: &str, src: &str) -> String {
+        let label = self.to_string();
+        let Some(span) = self.span() else {
+            return label;
+        };
+        let snippet = snippet_builder(filename, src, &label, span);
+        DisplayList::from(snippet).to_string()
+    }
+}
+
+fn snippet_builder<'a>(
+    filename: &'a str,
+    src: &'a str,
+    label: &'a str,
+    span: Span,
+) -> Snippet<'a> {
+    let range = (span.idx_start, span.idx_end);
+    Snippet {
+        title: Some(Annotation {
+            label: Some(label),
+            id: None,
+            annotation_type: AnnotationType::Error,
+        }),
+        footer: vec![],
+        slices: vec![Slice {
+            source: src,
+            line_start: 1,
+            origin: Some(filename),
+            fold: true,
+            annotations: vec![SourceAnnotation {
+                label,
+                annotation_type: AnnotationType::Error,
+                range,
+            }],
+        }],
+        opt: FormatOptions {
+            color: true,
+            ..Default::default()
+        },
+    }
+}
@@ -1,123 +1,165 @@
+use crate::CodeGenError;
 use snowc_parse::{App, Atom, Binary, Expr, TypeInfo, Unary};
 
-pub fn java_gen_code(input: &Vec<Expr>) -> Option<String> {
+type Result<T> = std::result::Result<T, CodeGenError>;
+
+pub fn java_gen_code(input: &Vec<Expr>) -> Result<String> {
     let mut stmts = vec![];
     // stmts.push("const print = globalThis.console.log".to_string()); //TODO: Implement a ffi
     stmts.push("import java.util.function.Function;\n".to_string());
     for expr in input {
         let stmt = match expr {
-            Expr::Func(name, types, body, _) => {
-                gen_function(name, &mut (types.clone()), body)
+            Expr::Func(name, types, body, ..) => {
+                gen_function(name, &mut (types.clone()), body)?
             }
-            _ => todo!(),
+            Expr::Enum(ty, _, variants, ..) => gen_enum(ty, variants),
+            // aliases and records only matter to the type checker
+            Expr::Data(..) | Expr::Alias(..) => continue,
+            expr => gen_expr(&mut None, expr, false)?,
         };
         stmts.push(stmt);
     }
 
     let program = stmts.join("\n");
-    Some(program)
+    Ok(program)
 }
 
-fn gen_function(name: &str, types: &mut Vec<TypeInfo>, body: &Expr) -> String {
-    let count_types = types.len();
-    let return_type = if count_types > 0 {
-        type_to_gen(&types[count_types - 1])
-    } else {
-        type_to_gen(&TypeInfo::Custom("Object".to_string()))
+/// An enum is a class tagging its values with the name of their constructor.
+/// Constructors with fields look like functions, `Some()` gives back a
+/// curried `Function`, and the ones without are plain values.
+fn gen_enum(ty: &str, variants: &[(String, Vec<String>)]) -> String {
+    let mut stmts = vec![format!(
+        "static final class {ty} {{\n\tfinal String tag;\n\tfinal Object[] values;\n\n\t{ty}(String tag, Object... values) {{\n\t\tthis.tag = tag;\n\t\tthis.values = values;\n\t}}\n}}"
+    )];
+    for (name, fields) in variants {
+        if fields.is_empty() {
+            stmts.push(format!("{ty} {name} = new {ty}(\"{name}\");"));
+            continue;
+        }
+        let values = (0..fields.len())
+            .map(|idx| format!("v{idx}"))
+            .collect::<Vec<_>>();
+        let params = values
+            .iter()
+            .map(|value| format!("(Object {value}) -> "))
+            .collect::<String>();
+        let function = values
+            .iter()
+            .fold(ty.to_string(), |ret, _| format!("Function<Object, {ret}>"));
+        let values = values.join(", ");
+        stmts.push(format!(
+            "{function} {name}() {{\n\treturn {params}new {ty}(\"{name}\", {values});\n}}"
+        ));
+    }
+    stmts.join("\n")
+}
+
+fn gen_function(name: &str, types: &mut Option<TypeInfo>, body: &Expr) -> Result<String> {
+    let mut ret = types.as_ref();
+    while let Some((_, tail)) = ret.and_then(TypeInfo::split) {
+        ret = Some(tail);
+    }
+    let return_type = match ret {
+        Some(ret) => type_to_gen(ret),
+        None => "Object".to_string(),
     };
 
-    let _body = gen_function_body(types, body);
+    let _body = gen_function_body(types, body)?;
     if name == "main" {
-       format!("public static void {name}(String[] args) {{\n\t{_body};\n}}")
+       Ok(format!("public static void {name}(String[] args) {{\n\t{_body};\n}}"))
     } else {
         let arg = match body {
             Expr::Closure(_, _, _) => "Function<?, ?>", 
             _ => "Object"
         };
-        format!("Function<{return_type}, {arg}> {name}() {{\n\treturn {_body};\n}}")
+        Ok(format!("Function<{return_type}, {arg}> {name}() {{\n\treturn {_body};\n}}"))
     }
 }
 
-fn gen_function_body(types: &mut Vec<TypeInfo>, body: &Expr) -> String {
+fn gen_function_body(types: &mut Option<TypeInfo>, body: &Expr) -> Result<String> {
     match body {
-        Expr::Closure(_, _body, _) => {
-            let count_types = types.len();
-            if count_types > 0 {
-                gen_expr(types, body, true)
-            } else {
-                gen_expr(types, body, true)
-            }
-        }
+        Expr::Closure(..) => gen_expr(types, body, true),
         _ => gen_expr(types, body, false),
     }
 }
 
-fn gen_expr(types: &mut Vec<TypeInfo>, expr: &Expr, ret: bool) -> String {
-    match expr {
-        Expr::Atom(atom) => gen_atom(atom.clone()),
-        Expr::App(app) => gen_app(types, app.clone()),
-        Expr::Enum(_, _, _) => todo!(),
-        Expr::Closure(arg, body, _) => gen_closure(types, &arg, &body, ret),
-        Expr::Array(exprs, _, _) => gen_array(types, exprs),
-        Expr::Binary(bin) => gen_binary(types, bin),
-        Expr::Unary(unary) => gen_unary(types, unary),
+fn gen_expr(types: &mut Option<TypeInfo>, expr: &Expr, ret: bool) -> Result<String> {
+    let (feature, span) = match expr {
+        Expr::Atom(atom) => return Ok(gen_atom(atom.clone())),
+        Expr::App(app) => return gen_app(types, app.clone()),
+        Expr::Closure(arg, body, _) => return gen_closure(types, arg, body, ret),
+        Expr::Array(exprs, _, _) => return gen_array(types, exprs),
+        Expr::Binary(bin) => return gen_binary(types, bin),
+        Expr::Unary(unary) => return gen_unary(types, unary),
         Expr::IfElse(cond, if_true, if_false, _) => {
-            gen_conditional(types, cond, if_true, if_false)
+            return gen_conditional(types, cond, if_true, if_false)
         }
-        _ => todo!(),
-    }
+        Expr::Match(.., span) => ("match expressions", span),
+        Expr::Clauses(.., span) => ("functions with patterns or guards", span),
+        Expr::Let(.., span) => ("let expressions", span),
+        Expr::Tuple(.., span) => ("tuples", span),
+        Expr::Record(.., span) | Expr::Field(.., span) | Expr::Update(.., span) => {
+            ("records", span)
+        }
+        Expr::Enum(.., span)
+        | Expr::Data(.., span)
+        | Expr::Alias(.., span)
+        | Expr::Func(.., span)
+        | Expr::Error(span) => ("nested declarations", span),
+    };
+    Err(CodeGenError::Unsupported(feature.into(), "java", *span))
 }
 
 fn gen_conditional(
-    types: &mut Vec<TypeInfo>,
+    types: &mut Option<TypeInfo>,
     cond: &Expr,
     truth: &Expr,
     falsy: &Expr,
-) -> String {
-    let cond = gen_expr(types, cond, false);
-    let truth = gen_expr(types, truth, false);
-    let falsy = gen_expr(types, falsy, false);
-    format!("({cond} ? {truth} : {falsy})")
+) -> Result<String> {
+    let cond = gen_expr(types, cond, false)?;
+    let truth = gen_expr(types, truth, false)?;
+    let falsy = gen_expr(types, falsy, false)?;
+    Ok(format!("({cond} ? {truth} : {falsy})"))
 }
 
-fn gen_unary(types: &mut Vec<TypeInfo>, unary: &Unary) -> String {
+fn gen_unary(types: &mut Option<TypeInfo>, unary: &Unary) -> Result<String> {
     let op = &unary.op;
-    let expr = gen_expr(types, &unary.expr, false);
-    format!("({op} {expr})")
+    let expr = gen_expr(types, &unary.expr, false)?;
+    Ok(format!("({op} {expr})"))
 }
 
-fn gen_binary(types: &mut Vec<TypeInfo>, binary: &Binary) -> String {
+fn gen_binary(types: &mut Option<TypeInfo>, binary: &Binary) -> Result<String> {
     let op = &binary.op;
-    let left = gen_expr(types, &binary.left, false);
-    let right = gen_expr(types, &binary.right, false);
-    format!("({left} {op} {right})")
+    let left = gen_expr(types, &binary.left, false)?;
+    let right = gen_expr(types, &binary.right, false)?;
+    Ok(format!("({left} {op} {right})"))
 }
 
-fn gen_array(types: &mut Vec<TypeInfo>, exprs: &[Expr]) -> String {
+fn gen_array(types: &mut Option<TypeInfo>, exprs: &[Expr]) -> Result<String> {
     let mut values = vec![];
     for value in exprs {
-        values.push(gen_expr(types, value, false));
+        values.push(gen_expr(types, value, false)?);
     }
 
-    format!("[{}]", values.join(","))
+    Ok(format!("[{}]", values.join(",")))
 }
 
-fn gen_closure(types: &mut Vec<TypeInfo>, arg: &Expr, body: &Expr, ret: bool) -> String {
+fn gen_closure(types: &mut Option<TypeInfo>, arg: &Expr, body: &Expr, ret: bool) -> Result<String> {
     let arg_type = gen_top_arg_type(types);
-    let arg_value = gen_expr(types, &arg, ret);
-    let _body = gen_expr(types, body, false);
-    match body {
+    let arg_value = gen_expr(types, arg, ret)?;
+    let _body = gen_expr(types, body, false)?;
+    Ok(match body {
         Expr::Closure(_, _, _) => format!("({arg_type} {arg_value}) -> {_body}"), 
         _ => format!("({arg_type} {arg_value}) -> {_body}")
-    }
+    })
 }
 
-fn gen_app(types: &mut Vec<TypeInfo>, app: App) -> String {
+fn gen_app(types: &mut Option<TypeInfo>, app: App) -> Result<String> {
     let name = &app.name;
     let mut args = vec![];
     let size = app.args.len();
     for (i, arg) in app.args.into_iter().enumerate() {
-        let expr = gen_expr(types, &arg, true);
+        let expr = gen_expr(types, &arg, true)?;
         if i == 0 {
             args.push(format!("var {name}_{i} = {name}().apply({expr});"));
         } else if i < size -1 {
@@ -129,37 +171,85 @@ fn gen_app(types: &mut Vec<TypeInfo>, app: App) -> String {
         }
     }
 
-    format!("{}", args.join("\n"))
+    Ok(args.join("\n").to_string())
 }
 
 fn gen_atom(atom: Atom) -> String {
     match atom {
         Atom::Id(id, _, _) => id,
-        Atom::Int(int, _, _) => format!("{int}"),
+        Atom::Int(int, _, _) => format!("{int}L"),
         Atom::Bool(bool, _, _) => format!("{bool}"),
-        Atom::Char(char, _, _) => format!("{char}"),
-        Atom::Float(float, _, _) => format!("{float}"),
-        Atom::String(str, _, _) => format!("\"{str}\""),
+        Atom::Char(char, _, _) => format!("{char:?}"),
+        Atom::Float(float, _, _) => format!("{float:?}"),
+        Atom::String(str, _, _) => format!("{str:?}"),
     }
 }
 
-fn gen_top_arg_type(types: &mut Vec<TypeInfo>) -> String {
-    let count_types = types.len();
-    if count_types > 0 {
-        type_to_gen(&types.remove(0))
-    } else {
-        type_to_gen(&TypeInfo::Custom("Object".to_string()))
+/// The parameter of the function type, leaving its result in `types` for
+/// the closures that follow.
+fn gen_top_arg_type(types: &mut Option<TypeInfo>) -> String {
+    match types.take() {
+        Some(TypeInfo::Fn(param, ret)) => {
+            *types = Some(*ret);
+            type_to_gen(&param)
+        }
+        _ => "Object".to_string(),
     }
 }
 
 fn type_to_gen(_type: &TypeInfo) -> String {
     match _type {
-        TypeInfo::Int => "Integer".to_string(),
-        TypeInfo::Float => "Float".to_string(),
+        TypeInfo::Int => "Long".to_string(),
+        TypeInfo::Float => "Double".to_string(),
         TypeInfo::Bool => "Boolean".to_string(),
         TypeInfo::String => "String".to_string(),
         TypeInfo::Char => "Char".to_string(),
         TypeInfo::Array(inner) => format!("{}[]", type_to_gen(inner)),
-        TypeInfo::Custom(value) => format!("{value}"),
+        TypeInfo::Tuple(..) => "Object[]".to_string(),
+        TypeInfo::Var(_) => "Object".to_string(),
+        TypeInfo::App(name, args) if args.is_empty() => name.to_string(),
+        TypeInfo::App(name, args) => {
+            let args = args.iter().map(type_to_gen).collect::<Vec<_>>();
+            format!("{name}<{}>", args.join(", "))
+        }
+        TypeInfo::Fn(param, ret) => {
+            format!("Function<{}, {}>", type_to_gen(param), type_to_gen(ret))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use snowc_parse::parse;
+
+    #[test]
+    fn gen_code_enum() {
+        let ast = parse("enum Either a b = Left a | Right b | Neither").unwrap();
+        let program = java_gen_code(&ast).unwrap();
+        assert!(program.contains("static final class Either {"));
+        assert!(program.contains(
+            "Function<Object, Either> Left() {\n\treturn (Object v0) -> new Either(\"Left\", v0);"
+        ));
+        assert!(program.contains("Either Neither = new Either(\"Neither\");"));
+    }
+
+    #[test]
+    fn gen_code_declarations() {
+        let ast = parse("type N = Int\ndata P = { x: Int }\nmain = 1").unwrap();
+        let program = java_gen_code(&ast).unwrap();
+        assert!(program.contains("public static void main(String[] args) {"));
+    }
+
+    #[test]
+    fn gen_code_unsupported() {
+        let ast = parse("f x = let y = x in y\nmain = f 1").unwrap();
+        let Err(error) = java_gen_code(&ast) else {
+            panic!("let expressions should not be supported");
+        };
+        assert_eq!(
+            error.to_string(),
+            "let expressions are not supported by the java target"
+        );
     }
 }
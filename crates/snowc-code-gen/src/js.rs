@@ -1,122 +1,588 @@
-use snowc_parse::{App, Atom, Binary, Expr, TypeInfo, Unary};
+use crate::CodeGenError;
+use snowc_parse::{
+    is_primitive, method_name, App, Atom, Binary, Clause, Expr, Op, Pattern, TypeInfo,
+    Unary, BUILTINS,
+};
 
-pub fn js_gen_code(input: &Vec<Expr>) -> Option<String> {
+type Result<T> = std::result::Result<T, CodeGenError>;
+
+pub fn js_gen_code(input: &Vec<Expr>) -> Result<String> {
     let mut stmts = vec![];
-    stmts.push("const print = globalThis.console.log".to_string()); //TODO: Implement a ffi
+    stmts.push(NUMBERS.to_string()); //TODO: Implement a ffi
+    stmts.push(IO.to_string());
+    stmts.push(ARRAYS.to_string());
+    // `print` and the desugared `__` primitives are part of the prelude above
+    let prelude: Vec<_> = ARRAY_BUILTINS
+        .into_iter()
+        .chain(CONVERSIONS)
+        .chain([PURE])
+        .collect();
+    for name in BUILTINS {
+        let defined = input
+            .iter()
+            .any(|expr| matches!(expr, Expr::Func(func, ..) if func == name));
+        match prelude.iter().find(|(builtin, _)| *builtin == name) {
+            Some((_, code)) if !defined => stmts.push(code.to_string()),
+            _ => {}
+        }
+    }
+    stmts.push(TUPLE.to_string());
+    // fields are read through `__field` so records need it as well
+    if input
+        .iter()
+        .any(|expr| matches!(expr, Expr::Enum(..) | Expr::Data(..)))
+    {
+        stmts.push(VARIANT.to_string());
+        stmts.push(METHODS.to_string());
+        stmts.push(gen_namespaces(input));
+    }
     for expr in input {
         let stmt = match expr {
-            Expr::Func(name, types, body, _) => {
-                gen_function(name, &mut (types.clone()), body)
+            // the prelude already defines them and they can not be shadowed
+            Expr::Func(name, ..) if is_primitive(name) => continue,
+            Expr::Func(name, types, body, ..) if method_name(name).is_some() => {
+                gen_method(name, &mut (types.clone()), body)?
+            }
+            Expr::Func(name, types, body, ..) => {
+                gen_function(name, &mut (types.clone()), body)?
             }
-            _ => todo!(),
+            Expr::Enum(ty, _, variants, ..) => gen_enum(ty, variants),
+            // records are plain objects and need no constructor
+            Expr::Data(..) | Expr::Alias(..) => continue,
+            expr => gen_expr(&mut None, expr, false)?,
         };
         stmts.push(stmt);
     }
 
     let program = stmts.join("\n");
     let (pretty, _) = prettify_js::prettyprint(&program);
-    Some(pretty)
+    Ok(pretty)
+}
+
+/// Ints are `BigInt`s checked to fit in 64 bits after every operation that
+/// could overflow and floats are numbers, both printed the way the
+/// interpreter prints them.
+const NUMBERS: &str = r#"const __int = (n) => {
+  if (typeof n === "bigint" && BigInt.asIntN(64, n) !== n) throw new Error("Int overflow");
+  return n;
+}
+const __div = (a, b) => {
+  if (b === 0n) throw new Error("division by zero");
+  return __int(a / b);
+}
+const __mod = (a, b) => {
+  if (b === 0n) throw new Error("division by zero");
+  return a % b;
+}
+const __float = (f) => {
+  if (Number.isNaN(f)) return "NaN";
+  if (!Number.isFinite(f)) return f > 0 ? "inf" : "-inf";
+  const abs = Math.abs(f);
+  if (abs !== 0 && (abs < 1e-4 || abs >= 1e16)) return f.toExponential().replace("e+", "e");
+  return Number.isInteger(f) ? f.toFixed(1) : `${f}`;
+}
+const __show = (v) => {
+  if (typeof v === "number") return __float(v);
+  if (typeof v === "bigint") return `${v}`;
+  if (Array.isArray(v)) return `[${v.map(__show).join(", ")}]`;
+  if (v && v.__tuple) return `${v}`;
+  return v;
+}"#;
+
+/// Actions are objects holding what to do once they are run, which only
+/// `main` and `__bind` do. `do` blocks are desugared to `__bind`.
+const IO: &str = r#"const __io = (run) => ({ __io: run })
+const __run = (v) => (v && v.__io ? v.__io() : v)
+const print = (...values) =>
+  __io(() => {
+    globalThis.console.log(...values.map(__show));
+    return values[0];
+  })
+const __bind = (io) => (f) => __io(() => __run(f(__run(io))))"#;
+
+/// Tuples are objects holding their items so they are not mistaken for
+/// arrays, printed the same way the interpreter prints them.
+const TUPLE: &str = r#"const __tuple = (...items) => ({
+  __tuple: items,
+  toString() {
+    return `(${items.map(__show).join(", ")})`;
+  },
+})"#;
+
+/// Ranges and list comprehensions are desugared to these curried helpers.
+const ARRAYS: &str = r#"const __range = (start) => (step) => (end) => {
+  if (step === 0n) throw new Error("invalid args to function");
+  const items = [];
+  for (let i = start; step > 0n ? i <= end : i >= end; i += step) items.push(i);
+  return items;
+}
+const __map = (f) => (xs) => xs.map((x) => f(x))
+const __filter = (f) => (xs) => xs.filter((x) => f(x))
+const __concat = (xss) => xss.flat()
+const __plus = (a, b) => (Array.isArray(a) ? a.concat(b) : __int(a + b))"#;
+
+/// The array builtins of the interpreter, `head`, `tail` and `push` work on
+/// strings as well.
+const ARRAY_BUILTINS: [(&str, &str); 5] = [
+    (
+        "nth",
+        r#"const nth = (xs) => (idx) => {
+  if (idx < 0n || idx >= BigInt(xs.length)) throw new Error("index out of bounds");
+  return xs[Number(idx)];
+}"#,
+    ),
+    (
+        "length",
+        "const length = (xs) => BigInt(Array.isArray(xs) ? xs.length : 0)",
+    ),
+    (
+        "push",
+        "const push = (xs) => (x) => (Array.isArray(xs) ? [...xs, x] : xs + x)",
+    ),
+    (
+        "head",
+        r#"const head = (xs) => {
+  if (xs.length === 0) throw new Error("empty array");
+  return xs[0];
+}"#,
+    ),
+    ("tail", "const tail = (xs) => xs.slice(1)"),
+];
+
+const PURE: (&str, &str) = ("pure", "const pure = (x) => __io(() => x)");
+
+/// `toInt` drops the fraction, saturating out of range floats and turning
+/// `NaN` into 0.
+const CONVERSIONS: [(&str, &str); 2] = [
+    ("toFloat", "const toFloat = (n) => Number(n)"),
+    (
+        "toInt",
+        r#"const toInt = (f) => {
+  if (Number.isNaN(f)) return 0n;
+  if (f >= 2 ** 63) return 2n ** 63n - 1n;
+  if (f <= -(2 ** 63)) return -(2n ** 63n);
+  return BigInt(Math.trunc(f));
+}"#,
+    ),
+];
+
+/// Enum values are objects tagged with their type and the name of their
+/// constructor, printed the same way the interpreter prints them.
+const VARIANT: &str = r#"const __variant = (__type, tag, values) => ({
+  __type,
+  tag,
+  values,
+  toString() {
+    const fields = values.map((v) => (v.values && v.values.length ? `(${v})` : `${v}`));
+    return [tag, ...fields].join(" ");
+  },
+})"#;
+
+/// Methods live in `__methods` under their type, `Option.map` is
+/// `__methods.Option.map`, along with how many arguments they take before
+/// `self`. A field that is not on a record is a method of the type of the
+/// value, `opt.map` waits for those arguments and passes `opt` last.
+const METHODS: &str = r#"const __method = (before, method) => Object.assign(method, { before })
+const __field = (value, name) => {
+  if (value.tag === undefined && Object.hasOwn(value, name)) return value[name];
+  const method = __methods[value.__type][name];
+  const collect = (args) =>
+    args.length === method.before
+      ? args.reduce((f, arg) => f(arg), method)(value)
+      : (arg) => collect([...args, arg]);
+  return collect([]);
+}"#;
+
+/// One namespace for every type with methods,
+/// `const __methods = { Option: {} }`.
+fn gen_namespaces(input: &[Expr]) -> String {
+    let mut namespaces = vec![];
+    for expr in input {
+        let Expr::Func(name, ..) = expr else {
+            continue;
+        };
+        if let Some((ty, _)) = method_name(name) {
+            if !namespaces.contains(&ty) {
+                namespaces.push(ty);
+            }
+        }
+    }
+    let namespaces = namespaces
+        .iter()
+        .map(|ty| format!("{ty}: {{}}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("const __methods = {{ {namespaces} }}")
+}
+
+fn gen_method(name: &str, types: &mut Option<TypeInfo>, body: &Expr) -> Result<String> {
+    let mut before = 0usize;
+    let mut closure = body;
+    while let Expr::Closure(_, inner, ..) = closure {
+        before += 1;
+        closure = inner;
+    }
+    let body = gen_function_body(types, body)?;
+    let before = before.saturating_sub(1);
+    Ok(format!("{} = __method({before}, {body})", js_name(name)))
+}
+
+/// Constructors with fields become curried functions, `Some` turns into
+/// `const Some = (v0) => __variant("Option", "Some", [v0])`.
+fn gen_enum(ty: &str, variants: &[(String, Vec<String>)]) -> String {
+    let mut constructors = vec![];
+    for (name, fields) in variants {
+        let values = (0..fields.len())
+            .map(|idx| format!("v{idx}"))
+            .collect::<Vec<_>>();
+        let params = values
+            .iter()
+            .map(|value| format!("({value}) => "))
+            .collect::<String>();
+        let values = values.join(", ");
+        constructors.push(format!(
+            "const {name} = {params}__variant(\"{ty}\", \"{name}\", [{values}])"
+        ));
+    }
+    constructors.join("\n")
 }
 
-fn gen_function(name: &str, types: &mut Vec<TypeInfo>, body: &Expr) -> String {
-    let body = gen_function_body(types, body);
+fn gen_function(name: &str, types: &mut Option<TypeInfo>, body: &Expr) -> Result<String> {
+    let body = gen_function_body(types, body)?;
     if name == "main" {
         let main_function = format!("const {name} = () => {body}");
-        format!("{main_function}\n\n{name}()")
+        // what the action gives back is dropped, like the interpreter does
+        Ok(format!("{main_function}\n\nvoid __run({name}())"))
     } else {
-        format!("const {name} = {body}")
+        Ok(format!("const {} = {body}", js_name(name)))
     }
 }
 
-fn gen_function_body(types: &mut Vec<TypeInfo>, body: &Expr) -> String {
+fn gen_function_body(types: &mut Option<TypeInfo>, body: &Expr) -> Result<String> {
     match body {
-        Expr::Closure(_, _body, _) => {
-            let count_types = types.len();
-            if count_types > 0 {
-                gen_expr(types, body, true)
-            } else {
-                gen_expr(types, body, true)
-            }
-        }
+        Expr::Closure(..) => gen_expr(types, body, true),
         _ => gen_expr(types, body, false),
     }
 }
 
-fn gen_expr(types: &mut Vec<TypeInfo>, expr: &Expr, ret: bool) -> String {
-    match expr {
+fn gen_expr(types: &mut Option<TypeInfo>, expr: &Expr, ret: bool) -> Result<String> {
+    let code = match expr {
         Expr::Atom(atom) => gen_atom(atom.clone()),
-        Expr::App(app) => gen_app(types, app.clone()),
-        Expr::Enum(_, _, _) => todo!(),
-        Expr::Closure(arg, body, _) => gen_closure(types, &arg, &body, ret),
-        Expr::Array(exprs, _, _) => gen_array(types, exprs),
-        Expr::Binary(bin) => gen_binary(types, bin),
-        Expr::Unary(unary) => gen_unary(types, unary),
+        Expr::App(app) => gen_app(types, app.clone())?,
+        Expr::Closure(arg, body, _) => gen_closure(types, arg, body, ret)?,
+        Expr::Array(exprs, _, _) => gen_array(types, exprs)?,
+        Expr::Binary(bin) => gen_binary(types, bin)?,
+        Expr::Unary(unary) => gen_unary(types, unary)?,
         Expr::IfElse(cond, if_true, if_false, _) => {
-            gen_conditional(types, cond, if_true, if_false)
+            gen_conditional(types, cond, if_true, if_false)?
         }
-        _ => todo!(),
-    }
+        Expr::Match(expr, arms, _) => gen_match(types, expr, arms)?,
+        Expr::Clauses(args, clauses, _) => gen_clauses(types, args, clauses)?,
+        Expr::Let(bindings, body, _) => gen_let(types, bindings, body)?,
+        Expr::Tuple(items, _) => gen_tuple(types, items)?,
+        Expr::Record(name, fields, _) => {
+            let fields = gen_fields(types, fields)?;
+            format!("({{ __type: {name:?}, {fields} }})")
+        }
+        Expr::Field(expr, field, _) => {
+            format!("__field({}, {field:?})", gen_expr(types, expr, false)?)
+        }
+        Expr::Update(expr, fields, _) => {
+            let expr = gen_expr(types, expr, false)?;
+            format!("({{ ...{expr}, {} }})", gen_fields(types, fields)?)
+        }
+        Expr::Enum(.., span)
+        | Expr::Data(.., span)
+        | Expr::Alias(.., span)
+        | Expr::Func(.., span)
+        | Expr::Error(span) => {
+            return Err(CodeGenError::Unsupported(
+                "nested declarations".into(),
+                "js",
+                *span,
+            ));
+        }
+    };
+    Ok(code)
 }
 
 fn gen_conditional(
-    types: &mut Vec<TypeInfo>,
+    types: &mut Option<TypeInfo>,
     cond: &Expr,
     truth: &Expr,
     falsy: &Expr,
-) -> String {
-    let cond = gen_expr(types, cond, false);
-    let truth = gen_expr(types, truth, false);
-    let falsy = gen_expr(types, falsy, false);
-    format!("({cond} ? {truth} : {falsy})")
+) -> Result<String> {
+    let cond = gen_expr(types, cond, false)?;
+    let truth = gen_expr(types, truth, false)?;
+    let falsy = gen_expr(types, falsy, false)?;
+    Ok(format!("({cond} ? {truth} : {falsy})"))
 }
 
-fn gen_unary(types: &mut Vec<TypeInfo>, unary: &Unary) -> String {
+/// Each arm becomes an `if` inside of an arrow function which is called with
+/// the value being matched on.
+fn gen_match(
+    types: &mut Option<TypeInfo>,
+    expr: &Expr,
+    arms: &[(Pattern, Expr)],
+) -> Result<String> {
+    let value = gen_expr(types, expr, false)?;
+    let mut body = String::new();
+    for (pattern, arm) in arms {
+        let mut conditions = vec![];
+        let mut bindings = vec![];
+        gen_pattern(pattern, "__match".into(), &mut conditions, &mut bindings);
+        let condition = if conditions.is_empty() {
+            "true".to_string()
+        } else {
+            conditions.join(" && ")
+        };
+        let arm = gen_expr(types, arm, false)?;
+        body += &format!(
+            "if ({condition}) {{ {} return {arm}; }}",
+            bindings.join(" ")
+        );
+    }
+    body += "throw new Error(\"no pattern matched the value\");";
+    Ok(format!("((__match) => {{ {body} }})({value})"))
+}
+
+/// Like a `match` on every argument at once, a clause whose guards all fail
+/// falls through to the next one.
+fn gen_clauses(
+    types: &mut Option<TypeInfo>,
+    args: &[Expr],
+    clauses: &[Clause],
+) -> Result<String> {
+    let args = args
+        .iter()
+        .map(|arg| gen_expr(types, arg, false))
+        .collect::<Result<Vec<_>>>()?;
+    let mut body = String::new();
+    for clause in clauses {
+        let mut conditions = vec![];
+        let mut bindings = vec![];
+        for (pattern, arg) in clause.patterns.iter().zip(args.iter()) {
+            gen_pattern(pattern, arg.clone(), &mut conditions, &mut bindings);
+        }
+        let condition = if conditions.is_empty() {
+            "true".to_string()
+        } else {
+            conditions.join(" && ")
+        };
+        // `where` bindings come after the patterns they may refer to
+        let mut arms = gen_bindings(types, &clause.bindings)?;
+        for (guard, arm) in clause.bodies.iter() {
+            let arm = gen_expr(types, arm, false)?;
+            match guard {
+                Some(guard) => {
+                    let guard = gen_expr(types, guard, false)?;
+                    arms += &format!("if ({guard}) {{ return {arm}; }} ");
+                }
+                None => arms += &format!("return {arm}; "),
+            }
+        }
+        body += &format!("if ({condition}) {{ {} {arms}}}", bindings.join(" "));
+    }
+    body += "throw new Error(\"no clause matched the arguments\");";
+    Ok(format!("(() => {{ {body} }})()"))
+}
+
+/// The bindings become `const`s inside of an arrow function which is called
+/// right away, so they are only visible to the body.
+fn gen_let(
+    types: &mut Option<TypeInfo>,
+    bindings: &[Expr],
+    body: &Expr,
+) -> Result<String> {
+    let stmts = gen_bindings(types, bindings)?;
+    let body = gen_expr(types, body, false)?;
+    Ok(format!("(() => {{ {stmts}return {body}; }})()"))
+}
+
+fn gen_bindings(types: &mut Option<TypeInfo>, bindings: &[Expr]) -> Result<String> {
+    let mut stmts = String::new();
+    for binding in bindings {
+        let Expr::Func(name, _, value, ..) = binding else {
+            unreachable!("let binding '{binding}'");
+        };
+        let value = gen_function_body(types, value)?;
+        stmts += &format!("const {} = {value}; ", js_name(name));
+    }
+    Ok(stmts)
+}
+
+fn gen_pattern(
+    pattern: &Pattern,
+    value: String,
+    conditions: &mut Vec<String>,
+    bindings: &mut Vec<String>,
+) {
+    match pattern {
+        Pattern::Wildcard(_) => {}
+        Pattern::Id(name, _) => bindings.push(format!("const {name} = {value};")),
+        Pattern::Literal(atom) => {
+            conditions.push(format!("{value} === {}", gen_atom(atom.clone())))
+        }
+        Pattern::Constructor(name, args, _) => {
+            conditions.push(format!("{value}.tag === \"{name}\""));
+            for (idx, arg) in args.iter().enumerate() {
+                gen_pattern(arg, format!("{value}.values[{idx}]"), conditions, bindings);
+            }
+        }
+        Pattern::Tuple(items, _) => {
+            for (idx, item) in items.iter().enumerate() {
+                gen_pattern(
+                    item,
+                    format!("{value}.__tuple[{idx}]"),
+                    conditions,
+                    bindings,
+                );
+            }
+        }
+        Pattern::Array(items, rest, _) => {
+            let len = items.len();
+            conditions.push(format!("Array.isArray({value})"));
+            match rest {
+                Some(_) => conditions.push(format!("{value}.length >= {len}")),
+                None => conditions.push(format!("{value}.length === {len}")),
+            }
+            for (idx, item) in items.iter().enumerate() {
+                gen_pattern(item, format!("{value}[{idx}]"), conditions, bindings);
+            }
+            if let Some(rest) = rest {
+                gen_pattern(rest, format!("{value}.slice({len})"), conditions, bindings);
+            }
+        }
+    }
+}
+
+fn gen_fields(types: &mut Option<TypeInfo>, fields: &[(String, Expr)]) -> Result<String> {
+    let fields = fields
+        .iter()
+        .map(|(field, value)| Ok(format!("{field}: {}", gen_expr(types, value, false)?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(fields.join(", "))
+}
+
+fn gen_unary(types: &mut Option<TypeInfo>, unary: &Unary) -> Result<String> {
     let op = &unary.op;
-    let expr = gen_expr(types, &unary.expr, false);
-    format!("({op} {expr})")
+    let expr = gen_expr(types, &unary.expr, false)?;
+    Ok(match op {
+        Op::Minus => format!("__int({op} {expr})"),
+        _ => format!("({op} {expr})"),
+    })
 }
 
-fn gen_binary(types: &mut Vec<TypeInfo>, binary: &Binary) -> String {
+fn gen_binary(types: &mut Option<TypeInfo>, binary: &Binary) -> Result<String> {
     let op = &binary.op;
-    let left = gen_expr(types, &binary.left, false);
-    let right = gen_expr(types, &binary.right, false);
-    format!("({left} {op} {right})")
+    let left = gen_expr(types, &binary.left, false)?;
+    let right = gen_expr(types, &binary.right, false)?;
+    Ok(match op {
+        Op::Div => format!("__div({left}, {right})"),
+        Op::Mod => format!("__mod({left}, {right})"),
+        Op::Plus => format!("__plus({left}, {right})"),
+        Op::Minus | Op::Mult => format!("__int({left} {op} {right})"),
+        Op::And => format!("({left} && {right})"),
+        Op::Or => format!("({left} || {right})"),
+        _ => format!("({left} {op} {right})"),
+    })
+}
+
+fn gen_array(types: &mut Option<TypeInfo>, exprs: &[Expr]) -> Result<String> {
+    let mut values = vec![];
+    for value in exprs {
+        values.push(gen_expr(types, value, false)?);
+    }
+
+    Ok(format!("[{}]", values.join(",")))
 }
 
-fn gen_array(types: &mut Vec<TypeInfo>, exprs: &[Expr]) -> String {
+fn gen_tuple(types: &mut Option<TypeInfo>, exprs: &[Expr]) -> Result<String> {
     let mut values = vec![];
     for value in exprs {
-        values.push(gen_expr(types, value, false));
+        values.push(gen_expr(types, value, false)?);
     }
 
-    format!("[{}]", values.join(","))
+    Ok(format!("__tuple({})", values.join(", ")))
 }
 
-fn gen_closure(types: &mut Vec<TypeInfo>, arg: &Expr, body: &Expr, ret: bool) -> String {
+fn gen_closure(
+    types: &mut Option<TypeInfo>,
+    arg: &Expr,
+    body: &Expr,
+    ret: bool,
+) -> Result<String> {
     //let arg_type = gen_top_arg_type(types);
-    let arg_value = gen_expr(types, &arg, ret);
-    let _body = gen_expr(types, body, false);
-    format!("({arg_value}) => {_body}")
+    let arg_value = gen_expr(types, arg, ret)?;
+    let _body = gen_expr(types, body, false)?;
+    Ok(format!("({arg_value}) => {_body}"))
 }
 
-fn gen_app(types: &mut Vec<TypeInfo>, app: App) -> String {
-    let name = &app.name;
+fn gen_app(types: &mut Option<TypeInfo>, app: App) -> Result<String> {
+    // `print` is variadic, so all of its arguments go into a single call.
+    if matches!(app.name.as_ref(), Expr::Atom(Atom::Id(name, ..)) if name == "print") {
+        let args = app
+            .args
+            .iter()
+            .map(|arg| gen_expr(types, arg, true))
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(format!("print({})", args.join(", ")));
+    }
+    let name = match app.name.as_ref() {
+        Expr::Atom(atom) => gen_atom(atom.clone()),
+        name => format!("({})", gen_expr(types, name, false)?),
+    };
     let mut args = vec![];
     for arg in app.args {
-        args.push(format!("({})", gen_expr(types, &arg, true)));
+        args.push(format!("({})", gen_expr(types, &arg, true)?));
     }
 
-    format!("{name}{}", args.join(""))
+    Ok(format!("{name}{}", args.join("")))
+}
+
+/// Operators are named after their symbols, `(<>)` becomes `__op_lt_gt`.
+fn js_name(name: &str) -> String {
+    if let Some((module, name)) = name.rsplit_once("::") {
+        return format!("{}__{}", module.replace(['.', '#'], "_"), js_name(name));
+    }
+    if method_name(name).is_some() {
+        return format!("__methods.{name}");
+    }
+    let Some(op) = name.strip_prefix('(').and_then(|n| n.strip_suffix(')')) else {
+        return name.to_string();
+    };
+    let symbols = op.chars().map(|c| match c {
+        '!' => "bang",
+        '#' => "hash",
+        '$' => "dollar",
+        '%' => "percent",
+        '&' => "amp",
+        '*' => "star",
+        '+' => "plus",
+        '.' => "dot",
+        '/' => "slash",
+        '<' => "lt",
+        '=' => "eq",
+        '>' => "gt",
+        '?' => "question",
+        '@' => "at",
+        '^' => "caret",
+        '|' => "bar",
+        '~' => "tilde",
+        '-' => "minus",
+        ':' => "colon",
+        _ => "_",
+    });
+    format!("__op_{}", symbols.collect::<Vec<_>>().join("_"))
 }
 
 fn gen_atom(atom: Atom) -> String {
     match atom {
-        Atom::Id(id, _, _) => id,
-        Atom::Int(int, _, _) => format!("{int}"),
+        Atom::Id(id, _, _) => js_name(&id),
+        Atom::Int(int, _, _) => format!("{int}n"),
         Atom::Bool(bool, _, _) => format!("{bool}"),
-        Atom::Char(char, _, _) => format!("{char}"),
-        Atom::Float(float, _, _) => format!("{float}"),
-        Atom::String(str, _, _) => format!("\"{str}\""),
+        Atom::Char(char, _, _) => format!("{:?}", char.to_string()),
+        Atom::Float(float, _, _) => format!("{float:?}"),
+        Atom::String(str, _, _) => format!("{str:?}"),
     }
 }
-
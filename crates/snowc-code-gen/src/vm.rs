@@ -0,0 +1,899 @@
+//! Lowers the Snow AST to `snowc-vm` bytecode.
+//!
+//! Every value lives in a single 32 bit register. The low two bits hold a tag
+//! so `print` and the closure dispatcher can tell values apart at runtime:
+//!
+//! | tag | value                                          |
+//! |-----|------------------------------------------------|
+//! | 0   | Int, stored as `n * 4`                         |
+//! | 1   | Bool, `5` is true and `1` is false             |
+//! | 2   | String, address in the data section `* 4 + 2`  |
+//! | 3   | Closure, offset of its heap record `* 4 + 3`   |
+//!
+//! Top level functions take their arguments in `%1..%n` and return in `%0`.
+//! Callers save the registers they still need on the stack. Lambdas are
+//! lifted out into their own functions and called through `.apply` with the
+//! closure record in `%1` and the argument in `%2`.
+use crate::CodeGenError;
+use snowc_parse::{is_primitive, App, Atom, Binary, Expr, Op, Pattern, Unary};
+use snowc_vm::{Data, Directive, Item, Label, Span, Text, Token, TokenOp};
+use std::{collections::HashMap, ops::RangeInclusive};
+
+type Result<T> = std::result::Result<T, CodeGenError>;
+
+/// Holds the return value of every call.
+const RET: u8 = 0;
+/// Last register free for temporaries, `%1` up to this one.
+const LAST_TEMP: u8 = 26;
+const SCRATCH2: u8 = 27;
+const SCRATCH: u8 = 28;
+/// Always holds `4`, loaded by the entry stub.
+const FOUR: u8 = 29;
+/// Never written to, used to move registers around.
+const ZERO: u8 = 30;
+/// Bump pointer into the heap.
+const HEAP: u8 = 31;
+
+/// Ints give up their low two bits to the tag, leaving 30 bits for the value.
+const INT_RANGE: RangeInclusive<i64> = -(1 << 29)..=(1 << 29) - 1;
+
+const TAG_STRING: u32 = 2;
+const TAG_CLOSURE: u32 = 3;
+const TRUE: u32 = 5;
+const FALSE: u32 = 1;
+
+/// The assembler puts the data section right after the 64 byte header.
+const DATA_START: usize = 64;
+
+const START: &str = ".start";
+const APPLY: &str = ".apply";
+const PRINT: &str = ".print";
+
+pub fn gen_code(input: &[Expr]) -> Result<Vec<u8>> {
+    let mut generator = Generator::default();
+    for expr in input.iter() {
+        if let Expr::Func(name, _, body, ..) = expr {
+            generator.globals.insert(name.clone(), arity(body));
+        }
+    }
+    if generator.globals.get("main") != Some(&0) {
+        return Err(CodeGenError::MissingMainFunction);
+    }
+    for name in [" ", "true", "false", "<closure>"] {
+        generator.string(name);
+    }
+
+    generator.label(START);
+    generator.load_const(FOUR, 4);
+    generator.emit(TokenOp::Call(label("main")));
+    generator.emit(TokenOp::Hlt);
+
+    for expr in input.iter() {
+        match expr {
+            Expr::Func(name, _, body, ..) => generator.function(name, body)?,
+            Expr::Enum(.., span) => {
+                return Err(CodeGenError::Unsupported("enums".into(), "vm", *span));
+            }
+            Expr::Data(.., span) => {
+                return Err(CodeGenError::Unsupported("records".into(), "vm", *span));
+            }
+            _ => {}
+        }
+    }
+    let mut idx = 0;
+    while idx < generator.lifted.len() {
+        generator.lifted(idx)?;
+        idx += 1;
+    }
+    generator.apply();
+    generator.print();
+
+    let program = vec![
+        Item::EntryPoint(Token::Id(START.into(), Span::default())),
+        Item::Data(generator.data),
+        Item::Text(generator.text),
+    ];
+    snowc_vm::assemble_from_ast(&program)
+        .map_err(|error| CodeGenError::Assembler(error.label))
+}
+
+/// Number of leading closures, which become arguments of the function.
+fn arity(mut body: &Expr) -> usize {
+    let mut count = 0;
+    while let Expr::Closure(_, tail, _) = body {
+        count += 1;
+        body = tail;
+    }
+    count
+}
+
+fn label(name: impl Into<String>) -> Label {
+    Label {
+        name: name.into(),
+        span: Span::default(),
+        def: false,
+    }
+}
+
+fn closure_param(head: &Expr) -> Result<String> {
+    match head {
+        Expr::Atom(Atom::Id(name, ..)) => Ok(name.clone()),
+        _ => Err(CodeGenError::Unsupported(
+            "patterns in closure parameters".into(),
+            "vm",
+            head.span(),
+        )),
+    }
+}
+
+/// Identifiers used in `expr` that are not bound inside of it, in order of
+/// first use.
+fn free_vars(expr: &Expr, bound: &mut Vec<String>, out: &mut Vec<String>) {
+    match expr {
+        Expr::Atom(Atom::Id(name, ..)) => {
+            if !bound.contains(name) && !out.contains(name) {
+                out.push(name.clone());
+            }
+        }
+        Expr::Atom(_)
+        | Expr::Enum(..)
+        | Expr::Data(..)
+        | Expr::Alias(..)
+        | Expr::Error(..) => {}
+        Expr::App(App { name, args, .. }) => {
+            free_vars(name, bound, out);
+            args.iter().for_each(|arg| free_vars(arg, bound, out));
+        }
+        Expr::Array(items, ..) | Expr::Tuple(items, ..) => {
+            items.iter().for_each(|item| free_vars(item, bound, out));
+        }
+        Expr::Record(_, fields, _) => {
+            fields
+                .iter()
+                .for_each(|(_, value)| free_vars(value, bound, out));
+        }
+        Expr::Field(expr, ..) => free_vars(expr, bound, out),
+        Expr::Update(expr, fields, _) => {
+            free_vars(expr, bound, out);
+            fields
+                .iter()
+                .for_each(|(_, value)| free_vars(value, bound, out));
+        }
+        Expr::Binary(Binary { left, right, .. }) => {
+            free_vars(left, bound, out);
+            free_vars(right, bound, out);
+        }
+        Expr::Unary(Unary { expr, .. }) => free_vars(expr, bound, out),
+        Expr::IfElse(condition, then, otherwise, ..) => {
+            free_vars(condition, bound, out);
+            free_vars(then, bound, out);
+            free_vars(otherwise, bound, out);
+        }
+        Expr::Closure(head, tail, ..) => {
+            let Expr::Atom(Atom::Id(name, ..)) = head.as_ref() else {
+                return;
+            };
+            bound.push(name.clone());
+            free_vars(tail, bound, out);
+            bound.pop();
+        }
+        Expr::Match(expr, arms, ..) => {
+            free_vars(expr, bound, out);
+            for (pattern, arm) in arms.iter() {
+                let len = bound.len();
+                pattern_vars(pattern, bound);
+                free_vars(arm, bound, out);
+                bound.truncate(len);
+            }
+        }
+        Expr::Clauses(args, clauses, ..) => {
+            args.iter().for_each(|arg| free_vars(arg, bound, out));
+            for clause in clauses.iter() {
+                let len = bound.len();
+                clause
+                    .patterns
+                    .iter()
+                    .for_each(|pattern| pattern_vars(pattern, bound));
+                for binding in clause.bindings.iter() {
+                    if let Expr::Func(name, ..) = binding {
+                        bound.push(name.clone());
+                    }
+                }
+                clause
+                    .bindings
+                    .iter()
+                    .for_each(|b| free_vars(b, bound, out));
+                for (guard, body) in clause.bodies.iter() {
+                    guard.iter().for_each(|guard| free_vars(guard, bound, out));
+                    free_vars(body, bound, out);
+                }
+                bound.truncate(len);
+            }
+        }
+        Expr::Let(bindings, body, ..) => {
+            let len = bound.len();
+            for binding in bindings.iter() {
+                if let Expr::Func(name, ..) = binding {
+                    bound.push(name.clone());
+                }
+            }
+            bindings.iter().for_each(|b| free_vars(b, bound, out));
+            free_vars(body, bound, out);
+            bound.truncate(len);
+        }
+        Expr::Func(_, _, body, ..) => free_vars(body, bound, out),
+    }
+}
+
+fn pattern_vars(pattern: &Pattern, bound: &mut Vec<String>) {
+    match pattern {
+        Pattern::Id(name, ..) => bound.push(name.clone()),
+        Pattern::Wildcard(..) | Pattern::Literal(..) => {}
+        Pattern::Constructor(_, args, ..) | Pattern::Tuple(args, ..) => {
+            args.iter().for_each(|arg| pattern_vars(arg, bound))
+        }
+        Pattern::Array(items, rest, ..) => {
+            items.iter().for_each(|item| pattern_vars(item, bound));
+            if let Some(rest) = rest {
+                pattern_vars(rest, bound);
+            }
+        }
+    }
+}
+
+/// A function generated by the compiler which is only reachable through
+/// `.apply`.
+#[derive(Debug)]
+enum Lifted {
+    /// A lambda with the free variables it captured from its parent.
+    Lambda {
+        param: String,
+        captured: Vec<String>,
+        body: Expr,
+    },
+    /// Collects the arguments of a partially applied top level function.
+    Curry {
+        name: String,
+        arity: usize,
+        collected: usize,
+    },
+}
+
+impl Lifted {
+    fn label(&self, id: usize) -> String {
+        match self {
+            Self::Lambda { .. } => format!(".lambda.{id}"),
+            Self::Curry {
+                name, collected, ..
+            } => format!(".curry.{name}.{collected}"),
+        }
+    }
+}
+
+/// Variables in scope of the function being generated and the registers
+/// they live in.
+type Frame = HashMap<String, u8>;
+
+#[derive(Debug, Default)]
+struct Generator {
+    /// Top level functions and their arity.
+    globals: HashMap<String, usize>,
+    data: Vec<Data>,
+    strings: HashMap<String, (String, u32)>,
+    data_size: usize,
+    text: Vec<Text>,
+    lifted: Vec<Lifted>,
+    curries: HashMap<(String, usize), usize>,
+    label_count: usize,
+    pending_label: Option<String>,
+}
+
+impl Generator {
+    fn emit(&mut self, opcode: TokenOp) {
+        let text = match self.pending_label.take() {
+            Some(name) => Text::new_opcode_with_label(name, opcode),
+            None => Text::new_opcode(opcode),
+        };
+        self.text.push(text);
+    }
+
+    fn label(&mut self, name: impl Into<String>) {
+        if self.pending_label.is_some() {
+            self.emit(TokenOp::Nop);
+        }
+        self.pending_label = Some(name.into());
+    }
+
+    fn new_label(&mut self) -> String {
+        self.label_count += 1;
+        format!(".L{}", self.label_count)
+    }
+
+    /// Adds `value` to the data section once and returns its data label and
+    /// address.
+    fn string(&mut self, value: &str) -> (String, u32) {
+        if let Some(entry) = self.strings.get(value) {
+            return entry.clone();
+        }
+        let name = format!(".str.{}", self.strings.len());
+        let directive = Directive::Ascii(value.into());
+        let address = (DATA_START + self.data_size) as u32;
+        self.data_size += directive.size();
+        self.data.push(Data {
+            name: name.clone(),
+            directive,
+            span: Span::default(),
+        });
+        self.strings.insert(value.into(), (name.clone(), address));
+        (name, address)
+    }
+
+    fn load_const(&mut self, dst: u8, value: u32) {
+        let [_, _, hi, lo] = value.to_be_bytes();
+        if value <= u16::MAX as u32 {
+            self.emit(TokenOp::Load(dst, hi, lo));
+            return;
+        }
+        assert_ne!(dst, SCRATCH, "scratch register only holds small values");
+        let [upper_hi, upper_lo, ..] = value.to_be_bytes();
+        self.emit(TokenOp::Load(dst, upper_hi, upper_lo));
+        self.emit(TokenOp::Load(SCRATCH, 1, 0));
+        self.emit(TokenOp::Mul(dst, SCRATCH, dst));
+        self.emit(TokenOp::Mul(dst, SCRATCH, dst));
+        self.emit(TokenOp::Load(SCRATCH, hi, lo));
+        self.emit(TokenOp::Add(dst, SCRATCH, dst));
+    }
+
+    fn mov(&mut self, src: u8, dst: u8) {
+        if src != dst {
+            self.emit(TokenOp::Add(src, ZERO, dst));
+        }
+    }
+
+    /// Calls `name` with the arguments already stored in `dst..dst + argc`
+    /// and leaves the result in `dst`. Registers below `dst` are preserved.
+    fn call(&mut self, name: &str, dst: u8, argc: u8) {
+        for reg in 1..dst {
+            self.emit(TokenOp::Push(reg));
+        }
+        for i in 0..argc {
+            self.mov(dst + i, 1 + i);
+        }
+        self.emit(TokenOp::Call(label(name)));
+        for reg in (1..dst).rev() {
+            self.emit(TokenOp::Pop(reg));
+        }
+        self.mov(RET, dst);
+    }
+
+    /// Applies the closure in `dst` to the value in `dst + 1`.
+    fn call_apply(&mut self, dst: u8) {
+        self.call(APPLY, dst, 2);
+    }
+
+    /// Allocates a closure record for lifted function `id` holding the values
+    /// in `fields`, leaving the tagged closure in `dst`.
+    fn closure(&mut self, id: usize, fields: &[u8], dst: u8) {
+        self.load_const(SCRATCH, (fields.len() as u32 + 1) * 4);
+        self.emit(TokenOp::Aloc(SCRATCH));
+        self.mov(HEAP, dst);
+        self.emit(TokenOp::Add(HEAP, SCRATCH, HEAP));
+        self.load_const(SCRATCH, id as u32);
+        self.emit(TokenOp::Setm(dst, SCRATCH));
+        for (idx, field) in fields.iter().enumerate() {
+            self.load_const(SCRATCH, (idx as u32 + 1) * 4);
+            self.emit(TokenOp::Add(dst, SCRATCH, SCRATCH2));
+            self.emit(TokenOp::Setm(SCRATCH2, *field));
+        }
+        self.emit(TokenOp::Mul(dst, FOUR, dst));
+        self.load_const(SCRATCH, TAG_CLOSURE);
+        self.emit(TokenOp::Add(dst, SCRATCH, dst));
+    }
+
+    /// Loads the fields of the closure record pointed to by `%1` into
+    /// `%3..`.
+    fn load_fields(&mut self, count: usize) {
+        for idx in 0..count {
+            self.load_const(SCRATCH, (idx as u32 + 1) * 4);
+            self.emit(TokenOp::Add(1, SCRATCH, SCRATCH));
+            self.emit(TokenOp::LoadM(SCRATCH, 3 + idx as u8));
+        }
+    }
+
+    fn curry(&mut self, name: &str, collected: usize) -> usize {
+        let key = (name.to_string(), collected);
+        if let Some(id) = self.curries.get(&key) {
+            return *id;
+        }
+        let id = self.lifted.len();
+        self.lifted.push(Lifted::Curry {
+            name: name.into(),
+            arity: self.globals[name],
+            collected,
+        });
+        self.curries.insert(key, id);
+        id
+    }
+
+    fn function(&mut self, name: &str, body: &Expr) -> Result<()> {
+        let mut frame = Frame::new();
+        let mut body = body;
+        let mut reg = 1;
+        while let Expr::Closure(head, tail, span) = body {
+            if reg > LAST_TEMP {
+                return Err(CodeGenError::OutOfRegisters(*span));
+            }
+            frame.insert(closure_param(head)?, reg);
+            reg += 1;
+            body = tail;
+        }
+        self.label(name);
+        self.expr(body, &frame, reg)?;
+        self.mov(reg, RET);
+        self.emit(TokenOp::Ret);
+        Ok(())
+    }
+
+    fn lifted(&mut self, id: usize) -> Result<()> {
+        let name = self.lifted[id].label(id);
+        self.label(name);
+        match &self.lifted[id] {
+            Lifted::Lambda {
+                param,
+                captured,
+                body,
+            } => {
+                let (param, captured, body) =
+                    (param.clone(), captured.clone(), body.clone());
+                let dst = 3 + captured.len();
+                if dst > LAST_TEMP as usize {
+                    return Err(CodeGenError::OutOfRegisters(body.span()));
+                }
+                self.load_fields(captured.len());
+                let mut frame = Frame::new();
+                for (idx, name) in captured.into_iter().enumerate() {
+                    frame.insert(name, 3 + idx as u8);
+                }
+                frame.insert(param, 2);
+                self.expr(&body, &frame, dst as u8)?;
+                self.mov(dst as u8, RET);
+            }
+            &Lifted::Curry {
+                ref name,
+                arity,
+                collected,
+            } => {
+                let name = name.clone();
+                let args = 3 + collected as u8;
+                self.load_fields(collected);
+                self.mov(2, args);
+                if collected + 1 == arity {
+                    self.call(&name, 3, arity as u8);
+                    self.mov(3, RET);
+                } else {
+                    let next = self.curry(&name, collected + 1);
+                    let fields = (3..=args).collect::<Vec<_>>();
+                    self.closure(next, &fields, args + 1);
+                    self.mov(args + 1, RET);
+                }
+            }
+        }
+        self.emit(TokenOp::Ret);
+        Ok(())
+    }
+
+    /// Jumps to the lifted function whose id is stored in the closure record
+    /// in `%1`, replacing `%1` with the untagged record offset.
+    fn apply(&mut self) {
+        self.label(APPLY);
+        self.load_const(SCRATCH, TAG_CLOSURE);
+        self.emit(TokenOp::Sub(1, SCRATCH, 1));
+        self.emit(TokenOp::Div(1, FOUR, 1));
+        self.emit(TokenOp::LoadM(1, SCRATCH2));
+        for id in 0..self.lifted.len() {
+            let name = self.lifted[id].label(id);
+            self.load_const(SCRATCH, id as u32);
+            self.emit(TokenOp::Eq(SCRATCH2, SCRATCH));
+            self.emit(TokenOp::Jeq(label(name)));
+        }
+        self.emit(TokenOp::Hlt);
+    }
+
+    /// Prints the value in `%1` based on its tag.
+    fn print(&mut self) {
+        let (true_label, _) = self.string("true");
+        let (false_label, _) = self.string("false");
+        let (closure_label, _) = self.string("<closure>");
+        let [int, boolean, string, done] = [(); 4].map(|_| self.new_label());
+        self.label(PRINT);
+        self.emit(TokenOp::Mod(1, FOUR, SCRATCH));
+        self.emit(TokenOp::Eq(SCRATCH, ZERO));
+        self.emit(TokenOp::Jeq(label(&int)));
+        self.load_const(SCRATCH2, FALSE);
+        self.emit(TokenOp::Eq(SCRATCH, SCRATCH2));
+        self.emit(TokenOp::Jeq(label(&boolean)));
+        self.load_const(SCRATCH2, TAG_STRING);
+        self.emit(TokenOp::Eq(SCRATCH, SCRATCH2));
+        self.emit(TokenOp::Jeq(label(&string)));
+        self.emit(TokenOp::Prts(label(closure_label)));
+        self.emit(TokenOp::Ret);
+
+        self.label(int);
+        self.emit(TokenOp::Div(1, FOUR, 1));
+        self.emit(TokenOp::Prtn(1));
+        self.emit(TokenOp::Ret);
+
+        self.label(boolean);
+        self.load_const(SCRATCH, TRUE);
+        self.emit(TokenOp::Eq(1, SCRATCH));
+        self.emit(TokenOp::Jne(label(&done)));
+        self.emit(TokenOp::Prts(label(true_label)));
+        self.emit(TokenOp::Ret);
+        self.label(done);
+        self.emit(TokenOp::Prts(label(false_label)));
+        self.emit(TokenOp::Ret);
+
+        self.label(string);
+        self.emit(TokenOp::Div(1, FOUR, 1));
+        self.emit(TokenOp::Prtsr(1));
+        self.emit(TokenOp::Ret);
+    }
+
+    /// Generates `expr` leaving its value in `dst`. Registers above `dst`
+    /// may be clobbered.
+    fn expr(&mut self, expr: &Expr, frame: &Frame, dst: u8) -> Result<()> {
+        if dst > LAST_TEMP {
+            return Err(CodeGenError::OutOfRegisters(expr.span()));
+        }
+        match expr {
+            Expr::Atom(atom) => self.atom(atom, frame, dst),
+            Expr::App(app) => self.app(app, frame, dst),
+            Expr::Binary(binary) => self.binary(binary, frame, dst),
+            Expr::Unary(unary) => self.unary(unary, frame, dst),
+            Expr::IfElse(condition, then, otherwise, ..) => {
+                let otherwise_label = self.new_label();
+                let end = self.new_label();
+                self.expr(condition, frame, dst)?;
+                self.load_const(SCRATCH, TRUE);
+                self.emit(TokenOp::Eq(dst, SCRATCH));
+                self.emit(TokenOp::Jne(label(&otherwise_label)));
+                self.expr(then, frame, dst)?;
+                self.emit(TokenOp::Jmp(label(&end)));
+                self.label(otherwise_label);
+                self.expr(otherwise, frame, dst)?;
+                self.label(end);
+                Ok(())
+            }
+            Expr::Closure(head, tail, ..) => {
+                let param = closure_param(head)?;
+                let mut captured = vec![];
+                free_vars(expr, &mut vec![], &mut captured);
+                captured.retain(|name| frame.contains_key(name));
+                let fields = captured.iter().map(|name| frame[name]).collect::<Vec<_>>();
+                let id = self.lifted.len();
+                self.lifted.push(Lifted::Lambda {
+                    param,
+                    captured,
+                    body: tail.as_ref().clone(),
+                });
+                self.closure(id, &fields, dst);
+                Ok(())
+            }
+            Expr::Array(.., span) => {
+                Err(CodeGenError::Unsupported("arrays".into(), "vm", *span))
+            }
+            Expr::Match(.., span) => Err(CodeGenError::Unsupported(
+                "match expressions".into(),
+                "vm",
+                *span,
+            )),
+            Expr::Clauses(.., span) => Err(CodeGenError::Unsupported(
+                "functions with patterns or guards".into(),
+                "vm",
+                *span,
+            )),
+            Expr::Let(.., span) => Err(CodeGenError::Unsupported(
+                "let expressions".into(),
+                "vm",
+                *span,
+            )),
+            Expr::Tuple(.., span) => {
+                Err(CodeGenError::Unsupported("tuples".into(), "vm", *span))
+            }
+            Expr::Record(.., span) | Expr::Field(.., span) | Expr::Update(.., span) => {
+                Err(CodeGenError::Unsupported("records".into(), "vm", *span))
+            }
+            Expr::Enum(.., span)
+            | Expr::Data(.., span)
+            | Expr::Alias(.., span)
+            | Expr::Func(.., span)
+            | Expr::Error(span) => Err(CodeGenError::Unsupported(
+                "nested declarations".into(),
+                "vm",
+                *span,
+            )),
+        }
+    }
+
+    fn atom(&mut self, atom: &Atom, frame: &Frame, dst: u8) -> Result<()> {
+        match atom {
+            Atom::Int(int, _, span) if !INT_RANGE.contains(int) => {
+                return Err(CodeGenError::IntOutOfRange(*int, *span));
+            }
+            Atom::Int(int, ..) => self.load_const(dst, (*int as u32).wrapping_mul(4)),
+            Atom::Bool(boolean, ..) => {
+                self.load_const(dst, if *boolean { TRUE } else { FALSE })
+            }
+            Atom::String(string, ..) => {
+                let (_, address) = self.string(string);
+                self.load_const(dst, address * 4 + TAG_STRING);
+            }
+            Atom::Float(_, _, span) => {
+                return Err(CodeGenError::Unsupported("floats".into(), "vm", *span));
+            }
+            Atom::Char(_, _, span) => {
+                return Err(CodeGenError::Unsupported("chars".into(), "vm", *span));
+            }
+            Atom::Id(name, _, span) => {
+                if let Some(reg) = frame.get(name) {
+                    self.mov(*reg, dst);
+                    return Ok(());
+                }
+                match self.globals.get(name) {
+                    Some(0) => self.call(name, dst, 0),
+                    Some(_) => {
+                        let id = self.curry(name, 0);
+                        self.closure(id, &[], dst);
+                    }
+                    None => {
+                        return Err(CodeGenError::Undefined(name.clone(), *span));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn app(&mut self, app: &App, frame: &Frame, dst: u8) -> Result<()> {
+        let App { name, args, .. } = app;
+        let global = match name.as_ref() {
+            Expr::Atom(Atom::Id(name, ..))
+                if !frame.contains_key(name) || is_primitive(name) =>
+            {
+                Some(name.as_str())
+            }
+            _ => None,
+        };
+        if global == Some("print") && !self.globals.contains_key("print") {
+            return self.print_call(args, frame, dst);
+        }
+        // ranges and list comprehensions build arrays and `print` runs
+        // right away, so actions can not be passed around or sequenced
+        let feature = match global {
+            Some(name) if self.globals.contains_key(name) && !is_primitive(name) => None,
+            Some("__range") => Some("ranges"),
+            Some("__map" | "__filter" | "__concat") => Some("list comprehensions"),
+            Some("__bind") => Some("do blocks"),
+            Some("pure") => Some("pure"),
+            _ => None,
+        };
+        if let Some(feature) = feature {
+            return Err(CodeGenError::Unsupported(feature.into(), "vm", app.span));
+        }
+        let mut rest = &args[..];
+        match global.and_then(|name| Some((name, *self.globals.get(name)?))) {
+            Some((name, arity)) if arity > 0 && args.len() >= arity => {
+                for (idx, arg) in args[..arity].iter().enumerate() {
+                    self.expr(arg, frame, dst + idx as u8)?;
+                }
+                self.call(name, dst, arity as u8);
+                rest = &args[arity..];
+            }
+            _ => self.expr(name, frame, dst)?,
+        }
+        for arg in rest.iter() {
+            self.expr(arg, frame, dst + 1)?;
+            self.call_apply(dst);
+        }
+        Ok(())
+    }
+
+    fn print_call(&mut self, args: &[Expr], frame: &Frame, dst: u8) -> Result<()> {
+        let (space, _) = self.string(" ");
+        for (idx, arg) in args.iter().enumerate() {
+            self.expr(arg, frame, dst + idx as u8)?;
+        }
+        let last = dst + args.len() as u8 - 1;
+        for reg in dst..=last {
+            if reg != dst {
+                self.emit(TokenOp::Prts(label(&space)));
+            }
+            for saved in 1..=last {
+                self.emit(TokenOp::Push(saved));
+            }
+            self.mov(reg, 1);
+            self.emit(TokenOp::Call(label(PRINT)));
+            for saved in (1..=last).rev() {
+                self.emit(TokenOp::Pop(saved));
+            }
+        }
+        Ok(())
+    }
+
+    fn binary(&mut self, binary: &Binary, frame: &Frame, dst: u8) -> Result<()> {
+        let Binary {
+            op,
+            left,
+            right,
+            span,
+            ..
+        } = binary;
+        if let Op::And | Op::Or = op {
+            let end = self.new_label();
+            self.expr(left, frame, dst)?;
+            self.load_const(SCRATCH, TRUE);
+            self.emit(TokenOp::Eq(dst, SCRATCH));
+            self.emit(match op {
+                Op::And => TokenOp::Jne(label(&end)),
+                _ => TokenOp::Jeq(label(&end)),
+            });
+            self.expr(right, frame, dst)?;
+            self.label(end);
+            return Ok(());
+        }
+        let is_string = |expr: &Expr| matches!(expr, Expr::Atom(Atom::String(..)));
+        if *op == Op::Plus && (is_string(left) || is_string(right)) {
+            return Err(CodeGenError::Unsupported(
+                "string concatenations".into(),
+                "vm",
+                *span,
+            ));
+        }
+        let rhs = dst + 1;
+        self.expr(left, frame, dst)?;
+        self.expr(right, frame, rhs)?;
+        let compare = match op {
+            Op::Plus => {
+                self.emit(TokenOp::Add(dst, rhs, dst));
+                return Ok(());
+            }
+            Op::Minus => {
+                self.emit(TokenOp::Sub(dst, rhs, dst));
+                return Ok(());
+            }
+            Op::Mult => {
+                self.emit(TokenOp::Div(rhs, FOUR, rhs));
+                self.emit(TokenOp::Mul(dst, rhs, dst));
+                return Ok(());
+            }
+            Op::Div => {
+                self.emit(TokenOp::Div(dst, rhs, dst));
+                self.emit(TokenOp::Mul(dst, FOUR, dst));
+                return Ok(());
+            }
+            Op::Mod => {
+                self.emit(TokenOp::Mod(dst, rhs, dst));
+                return Ok(());
+            }
+            Op::Eq => TokenOp::Eq(dst, rhs),
+            Op::Neq => TokenOp::Neq(dst, rhs),
+            Op::Grt => TokenOp::Gt(dst, rhs),
+            Op::GrtEq => TokenOp::Geq(dst, rhs),
+            Op::Les => TokenOp::Lt(dst, rhs),
+            Op::LesEq => TokenOp::Leq(dst, rhs),
+            _ => {
+                return Err(CodeGenError::Unsupported(
+                    format!("`{op}` operators"),
+                    "vm",
+                    *span,
+                ));
+            }
+        };
+        let end = self.new_label();
+        self.emit(compare);
+        self.load_const(dst, TRUE);
+        self.emit(TokenOp::Jeq(label(&end)));
+        self.load_const(dst, FALSE);
+        self.label(end);
+        Ok(())
+    }
+
+    fn unary(&mut self, unary: &Unary, frame: &Frame, dst: u8) -> Result<()> {
+        let Unary { op, expr, span, .. } = unary;
+        self.expr(expr, frame, dst)?;
+        match op {
+            Op::Minus => self.emit(TokenOp::Sub(ZERO, dst, dst)),
+            Op::Not => {
+                self.load_const(SCRATCH, TRUE + FALSE);
+                self.emit(TokenOp::Sub(SCRATCH, dst, dst));
+            }
+            _ => {
+                return Err(CodeGenError::Unsupported(
+                    format!("`{op}` operators"),
+                    "vm",
+                    *span,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use snowc_parse::parse;
+
+    fn compile(src: &str) -> Result<Vec<u8>> {
+        let ast = parse(src).expect("failed to parse");
+        gen_code(&ast)
+    }
+
+    #[test]
+    fn gen_code_recursion_and_closures() {
+        let src = r#"
+fact n = if n < 2 then 1 else n * fact (n - 1)
+
+add x y = x + y
+
+twice f x = f (f x)
+
+main = print (fact 5) (twice (add 1) 2) (twice (\x -> x * 2) 3) true "done"
+"#;
+        assert!(compile(src).is_ok());
+    }
+
+    #[test]
+    fn gen_code_missing_main() {
+        let result = compile("add x y = x + y");
+        assert!(matches!(result, Err(CodeGenError::MissingMainFunction)));
+    }
+
+    #[test]
+    fn gen_code_unsupported_array() {
+        let result = compile("main = [1, 2, 3]");
+        assert!(matches!(result, Err(CodeGenError::Unsupported(..))));
+        let result = compile("main = [x | x <- [1..3]]");
+        assert!(matches!(result, Err(CodeGenError::Unsupported(..))));
+    }
+
+    #[test]
+    fn gen_code_report_line() {
+        let src = "main = print 1\n\nfoo = bar 2\n";
+        let error = compile(src).unwrap_err();
+        assert!(error.report("main.snow", src).contains("main.snow:3:7"));
+    }
+
+    #[test]
+    fn gen_code_division_by_zero() {
+        use snowc_vm::{Machine, RuntimeError};
+        for src in ["main = print (7 / 0)", "main = print (7 % 0)"] {
+            let program = compile(src).unwrap();
+            let result = Machine::new(program, false).run();
+            assert_eq!(result, Err(RuntimeError::DivisionByZero));
+        }
+    }
+
+    #[test]
+    fn gen_code_int_out_of_range() {
+        assert!(compile("main = print 536870911").is_ok());
+        let result = compile("main = print (600000000 + 1)");
+        assert!(matches!(
+            result,
+            Err(CodeGenError::IntOutOfRange(600000000, _))
+        ));
+    }
+
+    #[test]
+    fn gen_code_int_overflow() {
+        use snowc_vm::{Machine, RuntimeError};
+        let src = r#"
+square x = x * x
+
+main = print (536870911 + 1) (square 100000) (0 - 536870911 - 2)
+"#;
+        let program = compile(src).unwrap();
+        let result = Machine::new(program, false).run();
+        assert_eq!(result, Err(RuntimeError::IntOverflow));
+    }
+}
@@ -0,0 +1,176 @@
+use super::page_name;
+use snowc_parse::{Expr, Modules, Span, SyntaxTree};
+use snowc_tree_walker::walk;
+use snowc_type_checker::type_check;
+
+/// A fenced block of Snow code in the docs of a definition. Blocks marked
+/// with anything other than `snow` or `hs` are not examples.
+/// ````hs
+/// --| Adds one.
+/// --| ```
+/// --| main = print (inc 1)
+/// --| ```
+/// inc x = x + 1
+/// ````
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    /// The page of the module the example is from.
+    pub module: String,
+    /// The definition documented by the example.
+    pub name: String,
+    pub code: String,
+    filename: String,
+    /// The module with the example in place of its `main`.
+    program: String,
+}
+
+impl Example {
+    /// Runs the example as a program of its own, made of everything in its
+    /// module along with the example in place of the module's `main`. An
+    /// example without a `main` only has to type check. The errors come back
+    /// as reports ready to be printed.
+    pub fn run(&self) -> Result<(), Vec<String>> {
+        let mut modules = Modules::default();
+        let ast = modules
+            .load(&self.filename, &self.program)
+            .map_err(|errors| {
+                reports(&modules, &errors, |e| e.span(), |e, f, s| e.report(f, s))
+            })?;
+        type_check(&ast).map_err(|errors| {
+            reports(&modules, &errors, |e| e.span(), |e, f, s| e.report(f, s))
+        })?;
+        if !defines_main(&self.code) {
+            return Ok(());
+        }
+        walk(&ast).map_err(|errors| {
+            reports(&modules, &errors, |e| e.span(), |e, f, s| e.report(f, s))
+        })?;
+        Ok(())
+    }
+}
+
+/// Every example in the docs of `ast`, in the order they are written.
+pub fn examples(modules: &Modules, ast: &[Expr]) -> Vec<Example> {
+    let mut examples = Vec::new();
+    for definition in ast.iter() {
+        let (Expr::Func(name, _, _, Some(docs), span)
+        | Expr::Enum(name, _, _, Some(docs), span)) = definition
+        else {
+            continue;
+        };
+        let Some(source) = modules.source(*span) else {
+            continue;
+        };
+        let name = name
+            .strip_prefix(&format!("{}::", source.module))
+            .unwrap_or(name);
+        let program = without_main(&source.src);
+        for code in fenced(docs) {
+            examples.push(Example {
+                module: page_name(source),
+                name: name.to_string(),
+                program: format!("{program}\n\n{code}\n"),
+                filename: source.filename.clone(),
+                code,
+            });
+        }
+    }
+    examples
+}
+
+/// The code in every fenced block of Snow code.
+fn fenced(docs: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut block: Option<(bool, Vec<&str>)> = None;
+    for line in docs.lines() {
+        let Some(info) = line.trim_start().strip_prefix("```") else {
+            if let Some((_, lines)) = block.as_mut() {
+                lines.push(line);
+            }
+            continue;
+        };
+        match block.take() {
+            Some((true, lines)) => blocks.push(lines.join("\n")),
+            Some((false, _)) => {}
+            None => block = Some((matches!(info.trim(), "" | "snow" | "hs"), Vec::new())),
+        }
+    }
+    blocks
+}
+
+fn defines_main(src: &str) -> bool {
+    SyntaxTree::parse(src)
+        .items
+        .iter()
+        .any(|item| item.name() == Some("main"))
+}
+
+fn without_main(src: &str) -> String {
+    SyntaxTree::parse(src)
+        .items
+        .iter()
+        .filter(|item| item.name() != Some("main"))
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn reports<E>(
+    modules: &Modules,
+    errors: &[E],
+    span: impl Fn(&E) -> Span,
+    report: impl Fn(&E, &str, &str) -> String,
+) -> Vec<String> {
+    errors
+        .iter()
+        .map(|error| match modules.source(span(error)) {
+            Some(source) => report(error, &source.filename, &source.src),
+            None => report(error, "", ""),
+        })
+        .collect()
+}
+
+#[test]
+fn examples_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+--| Adds one.
+--| ```
+--| main = print (inc 1)
+--| ```
+--| ```text
+--| inc 1 = 2
+--| ```
+inc x = x + 1
+
+--| Only matches zero.
+--| ```snow
+--| main = print (zero 1)
+--| ```
+--| ```
+--| one : Int
+--| one = zero "one"
+--| ```
+zero 0 = 0
+
+main = print (inc (zero 0))
+"#;
+    let mut modules = Modules::default();
+    let ast = modules.load("samples/examples.snow", src).unwrap();
+    let examples = examples(&modules, &ast);
+    let found = examples
+        .iter()
+        .map(|example| (example.name.as_str(), example.code.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![
+            ("inc", "main = print (inc 1)"),
+            ("zero", "main = print (zero 1)"),
+            ("zero", "one : Int\none = zero \"one\""),
+        ]
+    );
+    assert_eq!(examples[0].module, "examples");
+    assert_eq!(examples[0].run(), Ok(()));
+    assert_eq!(examples[1].run().map_err(|errors| errors.len()), Err(1));
+    assert_eq!(examples[2].run().map_err(|errors| errors.len()), Err(1));
+}
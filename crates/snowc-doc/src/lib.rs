@@ -0,0 +1,166 @@
+mod example;
+mod render;
+pub use example::{examples, Example};
+pub use render::{html, markdown};
+use snowc_parse::{Expr, Modules, Source};
+use snowc_type_checker::Types;
+use std::path::Path;
+
+/// The documentation of one module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// The name of the module, the file being compiled goes by the name of
+    /// its file.
+    pub name: String,
+    pub filename: String,
+    pub entries: Vec<Entry>,
+}
+
+/// A function or enum along with its `--|` docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The name without its module in front of it.
+    pub name: String,
+    pub kind: EntryKind,
+    /// `inc : Int -> Int` or `enum Option a = Some a | None`, a function
+    /// has none when it was neither given a signature nor type checked.
+    pub signature: Option<String>,
+    pub docs: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Function,
+    Enum,
+}
+
+/// One page for every module of the program in the order they were loaded,
+/// so the file being compiled comes first. Signatures that were left out are
+/// filled in from `types` when the program type checked.
+pub fn pages(modules: &Modules, ast: &[Expr], types: Option<&Types>) -> Vec<Page> {
+    modules
+        .sources()
+        .iter()
+        .enumerate()
+        .map(|(file, source)| Page {
+            name: page_name(source),
+            filename: source.filename.clone(),
+            entries: ast
+                .iter()
+                .filter(|definition| definition.span().file == file)
+                .filter_map(|definition| entry(definition, &source.module, types))
+                .collect(),
+        })
+        .collect()
+}
+
+pub(crate) fn page_name(source: &Source) -> String {
+    if !source.module.is_empty() {
+        return source.module.clone();
+    }
+    Path::new(&source.filename)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| source.filename.clone())
+}
+
+fn entry(definition: &Expr, module: &str, types: Option<&Types>) -> Option<Entry> {
+    match definition {
+        Expr::Func(qualified, signature, _, docs, _) => {
+            let name = qualified
+                .strip_prefix(&format!("{module}::"))
+                .unwrap_or(qualified);
+            let signature = signature
+                .as_ref()
+                .map(ToString::to_string)
+                .or_else(|| Some(types?.get(qualified)?.to_string()));
+            // Operators are named `(<>)` and written `` `<>` ``.
+            let (name, shown) =
+                match name.strip_prefix('(').and_then(|n| n.strip_suffix(')')) {
+                    Some(op) => (op.to_string(), format!("`{op}`")),
+                    None => (name.to_string(), name.to_string()),
+                };
+            Some(Entry {
+                signature: signature.map(|signature| format!("{shown} : {signature}")),
+                name,
+                kind: EntryKind::Function,
+                docs: docs.clone(),
+            })
+        }
+        Expr::Enum(name, params, variants, docs, _) => {
+            let head = params.iter().fold(format!("enum {name}"), |head, param| {
+                format!("{head} {param}")
+            });
+            let variants = variants
+                .iter()
+                .map(|(variant, fields)| {
+                    fields.iter().fold(variant.clone(), |variant, field| {
+                        match field.contains(' ') {
+                            true => format!("{variant} ({field})"),
+                            false => format!("{variant} {field}"),
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            Some(Entry {
+                name: name.clone(),
+                kind: EntryKind::Enum,
+                signature: Some(format!("{head} = {}", variants.join(" | "))),
+                docs: docs.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn pages_test() {
+    use pretty_assertions::assert_eq;
+    use snowc_type_checker::type_check;
+    let src = r#"
+--| An optional value.
+enum Option a = Some a | None
+
+-- Not a doc comment.
+--| Adds one.
+inc x = x + 1
+
+--| Joins two arrays.
+`<+>` xs ys : Array<a> -> Array<a> -> Array<a> = xs + ys
+
+--| Picks the value out of `o`.
+withDefault d o : a -> Option a -> a = match o on Some x -> x | None -> d
+
+main = print (inc 1)
+"#;
+    let mut modules = Modules::default();
+    let ast = modules.load("samples/options.snow", src).unwrap();
+    let types = type_check(&ast).unwrap();
+    let pages = pages(&modules, &ast, Some(&types));
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].name, "options");
+    let entries = pages[0]
+        .entries
+        .iter()
+        .map(|entry| (entry.signature.as_deref(), entry.docs.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        vec![
+            (
+                Some("enum Option a = Some a | None"),
+                Some("An optional value.")
+            ),
+            (Some("inc : Int -> Int"), Some("Adds one.")),
+            (
+                Some("`<+>` : Array<a> -> Array<a> -> Array<a>"),
+                Some("Joins two arrays.")
+            ),
+            (
+                Some("withDefault : a -> Option a -> a"),
+                Some("Picks the value out of `o`.")
+            ),
+            (Some("main : IO Int"), None),
+        ]
+    );
+}
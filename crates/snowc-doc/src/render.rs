@@ -0,0 +1,218 @@
+use super::{EntryKind, Page};
+use std::collections::HashMap;
+
+/// Renders `page` as Markdown. Type names in signatures link to where they
+/// are documented, on this page or on another one of `pages`.
+pub fn markdown(page: &Page, pages: &[Page]) -> String {
+    let types = types(pages);
+    let mut out = format!("# {}\n\n", page.name);
+    out += &format!("{}\n", index(pages, "md"));
+    for entry in page.entries.iter() {
+        let id = escape(&entry.name);
+        out += &format!("\n## <a id=\"{id}\"></a>{id}\n\n");
+        if let Some(signature) = entry.signature.as_ref() {
+            let signature = link_types(signature, &types, &page.name, "md");
+            out += &format!("<pre><code>{signature}</code></pre>\n\n");
+        }
+        if let Some(docs) = entry.docs.as_ref() {
+            out += &format!("{docs}\n");
+        }
+    }
+    out
+}
+
+/// Renders `page` as a standalone HTML document, linking type names the
+/// same way as [`markdown`].
+pub fn html(page: &Page, pages: &[Page]) -> String {
+    let types = types(pages);
+    let name = escape(&page.name);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{name}</title>\n</head>\n<body>\n<h1>{name}</h1>\n"
+    );
+    out += &format!("<nav>{}</nav>\n", index(pages, "html"));
+    for entry in page.entries.iter() {
+        let id = escape(&entry.name);
+        out += &format!("<section id=\"{id}\">\n<h2>{id}</h2>\n");
+        if let Some(signature) = entry.signature.as_ref() {
+            let signature = link_types(signature, &types, &page.name, "html");
+            out += &format!("<pre><code>{signature}</code></pre>\n");
+        }
+        if let Some(docs) = entry.docs.as_ref() {
+            out += &docs_html(docs);
+        }
+        out += "</section>\n";
+    }
+    out += "</body>\n</html>\n";
+    out
+}
+
+/// Links to every page, `ext` is the extension of the rendered files.
+fn index(pages: &[Page], ext: &str) -> String {
+    pages
+        .iter()
+        .map(|page| format!("<a href=\"{0}.{ext}\">{0}</a>", escape(&page.name)))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// The page every documented type is on.
+fn types(pages: &[Page]) -> HashMap<&str, &str> {
+    pages
+        .iter()
+        .flat_map(|page| {
+            page.entries
+                .iter()
+                .filter(|entry| entry.kind == EntryKind::Enum)
+                .map(|entry| (entry.name.as_str(), page.name.as_str()))
+        })
+        .collect()
+}
+
+/// Escapes `signature` for HTML and turns every documented type name in it
+/// into a link.
+fn link_types(
+    signature: &str,
+    types: &HashMap<&str, &str>,
+    page: &str,
+    ext: &str,
+) -> String {
+    let mut out = String::new();
+    let mut word = String::new();
+    let flush = |word: &mut String, out: &mut String| {
+        match types.get(word.as_str()) {
+            Some(&on) if on == page => {
+                out.push_str(&format!("<a href=\"#{word}\">{word}</a>"))
+            }
+            Some(on) => out.push_str(&format!(
+                "<a href=\"{}.{ext}#{word}\">{word}</a>",
+                escape(on)
+            )),
+            None => out.push_str(word),
+        }
+        word.clear();
+    };
+    for c in signature.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+        flush(&mut word, &mut out);
+        out.push_str(&escape(&c.to_string()));
+    }
+    flush(&mut word, &mut out);
+    out
+}
+
+/// Docs are written in Markdown, this covers what they need: paragraphs,
+/// fenced code blocks and inline code.
+fn docs_html(docs: &str) -> String {
+    let mut out = String::new();
+    let mut paragraph = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+    for line in docs.lines() {
+        if line.trim_start().starts_with("```") {
+            match code.take() {
+                Some(lines) => {
+                    let lines = lines.iter().map(|line| escape(line)).collect::<Vec<_>>();
+                    out += &format!("<pre><code>{}</code></pre>\n", lines.join("\n"));
+                }
+                None => {
+                    out += &paragraph_html(&mut paragraph);
+                    code = Some(Vec::new());
+                }
+            }
+            continue;
+        }
+        match code.as_mut() {
+            Some(lines) => lines.push(line),
+            None if line.trim().is_empty() => out += &paragraph_html(&mut paragraph),
+            None => paragraph.push(line.trim()),
+        }
+    }
+    if let Some(lines) = code {
+        paragraph.extend(lines);
+    }
+    out += &paragraph_html(&mut paragraph);
+    out
+}
+
+fn paragraph_html(lines: &mut Vec<&str>) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let text = lines.join(" ");
+    lines.clear();
+    let inline = text
+        .split('`')
+        .enumerate()
+        .map(|(i, part)| match i % 2 {
+            0 => escape(part),
+            _ => format!("<code>{}</code>", escape(part)),
+        })
+        .collect::<String>();
+    format!("<p>{inline}</p>\n")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[test]
+fn render_test() {
+    use super::Entry;
+    use pretty_assertions::assert_eq;
+    let option = Entry {
+        name: "Option".into(),
+        kind: EntryKind::Enum,
+        signature: Some("enum Option a = Some a | None".into()),
+        docs: Some("An optional value.".into()),
+    };
+    let unwrap = Entry {
+        name: "unwrap".into(),
+        kind: EntryKind::Function,
+        signature: Some("unwrap : a -> Option a -> a".into()),
+        docs: Some(
+            "Gives `d` for `None`.\n\n```\nmain = print (unwrap 0 None)\n```".into(),
+        ),
+    };
+    let pages = vec![
+        Page {
+            name: "main".into(),
+            filename: "main.snow".into(),
+            entries: vec![unwrap],
+        },
+        Page {
+            name: "Data.Option".into(),
+            filename: "Data/Option.snow".into(),
+            entries: vec![option],
+        },
+    ];
+    assert_eq!(
+        markdown(&pages[0], &pages),
+        r##"# main
+
+<a href="main.md">main</a> | <a href="Data.Option.md">Data.Option</a>
+
+## <a id="unwrap"></a>unwrap
+
+<pre><code>unwrap : a -&gt; <a href="Data.Option.md#Option">Option</a> a -&gt; a</code></pre>
+
+Gives `d` for `None`.
+
+```
+main = print (unwrap 0 None)
+```
+"##
+    );
+    let html = html(&pages[1], &pages);
+    assert!(html.contains(
+        "<pre><code>enum <a href=\"#Option\">Option</a> a = Some a | None</code></pre>"
+    ));
+    assert_eq!(
+        docs_html("Gives `d` for\n`None`.\n\n```\nx -> y\n```"),
+        "<p>Gives <code>d</code> for <code>None</code>.</p>\n<pre><code>x -&gt; y</code></pre>\n"
+    );
+}
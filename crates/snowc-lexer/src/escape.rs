@@ -0,0 +1,54 @@
+use std::ops::Range;
+
+/// Reads the escape following a `\`, `next_if` takes the next char when it
+/// passes the check. Escapes are `\n`, `\t`, `\r`, `\0`, `\\`, `\"`, `\'`,
+/// `\{`, `\}` and `\u{...}` with the code point in hex.
+pub(crate) fn escape(
+    mut next_if: impl FnMut(&dyn Fn(char) -> bool) -> Option<char>,
+) -> Option<char> {
+    let c = next_if(&|_| true)?;
+    match c {
+        'n' => Some('\n'),
+        't' => Some('\t'),
+        'r' => Some('\r'),
+        '0' => Some('\0'),
+        '\\' | '"' | '\'' | '{' | '}' => Some(c),
+        'u' => {
+            next_if(&|c| c == '{')?;
+            let mut hex = String::new();
+            while let Some(c) = next_if(&|c| c.is_ascii_hexdigit()) {
+                hex.push(c);
+            }
+            next_if(&|c| c == '}')?;
+            if hex.is_empty() || hex.len() > 6 {
+                return None;
+            }
+            char::from_u32(u32::from_str_radix(&hex, 16).ok()?)
+        }
+        _ => None,
+    }
+}
+
+/// Replaces the escapes in `text` the same way a string literal does. A bad
+/// escape gives back where it is in `text`.
+pub fn unescape(text: &str) -> Result<String, Range<usize>> {
+    let mut chars = text.char_indices().peekable();
+    let mut out = String::new();
+    while let Some((idx, c)) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        let mut end = idx + 1;
+        let escaped = escape(|check| {
+            let (at, c) = chars.next_if(|(_, c)| check(*c))?;
+            end = at + c.len_utf8();
+            Some(c)
+        });
+        match escaped {
+            Some(c) => out.push(c),
+            None => return Err(idx..end),
+        }
+    }
+    Ok(out)
+}
@@ -1,9 +1,13 @@
+mod escape;
 mod scanner;
 mod span;
 mod token;
+mod trivia;
 
 pub use span::Span;
 
+pub use crate::escape::unescape;
 pub use crate::scanner::Scanner;
 pub use crate::token::TokenPosition;
-pub use crate::token::{Char, Ctrl, Error, Float, Ident, Int, KeyWord, Op, Str, Token};
+pub use crate::token::{Char, Ctrl, Error, Float, Ident, Int, KeyWord, Op, Part, Str, Token};
+pub use crate::trivia::{Lexeme, Lossless, Trivia};
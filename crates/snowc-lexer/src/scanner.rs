@@ -1,6 +1,7 @@
 use crate::token::TokenPosition;
 
-use super::{Char, Ctrl, Error, Float, Ident, Int, KeyWord, Op, Span, Str, Token};
+use super::escape::escape;
+use super::{Char, Ctrl, Error, Float, Ident, Int, KeyWord, Op, Part, Span, Str, Token};
 use std::iter::Peekable;
 use std::str::Chars;
 
@@ -22,16 +23,21 @@ impl<'a> Scanner<'a> {
         }
     }
 
+    /// Scans `src` as the module numbered `file`, see [`Span::file`].
+    pub fn with_file(src: &'a str, file: usize) -> Self {
+        let mut scanner = Self::new(src);
+        scanner.span.file = file;
+        scanner
+    }
+
     fn peek_char(&mut self) -> Option<&char> {
         self.src.peek()
     }
 
     fn next_char(&mut self) -> Option<char> {
-        let Some(ch) = self.src.next() else {
-            return None;
-        };
+        let ch = self.src.next()?;
         self.span.right_shift(ch);
-        self.last_chr_len = ch.to_string().as_bytes().len();
+        self.last_chr_len = ch.to_string().len();
         Some(ch)
     }
 
@@ -39,9 +45,7 @@ impl<'a> Scanner<'a> {
     where
         F: FnOnce(char) -> bool,
     {
-        let Some(c) = self.peek_char() else {
-            return None;
-        };
+        let c = self.peek_char()?;
         if func(*c) {
             return self.next_char();
         }
@@ -64,28 +68,87 @@ impl<'a> Scanner<'a> {
         span
     }
 
+    /// Numbers are kept the way they are written, `1_000`, `0xff`, `0b1010`,
+    /// `0o17`, `1.5` and `2.5e-3`. A prefixed number with a digit its base
+    /// does not have is an error.
     fn number(&mut self, c: char) -> Option<Token> {
         let mut lexme = c.to_string();
-        while let Some(c) = self.next_char_if(|c| c.is_ascii_digit() || c == '_') {
-            lexme.push(c);
+        let radix = match self.peek_char() {
+            Some('x') if c == '0' => Some(16),
+            Some('o') if c == '0' => Some(8),
+            Some('b') if c == '0' => Some(2),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            lexme.extend(self.next_char());
+            while let Some(c) =
+                self.next_char_if(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                lexme.push(c);
+            }
+            let digits = &lexme[2..];
+            let span = self.span();
+            if !digits.chars().all(|c| c.is_digit(radix) || c == '_')
+                || !digits.chars().any(|c| c.is_digit(radix))
+            {
+                return Some(error(&lexme, span));
+            }
+            let pos = self.get_token_position();
+            return Some(Token::Int(Int { lexme, pos, span }));
+        }
+        self.digits(&mut lexme);
+        let mut is_float = false;
+        if self.peek_char() == Some(&'.') && self.is_followed_by_digit(1) {
+            is_float = true;
+            lexme.extend(self.next_char());
+            self.digits(&mut lexme);
+        }
+        if matches!(self.peek_char(), Some('e' | 'E')) {
+            let sign = self.src.clone().nth(1).filter(|c| matches!(c, '+' | '-'));
+            if self.is_followed_by_digit(1 + sign.iter().count()) {
+                is_float = true;
+                lexme.extend(self.next_char());
+                if sign.is_some() {
+                    lexme.extend(self.next_char());
+                }
+                self.digits(&mut lexme);
+            }
         }
         let span = self.span();
         let pos = self.get_token_position();
-        if lexme.contains('.') {
+        if is_float {
             return Some(Token::Float(Float { lexme, pos, span }));
         }
         Some(Token::Int(Int { lexme, pos, span }))
     }
 
+    fn digits(&mut self, lexme: &mut String) {
+        while let Some(c) = self.next_char_if(|c| c.is_ascii_digit() || c == '_') {
+            lexme.push(c);
+        }
+    }
+
+    /// Whether the char `skip` chars ahead is a digit.
+    fn is_followed_by_digit(&self, skip: usize) -> bool {
+        self.src
+            .clone()
+            .nth(skip)
+            .is_some_and(|c| c.is_ascii_digit())
+    }
+
     fn ident(&mut self, c: char) -> Option<Token> {
         let mut lexme = c.to_string();
         while let Some(c) = self.next_char_if(|c| c.is_ascii_alphanumeric() || c == '_') {
             lexme.push(c);
         }
+        if lexme == "r" && self.is_raw_string() {
+            return self.raw_string();
+        }
         let span = self.span();
         let keywords = [
             "enum", "data", "type", "true", "false", "return", "let", "and", "or", "not",
-            "if", "then", "else", "fn", "mod",
+            "if", "then", "else", "fn", "mod", "match", "on", "in", "where", "infixl",
+            "infixr", "infix", "import", "do",
         ];
         let pos = self.get_token_position();
         if keywords.contains(&lexme.as_str()) {
@@ -94,27 +157,206 @@ impl<'a> Scanner<'a> {
         Some(Token::Ident(Ident { lexme, pos, span }))
     }
 
+    /// Strings can span lines. Escapes are replaced and the code in `{}` is
+    /// scanned along with the string, `"Hello {name}!"`.
     fn string(&mut self) -> Option<Token> {
+        let open = self.span;
+        let last_char = self.last_char;
+        let mut text = String::new();
+        let mut parts = Vec::new();
+        let mut bad_escape = None;
+        loop {
+            let Some(c) = self.next_char() else {
+                return Some(error("\"", open));
+            };
+            match c {
+                '"' => break,
+                '\\' => {
+                    let backslash = self.last_char_span();
+                    match escape(|check| self.next_char_if(check)) {
+                        Some(c) => text.push(c),
+                        None => {
+                            bad_escape.get_or_insert(Span::from((backslash, self.span)));
+                        }
+                    }
+                }
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    let Some(code) = self.interpolation() else {
+                        return Some(error("\"", open));
+                    };
+                    parts.push(Part::Code(code));
+                }
+                c => text.push(c),
+            }
+        }
+        self.last_char = last_char;
+        let pos = self.get_token_position();
+        let span = Span::from((open, self.span()));
+        if let Some(span) = bad_escape {
+            return Some(error("\\", span));
+        }
+        if parts.is_empty() {
+            let parts = Vec::new();
+            return Some(Token::Str(Str {
+                lexme: text,
+                parts,
+                pos,
+                span,
+            }));
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        let lexme = parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.as_str(),
+                Part::Code(_) => "{}",
+            })
+            .collect();
+        Some(Token::Str(Str {
+            lexme,
+            parts,
+            pos,
+            span,
+        }))
+    }
+
+    /// The tokens of the code in `{}` inside a string, up to its `}`.
+    fn interpolation(&mut self) -> Option<Vec<Token>> {
+        let mut tokens = Vec::new();
+        let mut depth = 0usize;
+        self.span.reset(None);
+        loop {
+            let c = self.next_char()?;
+            let token = self.parse(c)?;
+            match &token {
+                Token::Ctrl(Ctrl { lexme, .. }) if lexme == "}" && depth == 0 => {
+                    return Some(tokens);
+                }
+                Token::Ctrl(Ctrl { lexme, .. }) if lexme == "{" => depth += 1,
+                Token::Ctrl(Ctrl { lexme, .. }) if lexme == "}" => depth -= 1,
+                _ => {}
+            }
+            tokens.push(token);
+        }
+    }
+
+    /// `r"..."` keeps everything up to its closing quote the way it is
+    /// written, `#`s around the quotes let it hold quotes, `r#"say "hi""#`.
+    fn raw_string(&mut self) -> Option<Token> {
+        let mut hashes = 0;
+        while self.next_char_if(|c| c == '#').is_some() {
+            hashes += 1;
+        }
+        self.next_char();
+        let open = self.span;
+        let closing = format!("\"{}", "#".repeat(hashes));
         let mut lexme = String::new();
-        while let Some(c) = self.next_char_if(|c| c != '"') {
+        while !lexme.ends_with(&closing) {
+            let Some(c) = self.next_char() else {
+                return Some(error("r\"", open));
+            };
             lexme.push(c);
         }
-        self.next_char();
-        let lexme = lexme.replace("\\n", "\n");
+        lexme.truncate(lexme.len() - closing.len());
         let pos = self.get_token_position();
-        let span = self.span();
-        Some(Token::Str(Str { lexme, pos, span }))
+        let span = Span::from((open, self.span()));
+        let parts = Vec::new();
+        Some(Token::Str(Str {
+            lexme,
+            parts,
+            pos,
+            span,
+        }))
+    }
+
+    /// `r` followed by a quote, or by `#`s and a quote, starts a raw string.
+    fn is_raw_string(&self) -> bool {
+        let mut ahead = self.src.clone().skip_while(|c| *c == '#');
+        ahead.next() == Some('"')
     }
 
+    /// A char literal holds exactly one char once its escape is replaced and
+    /// ends on the line it starts on.
     fn chr(&mut self) -> Option<Token> {
+        let open = self.span;
+        let mut chars = Vec::new();
+        let mut bad_escape = None;
+        let mut closed = false;
+        while let Some(c) = self.next_char_if(|c| c != '\n') {
+            match c {
+                '\'' => {
+                    closed = true;
+                    break;
+                }
+                '\\' => {
+                    let backslash = self.last_char_span();
+                    match escape(|check| self.next_char_if(check)) {
+                        Some(c) => chars.push(c),
+                        None => {
+                            bad_escape.get_or_insert(Span::from((backslash, self.span)));
+                        }
+                    }
+                }
+                c => chars.push(c),
+            }
+        }
+        let pos = self.get_token_position();
+        let span = Span::from((open, self.span()));
+        if let Some(span) = bad_escape {
+            return Some(error("\\", span));
+        }
+        match chars[..] {
+            [c] if closed => Some(Token::Char(Char {
+                lexme: c.to_string(),
+                pos,
+                span,
+            })),
+            _ => Some(error("'", span)),
+        }
+    }
+
+    /// The span of the char that was just read.
+    fn last_char_span(&self) -> Span {
+        let mut span = self.span;
+        span.reset(Some(self.last_chr_len));
+        span
+    }
+
+    /// Symbols are taken as long as possible so new operators like `<>` or
+    /// `>>=` come out as a single token.
+    fn operator(&mut self, c: char) -> Option<Token> {
+        let mut lexme = c.to_string();
+        while let Some(c) = self.next_char_if(is_symbol) {
+            lexme.push(c);
+        }
+        let pos = self.get_token_position();
+        let span = self.span();
+        match lexme.as_str() {
+            "=" | "->" | ":" | "::" => Some(Token::Ctrl(Ctrl { lexme, pos, span })),
+            _ => Some(Token::Op(Op { lexme, pos, span })),
+        }
+    }
+
+    /// An operator in backticks names the function behind it, `` `<>` `` is
+    /// the identifier `(<>)`.
+    fn backtick(&mut self) -> Option<Token> {
         let mut lexme = String::new();
-        while let Some(c) = self.next_char_if(|c| c != '\'') {
+        while let Some(c) = self.next_char_if(|c| c != '`' && c != '\n') {
             lexme.push(c);
         }
-        self.next_char();
+        self.next_char_if(|c| c == '`');
         let pos = self.get_token_position();
         let span = self.span();
-        Some(Token::Char(Char { lexme, pos, span }))
+        if lexme.is_empty() || !lexme.chars().all(is_symbol) {
+            return Some(Token::Error(Error { lexme, pos, span }));
+        }
+        let lexme = format!("({lexme})");
+        Some(Token::Ident(Ident { lexme, pos, span }))
     }
 
     fn take_while(&mut self, expected: char) {
@@ -123,9 +365,29 @@ impl<'a> Scanner<'a> {
 
     fn comment(&mut self) -> Option<Token> {
         self.take_while('\n');
-        let Some(ch) = self.next_char() else {
-            return None;
-        };
+        let ch = self.next_char()?;
+        self.parse(ch)
+    }
+
+    /// Block comments nest, `{- a {- b -} c -}` is one comment. The token
+    /// after it is placed as if the comment was not there, and a comment that
+    /// is never closed comes out as an error on its `{-`.
+    fn block_comment(&mut self) -> Option<Token> {
+        self.next_char();
+        let open = self.span;
+        let mut depth = 1;
+        while depth > 0 {
+            let Some(c) = self.next_char() else {
+                return Some(error("{-", open));
+            };
+            match c {
+                '{' if self.next_char_if(|c| c == '-').is_some() => depth += 1,
+                '-' if self.next_char_if(|c| c == '}').is_some() => depth -= 1,
+                _ => {}
+            }
+        }
+        let ch = self.next_char()?;
+        self.span.reset(Some(self.last_chr_len));
         self.parse(ch)
     }
 
@@ -149,42 +411,13 @@ impl<'a> Scanner<'a> {
     fn parse(&mut self, ch: char) -> Option<Token> {
         match ch {
             n @ '0'..='9' => self.number(n),
-            i @ ('a'..='z' | 'A'..='Z') => self.ident(i),
+            i @ ('a'..='z' | 'A'..='Z' | '_') => self.ident(i),
             '"' => self.string(),
             '\'' => self.chr(),
             '-' if self.matched('-') => self.comment(),
-            '-' if self.matched('>') => self.token("->", |lexme, pos, span| {
-                Token::Ctrl(Ctrl { lexme, pos, span })
-            }),
-            '>' if self.matched('=') => {
-                self.token(">=", |lexme, pos, span| Token::Op(Op { lexme, pos, span }))
-            }
-            '<' if self.matched('=') => {
-                self.token("<=", |lexme, pos, span| Token::Op(Op { lexme, pos, span }))
-            }
-            '=' if self.matched('=') => {
-                self.token("==", |lexme, pos, span| Token::Op(Op { lexme, pos, span }))
-            }
-            '!' if self.matched('=') => {
-                self.token("!=", |lexme, pos, span| Token::Op(Op { lexme, pos, span }))
-            }
-            ':' if self.matched(':') => self.token("::", |lexme, pos, span| {
-                Token::Ctrl(Ctrl { lexme, pos, span })
-            }),
-            '&' => self.token("&", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '|' => self.token("|", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '-' => self.token("-", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '+' => self.token("+", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '*' => self.token("*", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '/' => self.token("/", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '>' => self.token(">", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '<' => self.token("<", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '=' => self.token("=", |lexme, pos, span| {
-                Token::Ctrl(Ctrl { lexme, pos, span })
-            }),
-            '!' => self.token("!", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '%' => self.token("%", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
-            '.' => self.token(".", |lexme, pos, span| Token::Op(Op { lexme, pos, span })),
+            '{' if self.matched('-') => self.block_comment(),
+            c if is_symbol(c) => self.operator(c),
+            '`' => self.backtick(),
             ',' => self.token(",", |lexme, pos, span| {
                 Token::Ctrl(Ctrl { lexme, pos, span })
             }),
@@ -220,9 +453,7 @@ impl<'a> Scanner<'a> {
             }),
             '\n' | '\r' | ' ' | '\0' => {
                 self.last_char = ch;
-                let Some(ch) = self.next_char() else {
-                    return None;
-                };
+                let ch = self.next_char()?;
                 self.span.reset(Some(self.last_chr_len));
                 self.parse(ch)
             }
@@ -235,6 +466,22 @@ impl<'a> Scanner<'a> {
     }
 }
 
+/// What went wrong is told by how `lexme` starts: `{-` is a block comment
+/// that is never closed, `"` or `r"` a string, `'` a char literal that does
+/// not hold one char, `\\` a bad escape and a digit a number with a digit its
+/// base does not have.
+fn error(lexme: &str, span: Span) -> Token {
+    Token::Error(Error {
+        lexme: lexme.to_string(),
+        pos: TokenPosition::Middle,
+        span,
+    })
+}
+
+fn is_symbol(c: char) -> bool {
+    "!#$%&*+./<=>?@^|~-:".contains(c)
+}
+
 impl<'a> Iterator for Scanner<'a> {
     type Item = Token;
     fn next(&mut self) -> Option<Self::Item> {
@@ -8,6 +8,9 @@ pub struct Span {
     pub row_end: usize,
     pub col_start: usize,
     pub col_end: usize,
+    /// The file the span points into when a program is made of several
+    /// modules, the file being compiled is `0`.
+    pub file: usize,
 }
 
 impl Span {
@@ -26,13 +29,14 @@ impl Span {
             row_end,
             col_start,
             col_end,
+            file: 0,
         }
     }
     pub fn right_shift(&mut self, ch: char) {
         if ch == '\0' {
             return;
         }
-        let len = ch.to_string().as_bytes().len();
+        let len = ch.to_string().len();
         if ch == '\n' {
             self.row_end += 1;
             self.col_end = 0;
@@ -57,6 +61,10 @@ impl Span {
     pub fn len(&self) -> usize {
         self.idx_end.saturating_sub(self.idx_start)
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 impl From<(Span, Span)> for Span {
@@ -67,7 +75,11 @@ impl From<(Span, Span)> for Span {
         let col_start: usize = x.col_start;
         let row_end: usize = y.row_end;
         let col_end: usize = y.col_end;
-        Self::new(idx_start, idx_end, row_start, row_end, col_start, col_end)
+        let span = Self::new(idx_start, idx_end, row_start, row_end, col_start, col_end);
+        Self {
+            file: x.file,
+            ..span
+        }
     }
 }
 
@@ -35,10 +35,34 @@ init_token!(Op);
 init_token!(Ctrl);
 init_token!(Int);
 init_token!(Float);
-init_token!(Str);
 init_token!(Char);
 init_token!(Error);
 
+/// A string literal with its escapes replaced. A string with code in `{}`
+/// has its pieces in `parts` and the text between its quotes as written in
+/// `lexme`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Str {
+    pub lexme: String,
+    pub parts: Vec<Part>,
+    pub pos: TokenPosition,
+    pub span: Span,
+}
+
+impl fmt::Display for Str {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lexme)
+    }
+}
+
+/// A piece of an interpolated string, `"Hello {name}!"` is the text `Hello `,
+/// the code `name` and the text `!`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    Text(String),
+    Code(Vec<Token>),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token {
     KeyWord(KeyWord),
@@ -110,15 +134,12 @@ impl fmt::Display for Token {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Default)]
 pub enum TokenPosition {
     Start,
     Middle,
+    #[default]
     End,
     FullSpan,
 }
 
-impl Default for TokenPosition {
-    fn default() -> Self {
-        Self::End
-    }
-}
@@ -0,0 +1,167 @@
+use super::{Scanner, Span, Token};
+use std::fmt;
+
+/// The parts of the source the parser never sees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trivia {
+    /// Spaces and line breaks, blank lines are kept as they are.
+    Whitespace(String, Span),
+    /// A `--` comment up to the end of its line or a `{- -}` block comment.
+    Comment(String, Span),
+}
+
+impl Trivia {
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Whitespace(text, _) | Self::Comment(text, _) => text,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Whitespace(_, span) | Self::Comment(_, span) => *span,
+        }
+    }
+
+    pub fn is_comment(&self) -> bool {
+        matches!(self, Self::Comment(..))
+    }
+
+    /// The number of line breaks in whitespace.
+    pub fn newlines(&self) -> usize {
+        match self {
+            Self::Whitespace(text, _) => text.matches('\n').count(),
+            Self::Comment(..) => 0,
+        }
+    }
+}
+
+impl fmt::Display for Trivia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+/// A token along with the trivia in front of it. `text` is the token the way
+/// it was written, so a string keeps its quotes and a backtick operator its
+/// backticks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexeme {
+    pub leading: Vec<Trivia>,
+    pub text: String,
+    pub token: Token,
+}
+
+impl fmt::Display for Lexeme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in self.leading.iter() {
+            write!(f, "{trivia}")?;
+        }
+        write!(f, "{}", self.text)
+    }
+}
+
+/// The tokens of [`Scanner`] without anything left out. Writing out every
+/// lexeme followed by the [`Lossless::trailing`] trivia gives back the
+/// source it was scanned from.
+#[derive(Debug, Clone)]
+pub struct Lossless<'a> {
+    src: &'a str,
+    scanner: Scanner<'a>,
+    /// Ends where the last token did.
+    end: Span,
+}
+
+impl<'a> Lossless<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Self::with_file(src, 0)
+    }
+
+    /// Scans `src` as the module numbered `file`, see [`Span::file`].
+    pub fn with_file(src: &'a str, file: usize) -> Self {
+        Self {
+            src,
+            scanner: Scanner::with_file(src, file),
+            end: Span {
+                file,
+                ..Span::default()
+            },
+        }
+    }
+
+    /// The trivia after the last token.
+    pub fn trailing(&self) -> Vec<Trivia> {
+        self.trivia(self.src.len())
+    }
+
+    /// Splits the source between the last token and `idx` into comments and
+    /// the whitespace around them.
+    fn trivia(&self, idx: usize) -> Vec<Trivia> {
+        let gap = self.src.get(self.end.idx_end..idx).unwrap_or_default();
+        let mut span = self.end;
+        let mut trivia = Vec::new();
+        let mut chars = gap.chars().peekable();
+        while let Some(c) = chars.next() {
+            span.reset(None);
+            let mut text = c.to_string();
+            span.right_shift(c);
+            if c == '{' && chars.next_if_eq(&'-').is_some() {
+                text.push('-');
+                span.right_shift('-');
+                let mut depth = 1;
+                while depth > 0 {
+                    let Some(c) = chars.next() else {
+                        break;
+                    };
+                    text.push(c);
+                    span.right_shift(c);
+                    let pair = match c {
+                        '{' => chars.next_if_eq(&'-'),
+                        '-' => chars.next_if_eq(&'}'),
+                        _ => None,
+                    };
+                    if let Some(next) = pair {
+                        text.push(next);
+                        span.right_shift(next);
+                        depth += if c == '{' { 1 } else { -1 };
+                    }
+                }
+                trivia.push(Trivia::Comment(text, span));
+                continue;
+            }
+            let is_comment = c == '-' && chars.peek() == Some(&'-');
+            while let Some(c) = chars.next_if(|next| {
+                if is_comment {
+                    *next != '\n'
+                } else {
+                    next.is_whitespace() || *next == '\0'
+                }
+            }) {
+                text.push(c);
+                span.right_shift(c);
+            }
+            trivia.push(if is_comment {
+                Trivia::Comment(text, span)
+            } else {
+                Trivia::Whitespace(text, span)
+            });
+        }
+        trivia
+    }
+}
+
+impl Iterator for Lossless<'_> {
+    type Item = Lexeme;
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.scanner.next()?;
+        let span = token.span();
+        let leading = self.trivia(span.idx_start);
+        let text = self.src.get(span.range()).unwrap_or_default().to_string();
+        self.end = span;
+        Some(Lexeme {
+            leading,
+            text,
+            token,
+        })
+    }
+}
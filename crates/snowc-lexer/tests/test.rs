@@ -38,3 +38,164 @@ macro_rules! snapshot {
 
 snapshot!(hello_world, "./../../../samples/hello_world.snow");
 snapshot!(rule110, "./../../../samples/rule110.snow");
+
+#[test]
+fn lossless_round_trip() {
+    use pretty_assertions::assert_eq;
+    use snowc_lexer::{Lossless, Trivia};
+    for src in [
+        include_str!("./../../../samples/hello_world.snow"),
+        include_str!("./../../../samples/rule110.snow"),
+        include_str!("./../../../samples/std.snow"),
+        "-- only a comment\n\n",
+        "main = print \"a\\nb\" 'c' -- done\n  `<>` λx\n\n\n",
+        "{- one {- two -}\n -} a {-}-} b\n{- open",
+        "f = \"Hi {name \"x\"}!\\t\" r#\"raw \"q\"\"# \"two\nlines\"",
+        "g = \"open {x\n",
+    ] {
+        let mut lossless = Lossless::new(src);
+        let mut out = String::new();
+        for lexeme in lossless.by_ref() {
+            out += &lexeme.to_string();
+        }
+        out.extend(lossless.trailing().iter().map(Trivia::to_string));
+        assert_eq!(out, src);
+    }
+    let lexemes = Lossless::new("a -- one\n\n-- two\nb").collect::<Vec<_>>();
+    let comments = lexemes[1]
+        .leading
+        .iter()
+        .filter(|t| t.is_comment())
+        .map(Trivia::text)
+        .collect::<Vec<_>>();
+    assert_eq!(comments, vec!["-- one", "-- two"]);
+    assert_eq!(lexemes[1].leading[2].newlines(), 2);
+}
+
+#[test]
+fn block_comments() {
+    use pretty_assertions::assert_eq;
+    use snowc_lexer::{Span, TokenPosition};
+    let src = "{- a {- nested -}\n-} x {- -}\n{- -}y";
+    let tokens = Scanner::new(src)
+        .map(|token| (token.to_string(), token.span(), *token.position()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        vec![
+            (
+                "x".into(),
+                Span::new(21, 22, 1, 1, 3, 4),
+                TokenPosition::Middle
+            ),
+            (
+                "y".into(),
+                Span::new(34, 35, 2, 2, 5, 6),
+                TokenPosition::Start
+            ),
+        ]
+    );
+    let tokens = Scanner::new("x\n  {- a {- b -}").collect::<Vec<_>>();
+    assert!(matches!(
+        &tokens[..],
+        [_, Token::Error(error)] if error.lexme == "{-" && error.span.range() == (4..6)
+    ));
+}
+
+#[test]
+fn strings() {
+    use pretty_assertions::assert_eq;
+    use snowc_lexer::{unescape, Part};
+    let lexmes = Scanner::new(
+        r##""tab\t\"q\" \\ \u{263A} \{" r"raw \n" r#"say "hi""# 'x' '\'' "a
+b""##,
+    )
+    .map(|token| token.to_string())
+    .collect::<Vec<_>>();
+    assert_eq!(
+        lexmes,
+        vec![
+            "tab\t\"q\" \\ \u{263A} {",
+            "raw \\n",
+            "say \"hi\"",
+            "x",
+            "'",
+            "a\nb"
+        ]
+    );
+    let tokens = Scanner::new("\"Hi {name}, {f {x}}!\" y").collect::<Vec<_>>();
+    let [Token::Str(string), y] = &tokens[..] else {
+        panic!("expected a string and an ident but found {tokens:?}");
+    };
+    assert_eq!(string.lexme, "Hi {}, {}!");
+    assert_eq!(string.span.range(), (0..21));
+    assert_eq!(y.span().range(), (22..23));
+    let parts = string
+        .parts
+        .iter()
+        .map(|part| match part {
+            Part::Text(text) => text.clone(),
+            Part::Code(tokens) => tokens.iter().map(|t| format!("<{t}>")).collect(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(parts, vec!["Hi ", "<name>", ", ", "<f><{><x><}>", "!"]);
+    let Part::Code(code) = &string.parts[1] else {
+        panic!("expected code but found {:?}", string.parts[1]);
+    };
+    assert_eq!(code[0].span().range(), (5..9));
+    let errors = ["\"open", "\"bad \\q\"", "'ab'", "''", "'a", "r#\"open\""].map(|src| {
+        match Scanner::new(src).next() {
+            Some(Token::Error(error)) => (error.lexme, error.span.range()),
+            token => panic!("expected an error for {src} but found {token:?}"),
+        }
+    });
+    assert_eq!(
+        errors,
+        [
+            ("\"".into(), (0..1)),
+            ("\\".into(), (5..7)),
+            ("'".into(), (0..4)),
+            ("'".into(), (0..2)),
+            ("'".into(), (0..2)),
+            ("r\"".into(), (0..3)),
+        ]
+    );
+    assert_eq!(unescape(r"a\n\u{41}"), Ok("a\nA".into()));
+    assert_eq!(unescape(r"a\u{110000}b"), Err(1..11));
+}
+
+#[test]
+fn numbers() {
+    use pretty_assertions::assert_eq;
+    let tokens =
+        Scanner::new("1_000 0xfF 0o17 0b1_0 1.5 2e10 2.5E-3 1..2 x.1 3e 0b12 0x")
+            .map(|token| match token {
+                Token::Int(int) => format!("int {}", int.lexme),
+                Token::Float(float) => format!("float {}", float.lexme),
+                Token::Error(error) => format!("error {}", error.lexme),
+                token => token.to_string(),
+            })
+            .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        vec![
+            "int 1_000",
+            "int 0xfF",
+            "int 0o17",
+            "int 0b1_0",
+            "float 1.5",
+            "float 2e10",
+            "float 2.5E-3",
+            "int 1",
+            "..",
+            "int 2",
+            "x",
+            ".",
+            "int 1",
+            "int 3",
+            "e",
+            "error 0b12",
+            "error 0x",
+        ]
+    );
+}
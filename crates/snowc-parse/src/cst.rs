@@ -0,0 +1,348 @@
+use crate::Expr;
+use snowc_lexer::{Ctrl, Ident, Lexeme, Lossless, Span, Token, Trivia};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A file exactly as it was written, comments, blank lines and all. Writing
+/// the tree back out gives the source it was parsed from, and the tokens
+/// left once the trivia is dropped are what the [`Expr`](crate::Expr) tree
+/// is parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxTree {
+    pub items: Vec<Item>,
+    /// Whatever follows the last item.
+    pub trailing: Vec<Trivia>,
+}
+
+impl SyntaxTree {
+    pub fn parse(src: &str) -> Self {
+        Self::from_lexemes(Lossless::new(src))
+    }
+
+    /// Parses `src` as the module numbered `file`, see [`Span::file`].
+    pub fn with_file(src: &str, file: usize) -> Self {
+        Self::from_lexemes(Lossless::with_file(src, file))
+    }
+
+    fn from_lexemes(mut lossless: Lossless) -> Self {
+        let mut items: Vec<Vec<Lexeme>> = Vec::new();
+        let mut column = None;
+        let mut depth = 0usize;
+        let mut last_row = None;
+        for lexeme in lossless.by_ref() {
+            let span = lexeme.token.span();
+            let column = *column.get_or_insert(span.col_start);
+            let first_on_line = last_row.is_none_or(|row| span.row_start > row);
+            last_row = Some(span.row_end);
+            match items.last_mut() {
+                Some(item) if depth > 0 || !first_on_line || span.col_start > column => {
+                    item.push(lexeme.clone())
+                }
+                _ => items.push(vec![lexeme.clone()]),
+            }
+            if is_open(&lexeme.token) {
+                depth += 1;
+            } else if is_close(&lexeme.token) {
+                depth = depth.saturating_sub(1);
+            }
+        }
+        Self {
+            items: items.into_iter().map(Item::new).collect(),
+            trailing: lossless.trailing(),
+        }
+    }
+
+    /// The tokens of the file without any trivia.
+    pub fn tokens(&self) -> Vec<Token> {
+        self.items
+            .iter()
+            .flat_map(Item::lexemes)
+            .map(|lexeme| lexeme.token.clone())
+            .collect()
+    }
+
+    /// Hands the docs of every item to the function or enum it defines. A
+    /// function written as several clauses keeps the docs of the first one
+    /// that has any.
+    pub(crate) fn document(&self, definitions: &mut [Expr]) {
+        let mut docs = HashMap::new();
+        for item in self.items.iter() {
+            if let (Some(name), Some(text)) = (item.defines(), item.docs()) {
+                docs.entry(name).or_insert(text);
+            }
+        }
+        for definition in definitions.iter_mut() {
+            if let Expr::Func(name, _, _, slot, _) | Expr::Enum(name, _, _, slot, _) =
+                definition
+            {
+                if let Some(text) = docs.get(name.as_str()) {
+                    *slot = Some(text.clone());
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in self.items.iter() {
+            write!(f, "{item}")?;
+        }
+        for trivia in self.trailing.iter() {
+            write!(f, "{trivia}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What a top level [`Item`] declares, going by its first token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    /// A clause or signature of a function, or anything that is not one of
+    /// the others.
+    Function,
+    Enum,
+    Data,
+    Alias,
+    Fixity,
+    Import,
+}
+
+/// A top level definition with the comments and blank lines in front of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub kind: ItemKind,
+    pub nodes: Vec<Node>,
+}
+
+impl Item {
+    fn new(lexemes: Vec<Lexeme>) -> Self {
+        let kind = match lexemes.first().map(|lexeme| lexeme.text.as_str()) {
+            Some("enum") => ItemKind::Enum,
+            Some("data") => ItemKind::Data,
+            Some("type") => ItemKind::Alias,
+            Some("infixl" | "infixr" | "infix") => ItemKind::Fixity,
+            Some("import") => ItemKind::Import,
+            _ => ItemKind::Function,
+        };
+        Self {
+            kind,
+            nodes: nodes(lexemes),
+        }
+    }
+
+    /// The name being defined, operators are named with their backticks.
+    pub fn name(&self) -> Option<&str> {
+        let mut lexemes = self.lexemes();
+        if matches!(self.kind, ItemKind::Enum | ItemKind::Data | ItemKind::Alias) {
+            lexemes.next();
+        }
+        lexemes
+            .next()
+            .filter(|lexeme| matches!(lexeme.token, Token::Ident(..)))
+            .map(|lexeme| lexeme.text.as_str())
+    }
+
+    /// The name the definition is known by in the [`Expr`] tree, operators
+    /// without their backticks.
+    fn defines(&self) -> Option<&str> {
+        let mut lexemes = self.lexemes();
+        if self.kind != ItemKind::Function {
+            lexemes.next();
+        }
+        match lexemes.next().map(|lexeme| &lexeme.token) {
+            Some(Token::Ident(Ident { lexme, .. })) => Some(lexme),
+            _ => None,
+        }
+    }
+
+    /// The `--|` comments right above the item, one line each without the
+    /// `--|`. A blank line or a plain comment in between cuts them off.
+    /// ```hs
+    /// --| Adds one to `x`.
+    /// inc x = x + 1
+    /// ```
+    pub fn docs(&self) -> Option<String> {
+        let mut lines = Vec::new();
+        for trivia in self.leading().iter().rev() {
+            match trivia {
+                Trivia::Comment(text, _) if text.starts_with("--|") => {
+                    let line = &text["--|".len()..];
+                    lines.push(line.strip_prefix(' ').unwrap_or(line).trim_end());
+                }
+                Trivia::Whitespace(..) if trivia.newlines() <= 1 => {}
+                _ => break,
+            }
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        lines.reverse();
+        Some(lines.join("\n"))
+    }
+
+    /// The trivia in front of the item.
+    pub fn leading(&self) -> &[Trivia] {
+        self.lexemes()
+            .next()
+            .map(|lexeme| lexeme.leading.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn lexemes(&self) -> impl Iterator<Item = &Lexeme> {
+        self.nodes.iter().flat_map(Node::lexemes)
+    }
+
+    pub fn span(&self) -> Span {
+        let mut lexemes = self.lexemes().map(|lexeme| lexeme.token.span());
+        let first = lexemes.next().unwrap_or_default();
+        Span::from((first, lexemes.last().unwrap_or(first)))
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in self.nodes.iter() {
+            write!(f, "{node}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A token, or everything from an opening bracket up to the one closing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    Token(Lexeme),
+    Group(Vec<Node>),
+}
+
+impl Node {
+    pub fn lexemes(&self) -> Box<dyn Iterator<Item = &Lexeme> + '_> {
+        match self {
+            Self::Token(lexeme) => Box::new(std::iter::once(lexeme)),
+            Self::Group(nodes) => Box::new(nodes.iter().flat_map(Node::lexemes)),
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Token(lexeme) => write!(f, "{lexeme}"),
+            Self::Group(nodes) => nodes.iter().try_for_each(|node| write!(f, "{node}")),
+        }
+    }
+}
+
+/// Nests the lexemes between brackets, a bracket left open runs to the end
+/// of the item.
+fn nodes(lexemes: Vec<Lexeme>) -> Vec<Node> {
+    let mut stack = vec![Vec::new()];
+    for lexeme in lexemes {
+        if is_open(&lexeme.token) {
+            stack.push(vec![Node::Token(lexeme)]);
+            continue;
+        }
+        let closes = is_close(&lexeme.token) && stack.len() > 1;
+        if let Some(nodes) = stack.last_mut() {
+            nodes.push(Node::Token(lexeme));
+        }
+        if closes {
+            let group = stack.pop().unwrap_or_default();
+            if let Some(nodes) = stack.last_mut() {
+                nodes.push(Node::Group(group));
+            }
+        }
+    }
+    while stack.len() > 1 {
+        let group = stack.pop().unwrap_or_default();
+        if let Some(nodes) = stack.last_mut() {
+            nodes.push(Node::Group(group));
+        }
+    }
+    stack.pop().unwrap_or_default()
+}
+
+fn is_open(token: &Token) -> bool {
+    matches!(token, Token::Ctrl(Ctrl { lexme, .. }) if matches!(lexme.as_str(), "(" | "[" | "{"))
+}
+
+fn is_close(token: &Token) -> bool {
+    matches!(token, Token::Ctrl(Ctrl { lexme, .. }) if matches!(lexme.as_str(), ")" | "]" | "}"))
+}
+
+#[test]
+fn syntax_tree_test() {
+    use crate::layout::layout;
+    use pretty_assertions::assert_eq;
+    use snowc_lexer::Scanner;
+    let src = include_str!("./../../../samples/std.snow");
+    let tree = SyntaxTree::parse(src);
+    assert_eq!(tree.to_string(), src);
+    assert_eq!(layout(tree.tokens()).0, layout(Scanner::new(src).collect()).0);
+
+    let src =
+        "-- Adds one.\ninc x = (x\n  + 1) -- inc\n\n\nenum Bit = On | Off\ninfixl 6 <>\n";
+    let tree = SyntaxTree::parse(src);
+    assert_eq!(tree.to_string(), src);
+    let items = tree
+        .items
+        .iter()
+        .map(|item| (item.kind, item.name()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        vec![
+            (ItemKind::Function, Some("inc")),
+            (ItemKind::Enum, Some("Bit")),
+            (ItemKind::Fixity, None),
+        ]
+    );
+    assert_eq!(tree.items[0].leading()[0].text(), "-- Adds one.");
+    assert!(matches!(tree.items[0].nodes[3], Node::Group(ref group) if group.len() == 5));
+    assert_eq!(tree.items[1].leading()[1].text(), "-- inc");
+    assert_eq!(tree.items[1].leading()[2].newlines(), 3);
+}
+
+#[test]
+fn docs_test() {
+    use crate::parse;
+    use pretty_assertions::assert_eq;
+    let src = r#"--| Not attached.
+
+--| Adds one,
+--|
+--|   to `x`.
+inc : Int -> Int
+inc x = x + 1
+
+--| Cut off by a plain comment.
+-- inc
+dec x = x - 1
+
+--| Two bits.
+enum Bit = On | Off
+
+--| Swaps.
+`<~>` (a, b) = (b, a)
+"#;
+    let docs = parse(src)
+        .unwrap()
+        .iter()
+        .map(|definition| match definition {
+            Expr::Func(name, _, _, docs, _) | Expr::Enum(name, _, _, docs, _) => {
+                (name.clone(), docs.clone())
+            }
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        docs,
+        vec![
+            ("inc".into(), Some("Adds one,\n\n  to `x`.".into())),
+            ("dec".into(), None),
+            ("Bit".into(), Some("Two bits.".into())),
+            ("(<~>)".into(), Some("Swaps.".into())),
+        ]
+    );
+}
@@ -35,6 +35,48 @@ pub enum Error {
     UnexpectedEndOfInput(Span),
     #[error("unclosed parenthesis {0:?}")]
     UnclosedParen(Span),
+    #[error("block comment is never closed")]
+    UnclosedComment(Span),
+    #[error("string is never closed")]
+    UnclosedString(Span),
+    #[error("invalid escape")]
+    InvalidEscape(Span),
+    #[error("char literals hold exactly one character")]
+    InvalidCharLiteral(Span),
+    #[error("invalid number literal")]
+    InvalidNumber(Span),
+    #[error("invalid pattern")]
+    InvalidPattern(Span),
+    #[error("constructors must start with an upper case letter")]
+    InvalidConstructor(Span),
+    #[error("fixity declarations take a level from 0 to 9 and operators")]
+    InvalidFixity(Span),
+    #[error("non associative operators can not be chained")]
+    NonAssociative(Span),
+    #[error("expected an indented block")]
+    ExpectedBlock(Span),
+    #[error("line is not lined up with the block it belongs to")]
+    Indentation(Span),
+    #[error("a `do` block has to end with an action")]
+    ExpectedAction(Span),
+    #[error("every clause of `{0}` has to take the same number of arguments")]
+    ClauseArity(String, Span),
+    #[error("`{0}` has a signature but no definition")]
+    MissingDefinition(String, Span),
+    #[error("imports are only resolved when compiling a file")]
+    ImportOutsideModule(Span),
+    #[error("module file {0} not found")]
+    ModuleNotFound(String, Span),
+    #[error("modules import each other: {0}")]
+    ImportCycle(String, Span),
+    #[error("no module is imported as `{0}`")]
+    UnknownModule(String, Span),
+    #[error("`{0}` is not defined in module `{1}`")]
+    NotInModule(String, String, Span),
+    #[error("`{0}` is imported from more than one module")]
+    AmbiguousName(String, Span),
+    #[error("`{0}` is already defined by another module")]
+    DuplicateName(String, Span),
 }
 
 impl Error {
@@ -53,39 +95,60 @@ impl Error {
             | Self::UnexpectedToken(_, _, s)
             | Self::UnexpectedEndOfInput(s)
             | Self::UnclosedParen(s)
+            | Self::UnclosedComment(s)
+            | Self::UnclosedString(s)
+            | Self::InvalidEscape(s)
+            | Self::InvalidCharLiteral(s)
+            | Self::InvalidNumber(s)
+            | Self::InvalidPattern(s)
+            | Self::InvalidConstructor(s)
+            | Self::InvalidFixity(s)
+            | Self::NonAssociative(s)
+            | Self::ExpectedBlock(s)
+            | Self::Indentation(s)
+            | Self::ExpectedAction(s)
+            | Self::ClauseArity(_, s)
+            | Self::MissingDefinition(_, s)
+            | Self::ImportOutsideModule(s)
+            | Self::ModuleNotFound(_, s)
+            | Self::ImportCycle(_, s)
+            | Self::UnknownModule(_, s)
+            | Self::NotInModule(_, _, s)
+            | Self::AmbiguousName(_, s)
+            | Self::DuplicateName(_, s)
             | Self::UnknownOperator(s) => *s,
         }
     }
 
     pub fn report<'a>(&'a self, filename: &'a str, src: &'a str) -> String {
-        let span = match self {
-            Self::UnexpectedEndOfInput(_) => {
-                let idx_end = src.len();
-                let idx_start = idx_end.saturating_sub(1);
-                Span::new(idx_start, idx_end, 0, 0, 0, 0)
-            }
-            _ => self.span(),
-        };
-        if span.idx_end >= src.len() {
-            return format!(
-                "unexpected end of file at {filename} {span:?}, {src:?} {}",
-                self.to_string()
-            );
-        }
-        let range = (span.idx_start, span.idx_end);
         let label = &self.to_string();
+        let span = self.span();
+        let mut range = (span.idx_start, span.idx_end);
+        // the end of the file is pointed at with its last character
+        let at_end = matches!(self, Self::UnexpectedEndOfInput(_));
+        if at_end || range.1 >= src.len() {
+            let end = src.trim_end().len();
+            let Some((last, _)) = src[..end].char_indices().last() else {
+                return format!("{filename}: {label}");
+            };
+            range = if at_end {
+                (last, end)
+            } else {
+                (range.0.min(last), end)
+            };
+        }
         let mut annotations = vec![SourceAnnotation {
             label,
             annotation_type: AnnotationType::Error,
             range,
         }];
         let help_label;
-        if let Self::UnexpectedToken(expected, found, span) = self {
+        if let Self::UnexpectedToken(expected, found, _) = self {
             help_label = format!("expected token {expected} but found {found}");
             let info = SourceAnnotation {
                 label: &help_label,
                 annotation_type: AnnotationType::Info,
-                range: (span.idx_start, span.idx_end),
+                range,
             };
             annotations.push(info);
         }
@@ -98,7 +161,7 @@ impl Error {
             footer: vec![],
             slices: vec![Slice {
                 source: src,
-                line_start: span.row_start,
+                line_start: 1,
                 origin: Some(filename),
                 fold: true,
                 annotations,
@@ -1,5 +1,6 @@
 use super::{Ident, Op, Span, TokenPosition};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 macro_rules! is_expr {
     ($i:ident, $t:ident) => {
@@ -12,10 +13,12 @@ macro_rules! is_expr {
     };
 }
 
-#[derive(Clone, Hash, PartialEq, Eq)]
+/// Floats are compared and hashed by their bits, which makes `NaN` equal to
+/// itself so atoms can be used as keys.
+#[derive(Clone)]
 pub enum Atom {
-    Int(i32, TokenPosition, Span),
-    Float(String, TokenPosition, Span),
+    Int(i64, TokenPosition, Span),
+    Float(f64, TokenPosition, Span),
     Id(String, TokenPosition, Span),
     Bool(bool, TokenPosition, Span),
     String(String, TokenPosition, Span),
@@ -46,6 +49,39 @@ impl Atom {
     }
 }
 
+impl PartialEq for Atom {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Int(a, p, s), Self::Int(b, q, t)) => (a, p, s) == (b, q, t),
+            (Self::Float(a, p, s), Self::Float(b, q, t)) => {
+                (a.to_bits(), p, s) == (b.to_bits(), q, t)
+            }
+            (Self::Id(a, p, s), Self::Id(b, q, t))
+            | (Self::String(a, p, s), Self::String(b, q, t)) => (a, p, s) == (b, q, t),
+            (Self::Bool(a, p, s), Self::Bool(b, q, t)) => (a, p, s) == (b, q, t),
+            (Self::Char(a, p, s), Self::Char(b, q, t)) => (a, p, s) == (b, q, t),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Atom {}
+
+impl Hash for Atom {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Int(i, ..) => i.hash(state),
+            Self::Float(f, ..) => f.to_bits().hash(state),
+            Self::Id(s, ..) | Self::String(s, ..) => s.hash(state),
+            Self::Bool(b, ..) => b.hash(state),
+            Self::Char(c, ..) => c.hash(state),
+        }
+        self.position().hash(state);
+        self.span().hash(state);
+    }
+}
+
 impl fmt::Debug for Atom {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -63,7 +99,7 @@ impl fmt::Display for Atom {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Int(i, ..) => write!(f, "{i}"),
-            Self::Float(i, ..) => write!(f, "{i}"),
+            Self::Float(i, ..) => write!(f, "{i:?}"),
             Self::Id(id, ..) => write!(f, "{id}"),
             Self::Bool(b, ..) => write!(f, "{b}"),
             Self::String(s, ..) => write!(f, "{s}"),
@@ -97,6 +133,111 @@ pub struct App {
     pub span: Span,
 }
 
+/// Left hand side of a `match` arm.
+/// ```hs
+/// match xs on
+/// | [] -> 0
+/// | [Some x, .._] -> x
+/// | _ -> 1
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Pattern {
+    Wildcard(Span),
+    Id(String, Span),
+    Literal(Atom),
+    Constructor(String, Vec<Self>, Span),
+    /// Leading items and the pattern bound to the remaining items if the
+    /// pattern ends with `..`.
+    Array(Vec<Self>, Option<Box<Self>>, Span),
+    Tuple(Vec<Self>, Span),
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Wildcard(span) => *span,
+            Self::Id(_, span) => *span,
+            Self::Literal(atom) => atom.span(),
+            Self::Constructor(.., span) => *span,
+            Self::Array(.., span) => *span,
+            Self::Tuple(.., span) => *span,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wildcard(..) => write!(f, "_"),
+            Self::Id(name, ..) => write!(f, "{name}"),
+            Self::Literal(atom) => write!(f, "{atom}"),
+            Self::Constructor(name, args, ..) if args.is_empty() => write!(f, "{name}"),
+            Self::Constructor(name, args, ..) => {
+                write!(f, "({name}")?;
+                for arg in args.iter() {
+                    write!(f, " {arg}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Array(items, rest, ..) => {
+                let mut items = items.iter().map(ToString::to_string).collect::<Vec<_>>();
+                if let Some(rest) = rest {
+                    items.push(format!("..{rest}"));
+                }
+                write!(f, "[{}]", items.join(", "))
+            }
+            Self::Tuple(items, ..) => {
+                let items = items.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "({})", items.join(", "))
+            }
+        }
+    }
+}
+
+/// One clause of a function defined by matching on its arguments.
+/// ```hs
+/// fromOption d None = d
+/// fromOption d (Some x)
+///   | x > 0 = x
+///   | true = d
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Clause {
+    /// A pattern for each argument.
+    pub patterns: Vec<Pattern>,
+    /// Bodies along with their guards, a body without a guard always applies.
+    pub bodies: Vec<(Option<Expr>, Expr)>,
+    /// The `where` bindings, bound once the patterns match and shared by
+    /// every guard and body.
+    pub bindings: Vec<Expr>,
+    pub span: Span,
+}
+
+impl fmt::Display for Clause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "{}", patterns.join(" "))?;
+        for (guard, body) in self.bodies.iter() {
+            match guard {
+                Some(guard) => write!(f, " when {guard} -> {body}")?,
+                None => write!(f, " -> {body}")?,
+            }
+        }
+        for (i, binding) in self.bindings.iter().enumerate() {
+            let sep = if i == 0 { " where" } else { "," };
+            write!(f, "{sep} {binding}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A type written in a signature, `(a -> b) -> Array<a> -> Array<b>`.
+/// Lowercase names are type variables and the signature is polymorphic over
+/// every variable in it.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum TypeInfo {
     Int,
@@ -105,7 +246,26 @@ pub enum TypeInfo {
     String,
     Char,
     Array(Box<Self>),
-    Custom(String),
+    Tuple(Vec<Self>),
+    Var(String),
+    /// A user defined type along with its arguments, `Option a`.
+    App(String, Vec<Self>),
+    Fn(Box<Self>, Box<Self>),
+}
+
+impl TypeInfo {
+    /// The parameter and result of a function type.
+    pub fn split(&self) -> Option<(&Self, &Self)> {
+        match self {
+            Self::Fn(param, ret) => Some((param, ret)),
+            _ => None,
+        }
+    }
+
+    fn is_simple(&self) -> bool {
+        !matches!(self, Self::Fn(..))
+            && !matches!(self, Self::App(_, args) if !args.is_empty())
+    }
 }
 
 impl From<Ident> for TypeInfo {
@@ -116,7 +276,8 @@ impl From<Ident> for TypeInfo {
             "Bool" => Self::Bool,
             "String" => Self::String,
             "Char" => Self::Char,
-            name => Self::Custom(name.to_string()),
+            name if name.starts_with(char::is_lowercase) => Self::Var(name.to_string()),
+            name => Self::App(name.to_string(), vec![]),
         }
     }
 }
@@ -130,24 +291,95 @@ impl fmt::Display for TypeInfo {
             Self::String => write!(f, "String"),
             Self::Char => write!(f, "Char"),
             Self::Array(type_info) => write!(f, "Array<{type_info}>"),
-            Self::Custom(name) => write!(f, "{name}"),
+            Self::Tuple(items) => {
+                let items = items.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "({})", items.join(", "))
+            }
+            Self::Var(name) => write!(f, "{name}"),
+            Self::App(name, args) => {
+                write!(f, "{name}")?;
+                for arg in args.iter() {
+                    match arg.is_simple() {
+                        true => write!(f, " {arg}")?,
+                        false => write!(f, " ({arg})")?,
+                    }
+                }
+                Ok(())
+            }
+            Self::Fn(param, ret) if matches!(**param, Self::Fn(..)) => {
+                write!(f, "({param}) -> {ret}")
+            }
+            Self::Fn(param, ret) => write!(f, "{param} -> {ret}"),
         }
     }
 }
 
 #[derive(Clone, Hash, PartialEq, Eq)]
 pub enum Expr {
+    /// Another name for a type along with its parameters, `type Pair a = (a, a)`.
+    Alias(String, Vec<String>, TypeInfo, Span),
     // App(Box<Self>, Vec<Self>, Span),
     App(App),
     Array(Vec<Self>, TokenPosition, Span),
     Atom(Atom),
     Binary(Binary),
+    /// The clauses of a function tried in order on its arguments, each
+    /// argument is a parameter of the closures around it.
+    Clauses(Vec<Self>, Vec<Clause>, Span),
     Closure(Box<Self>, Box<Self>, Span),
-    Enum(String, Vec<(String, Vec<String>)>, Span),
+    /// A record type along with its parameters and fields, the type of a
+    /// field is written like the fields of an enum.
+    Data(String, Vec<String>, Vec<(String, String)>, Span),
+    /// A sum type along with its parameters, variants and `--|` docs.
+    Enum(
+        String,
+        Vec<String>,
+        Vec<(String, Vec<String>)>,
+        Option<String>,
+        Span,
+    ),
     Error(Span),
-    Func(String, Vec<TypeInfo>, Box<Self>, Span),
+    /// Reads a field of a record, `point.x`, or a method of the type of the
+    /// value, `opt.map`.
+    Field(Box<Self>, String, Span),
+    /// A named definition with its signature and the `--|` comments in
+    /// front of it. Methods are named after their type, `Option.map`, and
+    /// take `self` as their last argument.
+    Func(String, Option<TypeInfo>, Box<Self>, Option<String>, Span),
     IfElse(Box<Self>, Box<Self>, Box<Self>, Span),
+    /// Local definitions, each binding is an `Expr::Func` which can refer to
+    /// itself and the other bindings.
+    Let(Vec<Self>, Box<Self>, Span),
+    Match(Box<Self>, Vec<(Pattern, Self)>, Span),
+    /// Builds a record, `Point { x = 1, y = 2 }`.
+    Record(String, Vec<(String, Self)>, Span),
+    Tuple(Vec<Self>, Span),
     Unary(Unary),
+    /// A copy of a record with new values for some of its fields,
+    /// `point { x = 0 }`.
+    Update(Box<Self>, Vec<(String, Self)>, Span),
+}
+
+/// Splits the name of a method, `Option.map`, into its type and the method.
+/// Names from a module, `Data.List::map`, are not methods.
+pub fn method_name(name: &str) -> Option<(&str, &str)> {
+    let (ty, method) = name.split_once('.')?;
+    let is_method = ty.starts_with(|c: char| c.is_ascii_uppercase())
+        && method.starts_with(|c: char| c.is_lowercase() || c == '_')
+        && !method.contains(['.', ':']);
+    is_method.then_some((ty, method))
+}
+
+/// Names handled by the backends themselves unless shadowed by a definition.
+pub const BUILTINS: [&str; 14] = [
+    "print", "nth", "length", "push", "tail", "head", "toFloat", "toInt", "__range",
+    "__map", "__filter", "__concat", "pure", "__bind",
+];
+
+/// The builtins ranges, list comprehensions and do blocks are desugared to,
+/// these always refer to the builtin even when a definition shares the name.
+pub fn is_primitive(name: &str) -> bool {
+    name.starts_with("__") && BUILTINS.contains(&name)
 }
 
 impl Expr {
@@ -188,8 +420,13 @@ impl Expr {
             Self::IfElse(cond, then, r#else, span) => {
                 Self::IfElse(cond, then, r#else, span)
             }
+            Self::Match(expr, arms, span) => Self::Match(expr, arms, span),
+            Self::Let(bindings, body, span) => Self::Let(bindings, body, span),
+            Self::Clauses(args, clauses, span) => Self::Clauses(args, clauses, span),
             Self::Closure(head, tail, span) => Self::Closure(head, tail, span),
-            Self::Func(name, args, body, span) => Self::Func(name, args, body, span),
+            Self::Func(name, args, body, docs, span) => {
+                Self::Func(name, args, body, docs, span)
+            }
             Self::App(App {
                 name,
                 args,
@@ -202,7 +439,17 @@ impl Expr {
                 span,
             }),
             Self::Array(array, pos, span) => Self::Array(array, f(pos), span),
-            Self::Enum(name, variants, span) => Self::Enum(name, variants, span),
+            Self::Enum(name, params, variants, docs, span) => {
+                Self::Enum(name, params, variants, docs, span)
+            }
+            Self::Data(name, params, fields, span) => {
+                Self::Data(name, params, fields, span)
+            }
+            Self::Alias(name, params, ty, span) => Self::Alias(name, params, ty, span),
+            Self::Field(expr, field, span) => Self::Field(expr, field, span),
+            Self::Record(name, fields, span) => Self::Record(name, fields, span),
+            Self::Tuple(items, span) => Self::Tuple(items, span),
+            Self::Update(expr, fields, span) => Self::Update(expr, fields, span),
             Self::Error(span) => Self::Error(span),
         }
     }
@@ -213,11 +460,20 @@ impl Expr {
             Self::Unary(unary) => unary.span,
             Self::Binary(binary) => binary.span,
             Self::IfElse(.., span) => *span,
+            Self::Match(.., span) => *span,
+            Self::Let(.., span) => *span,
+            Self::Clauses(.., span) => *span,
             Self::Closure(.., span) => *span,
             Self::Func(.., span) => *span,
             Self::App(app) => app.span,
             Self::Array(.., span) => *span,
             Self::Enum(.., span) => *span,
+            Self::Data(.., span) => *span,
+            Self::Alias(.., span) => *span,
+            Self::Field(.., span) => *span,
+            Self::Record(.., span) => *span,
+            Self::Tuple(.., span) => *span,
+            Self::Update(.., span) => *span,
             Self::Error(span) => *span,
         }
     }
@@ -225,6 +481,8 @@ impl Expr {
     is_expr!(is_unary, Unary);
     is_expr!(is_binary, Binary);
     is_expr!(is_if_else, IfElse);
+    is_expr!(is_match, Match);
+    is_expr!(is_let, Let);
     is_expr!(is_clouser, Closure);
     is_expr!(is_func, Func);
     is_expr!(is_app, App);
@@ -236,10 +494,29 @@ impl Expr {
             Self::Unary(unary) => unary.expr.is_error(),
             Self::Binary(binary) => binary.left.is_error() || binary.right.is_error(),
             Self::IfElse(c, t, e, ..) => c.is_error() || t.is_error() || e.is_error(),
+            Self::Match(e, arms, ..) => {
+                e.is_error() || arms.iter().any(|(_, arm)| arm.is_error())
+            }
+            Self::Let(bindings, body, ..) => {
+                bindings.iter().any(|b| b.is_error()) || body.is_error()
+            }
+            Self::Clauses(_, clauses, ..) => clauses.iter().any(|clause| {
+                clause.bindings.iter().any(Self::is_error)
+                    || clause.bodies.iter().any(|(guard, body)| {
+                        guard.as_ref().is_some_and(Self::is_error) || body.is_error()
+                    })
+            }),
             Self::Closure(h, t, ..) => h.is_error() || t.is_error(),
             Self::Func(_, _, e, ..) => e.is_error(),
             Self::App(app) => app.name.is_error(),
-            Self::Array(array, ..) => array.iter().any(|e| e.is_error()),
+            Self::Array(array, ..) | Self::Tuple(array, ..) => {
+                array.iter().any(|e| e.is_error())
+            }
+            Self::Field(expr, ..) => expr.is_error(),
+            Self::Record(_, fields, ..) => fields.iter().any(|(_, e)| e.is_error()),
+            Self::Update(expr, fields, ..) => {
+                expr.is_error() || fields.iter().any(|(_, e)| e.is_error())
+            }
             Self::Error(..) => true,
             _ => false,
         }
@@ -275,6 +552,20 @@ impl Expr {
             Self::Closure(_, tail, ..) => tail.position(),
             Self::Array(_, pos, ..) => *pos,
             Self::IfElse(_, _, r#else, ..) => r#else.position(),
+            Self::Match(expr, arms, ..) => arms
+                .last()
+                .map(|(_, arm)| arm.position())
+                .unwrap_or_else(|| expr.position()),
+            Self::Let(_, body, ..) => body.position(),
+            Self::Clauses(_, clauses, ..) => clauses
+                .last()
+                .and_then(|clause| clause.bodies.last())
+                .map(|(_, body)| body.position())
+                .unwrap_or(TokenPosition::Middle),
+            Self::Field(expr, ..) => expr.position(),
+            Self::Record(..) | Self::Tuple(..) | Self::Update(..) => {
+                TokenPosition::Middle
+            }
             _ => unimplemented!("for {self:?}"),
         }
     }
@@ -289,16 +580,34 @@ impl fmt::Display for Expr {
             Self::IfElse(condition, branch1, branch2, ..) => {
                 write!(f, "(if ({condition}) then {branch1} else {branch2})")
             }
+            Self::Match(expr, arms, ..) => {
+                write!(f, "(match {expr} on")?;
+                for (pattern, arm) in arms.iter() {
+                    write!(f, " | {pattern} -> {arm}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Let(bindings, body, ..) => {
+                write!(f, "(let")?;
+                for (i, binding) in bindings.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { "," };
+                    write!(f, "{sep} {binding}")?;
+                }
+                write!(f, " in {body})")
+            }
+            Self::Clauses(args, clauses, ..) => {
+                let args = args.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "(match {} on", args.join(" "))?;
+                for clause in clauses.iter() {
+                    write!(f, " | {clause}")?;
+                }
+                write!(f, ")")
+            }
             Self::Closure(head, tail, ..) => {
                 write!(f, "(\\{head} -> {tail})")
             }
             Self::Func(name, typed, clouser, ..) => {
-                let t = typed.iter().fold("".to_string(), |acc, name| {
-                    if acc.is_empty() {
-                        return name.to_string();
-                    }
-                    format!("{acc} -> {name}")
-                });
+                let t = typed.as_ref().map(ToString::to_string).unwrap_or_default();
                 write!(f, "<{name}: {t} = {clouser}>")
             }
             Self::App(app) => {
@@ -326,7 +635,10 @@ impl fmt::Display for Expr {
                 a += "]";
                 write!(f, "{a}")
             }
-            Self::Enum(name, args, ..) => {
+            Self::Enum(name, params, args, ..) => {
+                let name = params
+                    .iter()
+                    .fold(name.to_string(), |acc, param| format!("{acc} {param}"));
                 if args.is_empty() {
                     return write!(f, "<{name}>");
                 }
@@ -349,6 +661,37 @@ impl fmt::Display for Expr {
                 );
                 write!(f, "{fstring}>")
             }
+            Self::Alias(name, params, ty, ..) => {
+                let name = params
+                    .iter()
+                    .fold(name.to_string(), |acc, param| format!("{acc} {param}"));
+                write!(f, "<type {name} = {ty}>")
+            }
+            Self::Data(name, params, fields, ..) => {
+                let name = params
+                    .iter()
+                    .fold(name.to_string(), |acc, param| format!("{acc} {param}"));
+                let fields = fields
+                    .iter()
+                    .map(|(field, ty)| format!("{field}: {ty}"))
+                    .collect::<Vec<_>>();
+                write!(f, "<{name}: {{ {} }}>", fields.join(", "))
+            }
+            Self::Field(expr, field, ..) => write!(f, "{expr}.{field}"),
+            Self::Record(name, fields, ..) => {
+                write!(f, "{name} {{")?;
+                write_fields(f, fields, false)?;
+                write!(f, " }}")
+            }
+            Self::Tuple(items, ..) => {
+                let items = items.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "({})", items.join(", "))
+            }
+            Self::Update(expr, fields, ..) => {
+                write!(f, "({expr} {{")?;
+                write_fields(f, fields, false)?;
+                write!(f, " }})")
+            }
             Self::Error(..) => write!(f, "Error"),
         }
     }
@@ -363,16 +706,33 @@ impl fmt::Debug for Expr {
             Self::IfElse(condition, branch1, branch2, ..) => {
                 write!(f, "(if ({condition:?}) then {branch1:?} else {branch2:?})")
             }
+            Self::Match(expr, arms, ..) => {
+                write!(f, "(match {expr:?} on")?;
+                for (pattern, arm) in arms.iter() {
+                    write!(f, " | {pattern} -> {arm:?}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Let(bindings, body, ..) => {
+                write!(f, "(let")?;
+                for (i, binding) in bindings.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { "," };
+                    write!(f, "{sep} {binding:?}")?;
+                }
+                write!(f, " in {body:?})")
+            }
+            Self::Clauses(args, clauses, ..) => {
+                write!(f, "(match {args:?} on")?;
+                for clause in clauses.iter() {
+                    write!(f, " | {clause}")?;
+                }
+                write!(f, ")")
+            }
             Self::Closure(head, tail, ..) => {
                 write!(f, "(\\{head:?} -> {tail:?})")
             }
             Self::Func(name, t, clouser, ..) => {
-                let t = t.iter().fold("".to_string(), |acc, name| {
-                    if acc.is_empty() {
-                        return name.to_string();
-                    }
-                    format!("{acc} -> {name}")
-                });
+                let t = t.as_ref().map(ToString::to_string).unwrap_or_default();
                 write!(f, "<{name:?}: {t} = {clouser:?}>")
             }
             Self::App(App { name, args, .. }) => {
@@ -400,7 +760,10 @@ impl fmt::Debug for Expr {
                 a += "]";
                 write!(f, "{a}")
             }
-            Self::Enum(name, args, ..) => {
+            Self::Enum(name, params, args, ..) => {
+                let name = params
+                    .iter()
+                    .fold(name.to_string(), |acc, param| format!("{acc} {param}"));
                 if args.is_empty() {
                     return write!(f, "<{name:?}>");
                 }
@@ -423,7 +786,43 @@ impl fmt::Debug for Expr {
                 );
                 write!(f, "{fstring}>")
             }
+            Self::Data(name, params, fields, ..) => {
+                let name = params
+                    .iter()
+                    .fold(name.to_string(), |acc, param| format!("{acc} {param}"));
+                write!(f, "<{name:?}: {fields:?}>")
+            }
+            Self::Alias(name, params, ty, ..) => {
+                write!(f, "<type {name:?} {params:?} = {ty:?}>")
+            }
+            Self::Field(expr, field, ..) => write!(f, "{expr:?}.{field}"),
+            Self::Record(name, fields, ..) => {
+                write!(f, "{name} {{")?;
+                write_fields(f, fields, true)?;
+                write!(f, " }}")
+            }
+            Self::Tuple(items, ..) => write!(f, "(tuple {items:?})"),
+            Self::Update(expr, fields, ..) => {
+                write!(f, "({expr:?} {{")?;
+                write_fields(f, fields, true)?;
+                write!(f, " }})")
+            }
             Self::Error(..) => write!(f, "Error"),
         }
     }
 }
+
+fn write_fields(
+    f: &mut fmt::Formatter<'_>,
+    fields: &[(String, Expr)],
+    debug: bool,
+) -> fmt::Result {
+    for (idx, (field, value)) in fields.iter().enumerate() {
+        let sep = if idx == 0 { "" } else { "," };
+        match debug {
+            true => write!(f, "{sep} {field} = {value:?}")?,
+            false => write!(f, "{sep} {field} = {value}")?,
+        }
+    }
+    Ok(())
+}
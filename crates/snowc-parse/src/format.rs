@@ -0,0 +1,801 @@
+use crate::cst::{Item, ItemKind, SyntaxTree};
+use crate::layout::layout;
+use crate::parser::{definitions, recover_definitions};
+use crate::precedence::Operators;
+use snowc_lexer::{Ctrl, KeyWord, Lexeme, Op, Token, Trivia};
+
+/// Lines longer than this are broken up where they can be.
+const MAX_WIDTH: usize = 80;
+/// How far a block or a continued line is indented past the block it is in.
+const INDENT: usize = 4;
+
+/// Prints `src` in the one style Snow is written in.
+///
+/// Spacing and indentation are rewritten, a signature goes on a line of its
+/// own between the name of a function and its body, and lines that are too
+/// long are broken up before `then` and `else` and between the arguments of
+/// an application. Comments and single blank lines stay where they are. A
+/// definition is only rewritten when it still parses to the same thing
+/// afterwards, so one that does not parse is left the way it was written.
+/// ```hs
+/// max x y : Int -> Int -> Int = if x > y then x else y
+/// -- is printed as
+/// max x y
+///     : Int -> Int -> Int
+///     = if x > y then x else y
+/// ```
+pub fn format(src: &str) -> String {
+    let tree = SyntaxTree::parse(src);
+    let (mut tokens, _) = layout(tree.tokens());
+    let ops = Operators::collect(&mut tokens).unwrap_or_default();
+    let mut out = Output::default();
+    let mut previous: Option<&Item> = None;
+    for item in tree.items.iter() {
+        let mut leading = item.leading();
+        if previous.is_some() {
+            let (ending, rest) = split_ending(leading, item.span().col_start);
+            out.ending(ending);
+            leading = rest;
+        }
+        let (_, pieces) = comments(leading, false);
+        if previous.is_some_and(|previous| !is_same_group(previous, item)) {
+            out.blank();
+        }
+        let pieces = pieces
+            .into_iter()
+            .skip_while(|piece| *piece == Piece::Blank);
+        out.pieces(pieces, 0);
+        out.extend(item_lines(src, item, &ops));
+        previous = Some(item);
+    }
+    let (eol, pieces) = comments(&tree.trailing, previous.is_some());
+    out.append(eol);
+    out.pieces(pieces, 0);
+    out.finish()
+}
+
+/// The clauses of a function, and runs of fixity declarations or imports,
+/// are not split up by blank lines.
+fn is_same_group(previous: &Item, item: &Item) -> bool {
+    match (previous.kind, item.kind) {
+        (ItemKind::Fixity, ItemKind::Fixity) | (ItemKind::Import, ItemKind::Import) => {
+            true
+        }
+        (ItemKind::Function, ItemKind::Function) => {
+            previous.name().is_some() && previous.name() == item.name()
+        }
+        _ => false,
+    }
+}
+
+/// The formatted lines of `item`, or the item as it was written when they
+/// would not parse to the same thing. Breaking up long lines is the first
+/// thing to go.
+fn item_lines(src: &str, item: &Item, ops: &Operators) -> Output {
+    let lexemes = item.lexemes().collect::<Vec<_>>();
+    // A block comment can sit in the middle of a line, where it would be lost.
+    let block_comments = lexemes[1..].iter().any(|lexeme| {
+        lexeme
+            .leading
+            .iter()
+            .any(|trivia| trivia.is_comment() && trivia.text().starts_with("{-"))
+    });
+    if let Some(expected) = meaning(item.kind, &lexemes, ops).filter(|_| !block_comments)
+    {
+        for breaks in [true, false] {
+            let out = Format::new(item.kind, &lexemes, breaks).lines();
+            let tree = SyntaxTree::parse(&out.lines.join("\n"));
+            let formatted = tree
+                .items
+                .iter()
+                .flat_map(Item::lexemes)
+                .collect::<Vec<_>>();
+            let same_tokens = formatted.len() == lexemes.len()
+                && formatted
+                    .iter()
+                    .zip(lexemes.iter())
+                    .all(|(a, b)| a.text == b.text);
+            if same_tokens
+                && meaning(item.kind, &formatted, ops) == Some(expected.clone())
+            {
+                return out;
+            }
+        }
+    }
+    let lines = src[item.span().range()]
+        .lines()
+        .map(ToString::to_string)
+        .collect();
+    Output {
+        lines,
+        comments: Vec::new(),
+    }
+}
+
+/// What the lexemes of a definition parse to, or `None` when they do not
+/// parse. Fixity declarations and imports mean the same thing as long as
+/// their tokens do.
+fn meaning(kind: ItemKind, lexemes: &[&Lexeme], ops: &Operators) -> Option<Vec<String>> {
+    if matches!(kind, ItemKind::Fixity | ItemKind::Import) {
+        return Some(Vec::new());
+    }
+    let tokens = lexemes.iter().map(|lexeme| lexeme.token.clone()).collect();
+    let (tokens, layout_errors) = layout(tokens);
+    let (ast, errors) = recover_definitions(definitions(tokens), ops);
+    (errors.is_empty() && layout_errors.is_empty())
+        .then(|| ast.iter().map(ToString::to_string).collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Comment(String),
+    Blank,
+}
+
+/// Splits off the comments right after an item, at the end of its last
+/// line or on lines of their own indented past `column`. They end the item
+/// before rather than start the next one.
+fn split_ending(trivia: &[Trivia], column: usize) -> (&[Trivia], &[Trivia]) {
+    let mut split = 0;
+    let mut newlines = 0;
+    for (idx, trivia) in trivia.iter().enumerate() {
+        match trivia {
+            Trivia::Comment(_, span) if newlines == 0 || span.col_start > column => {
+                split = idx + 1;
+                newlines = 0;
+            }
+            Trivia::Comment(..) => break,
+            _ => newlines += trivia.newlines(),
+        }
+        if newlines > 1 {
+            break;
+        }
+    }
+    trivia.split_at(split)
+}
+
+/// Splits trivia into a comment at the end of the line before it, along
+/// with the column it was written on, and the comments and blank lines on
+/// lines of their own.
+fn comments(
+    trivia: &[Trivia],
+    after_token: bool,
+) -> (Option<(String, usize)>, Vec<Piece>) {
+    let mut eol = None;
+    let mut pieces = Vec::new();
+    let mut newlines = 0;
+    for trivia in trivia {
+        let Trivia::Comment(text, span) = trivia else {
+            newlines += trivia.newlines();
+            continue;
+        };
+        let text = text.trim_end().to_string();
+        if after_token && newlines == 0 && pieces.is_empty() {
+            eol = Some(match eol {
+                Some((eol, column)) => (format!("{eol} {text}"), column),
+                None => (text, span.col_start),
+            });
+            continue;
+        }
+        if newlines > 1 {
+            pieces.push(Piece::Blank);
+        }
+        pieces.push(Piece::Comment(text));
+        newlines = 0;
+    }
+    if newlines > 1 {
+        pieces.push(Piece::Blank);
+    }
+    (eol, pieces)
+}
+
+#[derive(Debug, Default)]
+struct Output {
+    lines: Vec<String>,
+    /// The lines ending in a comment, lined up with each other when they
+    /// were written on the same column.
+    comments: Vec<Comment>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Comment {
+    line: usize,
+    /// Where the comment starts on the line.
+    at: usize,
+    /// The column it was written on.
+    written: usize,
+}
+
+impl Output {
+    /// Puts a comment at the end of the last line.
+    fn append(&mut self, comment: Option<(String, usize)>) {
+        let Some((comment, written)) = comment else {
+            return;
+        };
+        let last = self.lines.len().saturating_sub(1);
+        match self.lines.last_mut() {
+            Some(line) if !line.trim().is_empty() => {
+                line.push(' ');
+                self.comments.push(Comment {
+                    line: last,
+                    at: line.len(),
+                    written,
+                });
+                line.push_str(&comment);
+            }
+            _ => self.lines.push(comment),
+        }
+    }
+
+    /// Puts the comments ending an item after its last line. One on a line
+    /// of its own stays lined up with the comment ending the line before it.
+    fn ending(&mut self, trivia: &[Trivia]) {
+        let mut newlines = 0;
+        for trivia in trivia {
+            let Trivia::Comment(text, span) = trivia else {
+                newlines += trivia.newlines();
+                continue;
+            };
+            let text = text.trim_end().to_string();
+            if newlines == 0 {
+                self.append(Some((text, span.col_start)));
+                continue;
+            }
+            newlines = 0;
+            let line = self.lines.len();
+            let lined_up = self.comments.last().is_some_and(|comment| {
+                comment.line + 1 == line && comment.written == span.col_start
+            });
+            if lined_up {
+                self.comments.push(Comment {
+                    line,
+                    at: 0,
+                    written: span.col_start,
+                });
+                self.lines.push(text);
+            } else {
+                self.lines.push(" ".repeat(INDENT) + &text);
+            }
+        }
+    }
+
+    fn extend(&mut self, other: Output) {
+        let offset = self.lines.len();
+        self.lines.extend(other.lines);
+        self.comments
+            .extend(other.comments.into_iter().map(|comment| Comment {
+                line: comment.line + offset,
+                ..comment
+            }));
+    }
+
+    /// Comments ending lines that follow each other, which were written on
+    /// the same column, are moved to the column right after the longest of
+    /// those lines.
+    fn align(&mut self) {
+        let mut start = 0;
+        while start < self.comments.len() {
+            let first = self.comments[start];
+            let end = (start + 1..self.comments.len())
+                .find(|idx| {
+                    let (before, comment) = (self.comments[idx - 1], self.comments[*idx]);
+                    comment.line != before.line + 1 || comment.written != first.written
+                })
+                .unwrap_or(self.comments.len());
+            let group = self.comments[start..end].to_vec();
+            start = end;
+            if group.len() < 2 {
+                continue;
+            }
+            let code = |comment: &Comment| {
+                self.lines[comment.line][..comment.at]
+                    .trim_end()
+                    .chars()
+                    .count()
+            };
+            let column = group.iter().map(code).max().unwrap_or_default() + 1;
+            for comment in group {
+                let (before, text) = self.lines[comment.line].split_at(comment.at);
+                let before = before.trim_end();
+                let padding = column - before.chars().count();
+                self.lines[comment.line] =
+                    format!("{before}{}{text}", " ".repeat(padding));
+            }
+        }
+    }
+
+    /// Blank lines never follow each other or start the file.
+    fn blank(&mut self) {
+        if self
+            .lines
+            .last()
+            .is_some_and(|line| !line.trim().is_empty())
+        {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn pieces(&mut self, pieces: impl IntoIterator<Item = Piece>, column: usize) {
+        for piece in pieces {
+            match piece {
+                Piece::Comment(text) => self.lines.push(" ".repeat(column) + &text),
+                Piece::Blank => self.blank(),
+            }
+        }
+    }
+
+    fn line(&mut self, column: usize) {
+        self.lines.push(" ".repeat(column));
+    }
+
+    /// Adds `text` to the last line and returns the column it starts on.
+    fn push(&mut self, text: &str) -> usize {
+        if self.lines.is_empty() {
+            self.line(0);
+        }
+        let line = self.lines.last_mut().expect("a line to push onto");
+        let column = line.len();
+        line.push_str(text);
+        column
+    }
+
+    fn finish(mut self) -> String {
+        self.align();
+        let mut lines = self
+            .lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        if lines.is_empty() {
+            return String::new();
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Writes out the lexemes of a single definition.
+///
+/// Lines are indented from the blocks [`layout`] finds, so the same tokens
+/// come out of it again. A new item of a block starts on the column of the
+/// block, a block starting on a line of its own is indented past the block
+/// around it and any other line continues the one before it. A `where`
+/// goes half way between its block and the lines after it.
+struct Format<'a> {
+    kind: ItemKind,
+    lexemes: &'a [&'a Lexeme],
+    breaks: bool,
+    out: Output,
+    /// The columns of the blocks that are open along with how deep inside
+    /// brackets they are, the top level is at `0`.
+    blocks: Vec<(usize, usize)>,
+}
+
+impl<'a> Format<'a> {
+    fn new(kind: ItemKind, lexemes: &'a [&'a Lexeme], breaks: bool) -> Self {
+        Self {
+            kind,
+            lexemes,
+            breaks,
+            out: Output::default(),
+            blocks: vec![(0, 0)],
+        }
+    }
+
+    fn lines(mut self) -> Output {
+        let events = self.virtual_tokens();
+        let mut starts = self.line_starts();
+        let types = self.types();
+        let depths = depths(self.lexemes);
+        let mut opening = None;
+        for (idx, lexeme) in self.lexemes.iter().enumerate() {
+            let mut new_item = None;
+            let mut closed = false;
+            for event in events[idx].iter() {
+                match event.as_str() {
+                    "{" => opening = Some(self.top()),
+                    ";" => new_item = Some(self.top()),
+                    _ => {
+                        if opening.take().is_none() && self.blocks.len() > 1 {
+                            self.blocks.pop();
+                        }
+                        closed = true;
+                    }
+                }
+            }
+            if starts[idx] {
+                let column = match (opening, new_item) {
+                    _ if idx == 0 => 0,
+                    (None, _) if is_keyword(&lexeme.token, "where") => {
+                        self.top() + INDENT / 2
+                    }
+                    (Some(enclosing), _) => enclosing + INDENT,
+                    (None, Some(column)) => column,
+                    (None, None) if closed => self.top() + INDENT / 2,
+                    (None, None) => self.continuation(idx, depths[idx]),
+                };
+                if idx > 0 {
+                    let (eol, pieces) = comments(&lexeme.leading, true);
+                    self.out.append(eol);
+                    self.out.pieces(pieces, column);
+                }
+                self.out.line(column);
+                if self.breaks {
+                    let end = (idx + 1..self.lexemes.len())
+                        .find(|idx| starts[*idx])
+                        .unwrap_or(self.lexemes.len());
+                    for at in self.long_line(idx, end, column, &types) {
+                        starts[at] = true;
+                    }
+                }
+            } else if self.spaced(idx, &types) {
+                self.out.push(" ");
+            }
+            let column = self.out.push(&lexeme.text);
+            if opening.take().is_some() {
+                self.blocks.push((column, depths[idx]));
+            }
+        }
+        self.out
+    }
+
+    /// The column a line that continues the one before it starts on. It is
+    /// indented past its block once, and once more for every bracket it is
+    /// inside of, for a `then` or `else` and for the argument of a function
+    /// on an earlier line.
+    fn continuation(&self, idx: usize, depth: usize) -> usize {
+        let (column, block_depth) = self.blocks.last().copied().unwrap_or_default();
+        let token = &self.lexemes[idx].token;
+        let branch = is_keyword(token, "then") || is_keyword(token, "else");
+        let argument = is_operand(&self.lexemes[idx - 1].token)
+            && (is_open(token) || is_operand(token) && !is_close(token));
+        let depth = depth.saturating_sub(block_depth);
+        column + INDENT * (1 + depth + usize::from(branch) + usize::from(argument))
+    }
+
+    fn top(&self) -> usize {
+        self.blocks
+            .last()
+            .map(|(column, _)| *column)
+            .unwrap_or_default()
+    }
+
+    /// The `{`, `;` and `}` tokens [`layout`] puts in front of each lexeme.
+    fn virtual_tokens(&self) -> Vec<Vec<String>> {
+        let tokens = self
+            .lexemes
+            .iter()
+            .map(|lexeme| lexeme.token.clone())
+            .collect();
+        let mut events = vec![Vec::new(); self.lexemes.len()];
+        let mut idx = 0;
+        for token in layout(tokens).0 {
+            if self
+                .lexemes
+                .get(idx)
+                .is_some_and(|lexeme| lexeme.token == token)
+            {
+                idx += 1;
+            } else if let Some(events) = events.get_mut(idx) {
+                events.push(token.to_string());
+            }
+        }
+        events
+    }
+
+    /// Which lexemes start a line. They keep the lines they were written on,
+    /// except that a signature is put on a line of its own between the name
+    /// and the body of a function.
+    fn line_starts(&self) -> Vec<bool> {
+        let mut starts = self
+            .lexemes
+            .iter()
+            .enumerate()
+            .map(|(idx, lexeme)| {
+                idx == 0 || lexeme.leading.iter().any(|t| t.newlines() > 0)
+            })
+            .collect::<Vec<_>>();
+        let Some((colon, body)) = self.signature() else {
+            return starts;
+        };
+        let has_comments = self.lexemes[1..body]
+            .iter()
+            .any(|lexeme| lexeme.leading.iter().any(Trivia::is_comment));
+        if has_comments {
+            return starts;
+        }
+        starts[1..body].fill(false);
+        starts[colon] = true;
+        if let Some(start) = starts.get_mut(body) {
+            *start = true;
+        }
+        starts
+    }
+
+    /// Where the signature of a function starts and where its body does.
+    fn signature(&self) -> Option<(usize, usize)> {
+        if self.kind != ItemKind::Function {
+            return None;
+        }
+        let depths = depths(self.lexemes);
+        let body = (0..self.lexemes.len())
+            .find(|idx| depths[*idx] == 0 && is_body_start(&self.lexemes[*idx].token))
+            .unwrap_or(self.lexemes.len());
+        let colon = (1..body)
+            .find(|idx| depths[*idx] == 0 && is_ctrl(&self.lexemes[*idx].token, ":"))?;
+        Some((colon, body))
+    }
+
+    /// Which lexemes are part of a type, where `<` and `>` are brackets
+    /// rather than operators.
+    fn types(&self) -> Vec<bool> {
+        let all = matches!(self.kind, ItemKind::Enum | ItemKind::Data | ItemKind::Alias);
+        let mut types = vec![all; self.lexemes.len()];
+        if let Some((colon, body)) = self.signature() {
+            types[colon..body].fill(true);
+        }
+        types
+    }
+
+    fn spaced(&self, idx: usize, types: &[bool]) -> bool {
+        let before = idx.checked_sub(2).map(|idx| self.lexemes[idx]);
+        let prev = self.lexemes[idx - 1];
+        let next = self.lexemes[idx];
+        let (p, n) = (prev.text.as_str(), next.text.as_str());
+        if joins(p.chars().last(), n.chars().next()) {
+            return true;
+        }
+        let tight = next.leading.is_empty();
+        let is_rest =
+            before.is_some_and(|before| matches!(before.text.as_str(), "," | "["));
+        match (p, n) {
+            ("(" | "[" | "\\" | "λ", _) | (_, ")" | "]" | ",") => false,
+            // ranges keep their spacing, `[x, ..rest]` in patterns does not
+            ("..", _) if is_rest => false,
+            ("..", _) | (_, "..") => !tight,
+            ("." | "::", _) | (_, "." | "::") => false,
+            (_, ":") if self.kind == ItemKind::Data => false,
+            ("<", _) | (_, "<" | ">" | ">>") if types[idx] => false,
+            _ if matches!(prev.token, Token::Op(..))
+                && p != "|"
+                && !before.is_some_and(|before| is_operand(&before.token)) =>
+            {
+                !tight
+            }
+            _ => true,
+        }
+    }
+
+    /// How wide the lexemes from `start` up to `end` are on one line.
+    fn width(&self, start: usize, end: usize, column: usize, types: &[bool]) -> usize {
+        (start..end).fold(column, |width, idx| {
+            let space = idx > start && self.spaced(idx, types);
+            width + usize::from(space) + self.lexemes[idx].text.len()
+        })
+    }
+
+    /// Where to break up the line from `start` to `end` when it is too long.
+    /// `then` and `else` go on lines of their own first, the arguments of an
+    /// application after that. The lines this makes are broken up in turn
+    /// when they are still too long.
+    fn long_line(
+        &self,
+        start: usize,
+        end: usize,
+        column: usize,
+        types: &[bool],
+    ) -> Vec<usize> {
+        if self.width(start, end, column, types) <= MAX_WIDTH {
+            return Vec::new();
+        }
+        let depths = depths(&self.lexemes[start..end]);
+        let at_top = |idx: &usize| depths[idx - start] == 0;
+        let condition = (start..end)
+            .filter(at_top)
+            .find(|idx| is_keyword(&self.lexemes[*idx].token, "if"));
+        let branches = condition
+            .map(|condition| {
+                (condition + 1..end)
+                    .filter(at_top)
+                    .filter(|idx| {
+                        let token = &self.lexemes[*idx].token;
+                        is_keyword(token, "then") || is_keyword(token, "else")
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if !branches.is_empty() {
+            return branches;
+        }
+        self.arguments(start, end)
+    }
+
+    /// The arguments of the application the lexemes from `start` to `end`
+    /// end with, as long as it has at least two of them.
+    fn arguments(&self, start: usize, end: usize) -> Vec<usize> {
+        let mut atoms = Vec::new();
+        let mut depth = 0usize;
+        for idx in start..end {
+            let token = &self.lexemes[idx].token;
+            if depth == 0 {
+                if is_open(token) || is_operand(token) && !is_close(token) {
+                    atoms.push(idx);
+                } else {
+                    atoms.clear();
+                }
+            }
+            if is_open(token) {
+                depth += 1;
+            } else if is_close(token) {
+                depth = depth.saturating_sub(1);
+            }
+        }
+        if atoms.len() < 3 {
+            return Vec::new();
+        }
+        atoms.split_off(1)
+    }
+}
+
+/// How deep inside brackets each lexeme is.
+fn depths(lexemes: &[&Lexeme]) -> Vec<usize> {
+    let mut depth = 0usize;
+    lexemes
+        .iter()
+        .map(|lexeme| {
+            if is_close(&lexeme.token) {
+                depth = depth.saturating_sub(1);
+            }
+            let current = depth;
+            if is_open(&lexeme.token) {
+                depth += 1;
+            }
+            current
+        })
+        .collect()
+}
+
+/// Two lexemes that would be read as one when written next to each other.
+fn joins(last: Option<char>, first: Option<char>) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let is_symbol = |c: char| "!#$%&*+./<=>?@^|~-:".contains(c);
+    matches!((last, first), (Some(a), Some(b)) if (is_word(a) && is_word(b)) || (is_symbol(a) && is_symbol(b)))
+}
+
+fn is_body_start(token: &Token) -> bool {
+    is_ctrl(token, "=") || matches!(token, Token::Op(Op { lexme, .. }) if lexme == "|")
+}
+
+fn is_operand(token: &Token) -> bool {
+    match token {
+        Token::Ident(..) | Token::Int(..) | Token::Float(..) => true,
+        Token::Str(..) | Token::Char(..) => true,
+        Token::KeyWord(KeyWord { lexme, .. }) => lexme == "true" || lexme == "false",
+        token => is_close(token),
+    }
+}
+
+fn is_ctrl(token: &Token, expected: &str) -> bool {
+    matches!(token, Token::Ctrl(Ctrl { lexme, .. }) if lexme == expected)
+}
+
+fn is_keyword(token: &Token, expected: &str) -> bool {
+    matches!(token, Token::KeyWord(KeyWord { lexme, .. }) if lexme == expected)
+}
+
+fn is_open(token: &Token) -> bool {
+    is_ctrl(token, "(") || is_ctrl(token, "[") || is_ctrl(token, "{")
+}
+
+fn is_close(token: &Token) -> bool {
+    is_ctrl(token, ")") || is_ctrl(token, "]") || is_ctrl(token, "}")
+}
+
+#[test]
+fn format_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"-- Small helpers.
+infixr 6 <>
+
+infixl 1 >>=
+data Point = { x : Int, y:Int }
+max x y : Int -> Int -> Int = if x>y then x else y
+sign : Int -> Int
+
+sign 0 = 0 -- zero
+sign n
+  | n > 0 = 1
+
+  -- everything else
+  | true = 0 - 1
+describe o = match o on
+  | Some x -> x
+  | None -> fallback
+  where
+    fallback = "none"
+long x = if x > 100000000 then "a very long string that goes on and on" else "other"
+main = print (describe (Some "a")) (long 1) (max 1 2) (sign 0) [1, 2, 3, 4, 5, 6]
+broken x = x +
+"#;
+    let expected = r#"-- Small helpers.
+infixr 6 <>
+infixl 1 >>=
+
+data Point = { x: Int, y: Int }
+
+max x y
+    : Int -> Int -> Int
+    = if x > y then x else y
+
+sign : Int -> Int
+sign 0 = 0 -- zero
+sign n
+    | n > 0 = 1
+
+    -- everything else
+    | true = 0 - 1
+
+describe o = match o on
+    | Some x -> x
+    | None -> fallback
+  where
+    fallback = "none"
+
+long x = if x > 100000000
+        then "a very long string that goes on and on"
+        else "other"
+
+main = print
+        (describe (Some "a"))
+        (long 1)
+        (max 1 2)
+        (sign 0)
+        [1, 2, 3, 4, 5, 6]
+
+broken x = x +
+"#;
+    assert_eq!(format(src), expected);
+    assert_eq!(format(expected), expected);
+
+    let src = "main  =  print {- one -} 1 {- two -} -- three\n";
+    assert_eq!(format(src), src);
+
+    let src = "f x = x -- one\n  -- two\n-- g\ng = 1 -- three\n";
+    let expected = "f x = x -- one\n    -- two\n\n-- g\ng = 1 -- three\n";
+    assert_eq!(format(src), expected);
+
+    let src = "evens = [x|x <- [1..10], x mod 2 == 0] + [0, 2 .. 20]\n";
+    let expected = "evens = [x | x <- [1..10], x mod 2 == 0] + [0, 2 .. 20]\n";
+    assert_eq!(format(src), expected);
+}
+
+#[test]
+fn format_samples_test() {
+    use crate::parse;
+    use pretty_assertions::assert_eq;
+    let show = |src: &str| {
+        let ast = parse(src).unwrap();
+        ast.iter().map(ToString::to_string).collect::<Vec<_>>()
+    };
+    for src in [
+        include_str!("./../../../samples/hello_world.snow"),
+        include_str!("./../../../samples/other.snow"),
+        include_str!("./../../../samples/std.snow"),
+        include_str!("./../../../samples/rule110.snow"),
+    ] {
+        let formatted = format(src);
+        assert_eq!(show(&formatted), show(src));
+        assert_eq!(format(&formatted), formatted);
+    }
+    // the comments of the truth table stay lined up, the last one included
+    let formatted = format(include_str!("./../../../samples/rule110.snow"));
+    assert!(formatted.contains(concat!(
+        "        then true               --  1\n",
+        "        else false              -- 000\n",
+        "                                --  0\n",
+        "\n",
+        "idxOf i len\n",
+    )));
+}
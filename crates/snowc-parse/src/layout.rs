@@ -0,0 +1,386 @@
+use crate::error::Error;
+use snowc_lexer::{Ctrl, KeyWord, Op, Span, Token, TokenPosition};
+
+/// The keyword that opened a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Top,
+    Let,
+    Where,
+    On,
+    Do,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    /// A block lined up on `column`.
+    Implicit(usize, Block),
+    /// A block written with `{` and `}`, indentation is ignored inside of it.
+    Explicit,
+    /// An open `(`, `[` or `{`.
+    Bracket,
+    /// An `if` still waiting on its `else`.
+    If,
+}
+
+/// Inserts `{`, `;` and `}` tokens from the indentation of `tokens`, the same
+/// way Haskell's layout rule does.
+///
+/// `let`, `where`, `on` and `do` open a block lined up on the column of the token
+/// after them. A line starting on that column starts a new item in the block
+/// and a line starting left of it closes the block. Top level definitions
+/// are a block of their own, so they are separated by `;` too.
+/// ```hs
+/// sum xs = match xs on
+///   | [] -> 0
+///   | [x, ..rest] -> x + sum rest
+/// -- is read as
+/// sum xs = match xs on { | [] -> 0 ; | [x, ..rest] -> x + sum rest } ;
+/// ```
+/// Blocks are also closed by anything that can not be part of them, like
+/// the `in` of a `let` or the `)` around a `match`.
+///
+/// A binding indented past its block and a body not indented past it are
+/// reported, the tokens are laid out as if they had been lined up right.
+pub fn layout(tokens: Vec<Token>) -> (Vec<Token>, Vec<Error>) {
+    let mut layout = Layout::default();
+    for token in tokens {
+        layout.token(token);
+    }
+    let errors = std::mem::take(&mut layout.errors);
+    (layout.finish(), errors)
+}
+
+#[derive(Default)]
+struct Layout {
+    out: Vec<Token>,
+    stack: Vec<Context>,
+    /// Set right after a keyword that opens a block.
+    opens: Option<Block>,
+    last_row: Option<usize>,
+    /// A line continuing the item before it, where the `;` goes if the line
+    /// turns out to be a binding of its own.
+    continued: Option<Continued>,
+    errors: Vec<Error>,
+}
+
+#[derive(Clone, Copy)]
+struct Continued {
+    idx: usize,
+    depth: usize,
+    span: Span,
+}
+
+impl Layout {
+    fn token(&mut self, token: Token) {
+        let span = token.span();
+        let first_on_line = self.last_row.is_none_or(|row| span.row_start > row);
+        self.last_row = Some(span.row_end);
+        if first_on_line || self.opens.is_some() {
+            self.continued = None;
+        } else if is_ctrl(&token, "=") {
+            self.misplaced_binding();
+        }
+
+        if self.stack.is_empty() {
+            self.stack
+                .push(Context::Implicit(span.col_start, Block::Top));
+        } else if let Some(block) = self.opens.take() {
+            if is_ctrl(&token, "{") {
+                self.stack.push(Context::Explicit);
+                self.out.push(token);
+                return;
+            }
+            self.open(block, &token, first_on_line);
+        } else if first_on_line {
+            self.line_start(&token);
+        }
+
+        self.close_before(&token);
+        match &token {
+            Token::Ctrl(Ctrl { lexme, .. })
+                if matches!(lexme.as_str(), "(" | "[" | "{") =>
+            {
+                self.stack.push(Context::Bracket)
+            }
+            Token::KeyWord(KeyWord { lexme, .. }) => match lexme.as_str() {
+                "if" => self.stack.push(Context::If),
+                "let" => self.opens = Some(Block::Let),
+                "where" => self.opens = Some(Block::Where),
+                "on" => self.opens = Some(Block::On),
+                "do" => self.opens = Some(Block::Do),
+                _ => {}
+            },
+            _ => {}
+        }
+        self.out.push(token);
+    }
+
+    /// Opens a block on the column of `token`, which has to be to the right
+    /// of the block around it. Otherwise the block is empty.
+    fn open(&mut self, block: Block, token: &Token, first_on_line: bool) {
+        let span = token.span();
+        self.out.push(virtual_token("{", span));
+        let enclosing = self.stack.iter().rev().find_map(|context| match context {
+            Context::Implicit(column, _) => Some(*column),
+            _ => None,
+        });
+        if enclosing.is_none_or(|column| span.col_start > column) {
+            self.stack.push(Context::Implicit(span.col_start, block));
+            return;
+        }
+        self.out.push(virtual_token("}", span));
+        if first_on_line {
+            self.line_start(token);
+        }
+    }
+
+    /// Lines left of the innermost block close it and lines on its column
+    /// start a new item. A line right of it continues the item before it,
+    /// so does the line right after an `=`, which needs a body indented past
+    /// the block.
+    fn line_start(&mut self, token: &Token) {
+        let span = token.span();
+        let column = span.col_start;
+        let after_equals = self.out.last().is_some_and(|last| {
+            is_ctrl(last, "=") && last.span().row_end + 1 == span.row_start
+        });
+        if after_equals {
+            if self.innermost_block().is_some_and(
+                |idx| matches!(self.stack[idx], Context::Implicit(c, _) if column <= c),
+            ) {
+                self.errors.push(Error::Indentation(span));
+            }
+            return;
+        }
+        while let Some(idx) = self.innermost_block() {
+            let Context::Implicit(block_column, block) = self.stack[idx] else {
+                return;
+            };
+            if column < block_column && block != Block::Top {
+                self.close_to(idx, span);
+                continue;
+            }
+            if column <= block_column {
+                self.stack.truncate(idx + 1);
+                self.out.push(virtual_token(";", span));
+            } else if idx + 1 == self.stack.len()
+                && matches!(block, Block::Top | Block::Let | Block::Where)
+                && !is_ctrl(token, "|")
+                && !is_op(token, "|")
+            {
+                self.continued = Some(Continued {
+                    idx: self.out.len(),
+                    depth: self.stack.len(),
+                    span,
+                });
+            }
+            return;
+        }
+    }
+
+    /// An `=` on a line that continues a binding, or a definition, means the
+    /// line is one of its own indented too far. It is split off the item
+    /// before it as if it was lined up with the block.
+    fn misplaced_binding(&mut self) {
+        let Some(continued) = self.continued.take() else {
+            return;
+        };
+        if continued.depth != self.stack.len() {
+            self.continued = Some(continued);
+            return;
+        }
+        self.out
+            .insert(continued.idx, virtual_token(";", continued.span));
+        self.errors.push(Error::Indentation(continued.span));
+    }
+
+    /// Closes the blocks `token` can not be a part of.
+    fn close_before(&mut self, token: &Token) {
+        let span = token.span();
+        match token {
+            Token::Ctrl(Ctrl { lexme, .. }) => match lexme.as_str() {
+                ")" | "]" => {
+                    if let Some(idx) = self.find(|c| c == Context::Bracket) {
+                        self.close_to(idx, span);
+                    }
+                }
+                "}" => {
+                    let idx =
+                        self.find(|c| matches!(c, Context::Bracket | Context::Explicit));
+                    if let Some(idx) = idx {
+                        self.close_to(idx, span);
+                    }
+                }
+                "," => self.close_while(Block::On, span),
+                _ => {}
+            },
+            Token::KeyWord(KeyWord { lexme, .. }) => match lexme.as_str() {
+                "then" => {
+                    if let Some(idx) = self.find(|c| c == Context::If) {
+                        self.close_to(idx + 1, span);
+                    }
+                }
+                "else" => {
+                    if let Some(idx) = self.find(|c| c == Context::If) {
+                        self.close_to(idx, span);
+                    }
+                }
+                "in" => {
+                    let idx = self.find(|c| c == Context::Implicit(0, Block::Let));
+                    if let Some(idx) = idx {
+                        self.close_to(idx, span);
+                    }
+                }
+                "where" => self.close_while(Block::On, span),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// The innermost context matching `pred`, without looking past a block
+    /// written with braces. Blocks are compared by the keyword that opened
+    /// them only.
+    fn find(&self, pred: impl Fn(Context) -> bool) -> Option<usize> {
+        for (idx, context) in self.stack.iter().enumerate().rev() {
+            let context = match *context {
+                Context::Implicit(_, Block::Top) => return None,
+                Context::Implicit(_, block) => Context::Implicit(0, block),
+                context => context,
+            };
+            if pred(context) {
+                return Some(idx);
+            }
+            if context == Context::Explicit {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn innermost_block(&self) -> Option<usize> {
+        self.stack
+            .iter()
+            .rposition(|c| matches!(c, Context::Implicit(..) | Context::Explicit))
+    }
+
+    /// Pops every context from `idx` up, closing the blocks among them.
+    fn close_to(&mut self, idx: usize, span: Span) {
+        for context in self.stack.drain(idx..).rev() {
+            if let Context::Implicit(..) = context {
+                self.out.push(virtual_token("}", span));
+            }
+        }
+    }
+
+    fn close_while(&mut self, block: Block, span: Span) {
+        while let Some(Context::Implicit(_, b)) = self.stack.last() {
+            if *b != block {
+                break;
+            }
+            self.stack.pop();
+            self.out.push(virtual_token("}", span));
+        }
+    }
+
+    fn finish(mut self) -> Vec<Token> {
+        let span = self.out.last().map(|t| t.span()).unwrap_or_default();
+        if self.opens.take().is_some() {
+            self.out.push(virtual_token("{", span));
+            self.out.push(virtual_token("}", span));
+        }
+        for context in self.stack.drain(..).rev() {
+            if let Context::Implicit(_, block) = context {
+                if block != Block::Top {
+                    self.out.push(virtual_token("}", span));
+                }
+            }
+        }
+        self.out
+    }
+}
+
+fn is_ctrl(token: &Token, expected: &str) -> bool {
+    matches!(token, Token::Ctrl(Ctrl { lexme, .. }) if lexme == expected)
+}
+
+fn is_op(token: &Token, expected: &str) -> bool {
+    matches!(token, Token::Op(Op { lexme, .. }) if lexme == expected)
+}
+
+fn virtual_token(lexme: &str, span: Span) -> Token {
+    Token::Ctrl(Ctrl {
+        lexme: lexme.into(),
+        pos: TokenPosition::Middle,
+        span,
+    })
+}
+
+#[test]
+fn test_layout() {
+    use pretty_assertions::assert_eq;
+    use snowc_lexer::Scanner;
+    let show = |src: &str| {
+        let (tokens, errors) = layout(Scanner::new(src).collect());
+        assert!(errors.is_empty(), "{errors:?}");
+        tokens
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let src = r#"
+sum xs = match xs on
+  | [] -> 0
+  | [x, ..rest] -> x + sum rest
+
+area r = pi * r
+  where
+    pi = 3
+    e = 2
+
+main = let x = 1
+           y = (match x on 1 -> 2 | _ -> 3)
+       in [if x then 1 else match y on _ -> 0, x]
+"#;
+    assert_eq!(
+        show(src),
+        [
+            "sum xs = match xs on { | [ ] -> 0 ; | [ x , .. rest ] -> x + sum rest } ;",
+            "area r = pi * r where { pi = 3 ; e = 2 } ;",
+            "main = let { x = 1 ; y = ( match x on { 1 -> 2 | _ -> 3 } ) }",
+            "in [ if x then 1 else match y on { _ -> 0 } , x ]",
+        ]
+        .join(" ")
+    );
+
+    let errors = |src: &str| {
+        let (tokens, errors) = layout(Scanner::new(src).collect());
+        let tokens = tokens.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        let rows = errors
+            .iter()
+            .map(|error| match error {
+                Error::Indentation(span) => span.row_start,
+                error => panic!("{error:?}"),
+            })
+            .collect::<Vec<_>>();
+        (tokens.join(" "), rows)
+    };
+    assert_eq!(
+        errors("main = let a = 1\n             b = 2\n       in a + b"),
+        ("main = let { a = 1 ; b = 2 } in a + b".to_string(), vec![1])
+    );
+    assert_eq!(
+        errors("f x = 1\n  g y = 2\nh = 3"),
+        ("f x = 1 ; g y = 2 ; h = 3".to_string(), vec![1])
+    );
+    assert_eq!(
+        errors("f x =\nx + 1\n\nmain = f 1"),
+        ("f x = x + 1 ; main = f 1".to_string(), vec![1])
+    );
+    assert_eq!(
+        errors("f x\n  | x > 0 = 1\n  | true = 0"),
+        ("f x | x > 0 = 1 | true = 0".to_string(), vec![])
+    );
+}
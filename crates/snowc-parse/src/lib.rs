@@ -1,8 +1,16 @@
+pub mod cst;
 pub mod error;
 pub mod expr;
+pub mod format;
+pub mod layout;
+pub mod module;
 pub mod op;
 pub mod parser;
-pub use expr::{App, Atom, Binary, Expr, TypeInfo, Unary};
+pub mod precedence;
+pub use cst::SyntaxTree;
+pub use expr::{is_primitive, method_name, App, BUILTINS, Atom, Binary, Clause, Expr, Pattern, TypeInfo, Unary};
+pub use format::format;
+pub use module::{Modules, Source};
 pub use op::Op;
 
 #[cfg(test)]
@@ -10,11 +18,14 @@ mod tests;
 pub use snowc_lexer::{Ident, Scanner, Span, Token, TokenPosition};
 
 use error::Error;
-pub use parser::parse;
+pub use parser::{parse, parse_with_recovery};
 type Result<T> = std::result::Result<T, Error>;
 type ParserResult = std::result::Result<Vec<Expr>, Vec<Error>>;
 
 pub fn expression(src: &str) -> Result<Expr> {
-    let mut tokens: Vec<Token> = Scanner::new(src).collect();
-    parser::expression(&mut tokens)
+    let (mut tokens, errors) = layout::layout(Scanner::new(src).collect());
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+    parser::expression(&mut tokens, &precedence::Operators::default())
 }
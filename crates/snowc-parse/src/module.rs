@@ -0,0 +1,675 @@
+use super::cst::SyntaxTree;
+use super::error::Error;
+use super::expr::{method_name, App, Atom, Binary, Expr, Pattern, Unary};
+use super::layout::layout;
+use super::parser::{definitions, parse_definitions, lex_errors};
+use super::precedence::Operators;
+use super::{ParserResult, Result};
+use snowc_lexer::{Ctrl, Ident, KeyWord, Op, Span, Token};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A file that is part of the program, spans point into it through
+/// [`Span::file`].
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub filename: String,
+    /// The name definitions of the module are qualified with, empty for the
+    /// file being compiled.
+    pub module: String,
+    pub src: String,
+}
+
+/// Loads a program that is spread over several files. Every file is a
+/// module and imports are found relative to the file importing them.
+/// ```hs
+/// -- main.snow
+/// import Data.List (map)
+/// import qualified Utils as U
+///
+/// main = print (map U::double [1, 2, 3])
+/// ```
+/// `Data.List` is read from `Data/List.snow` next to `main.snow`. The
+/// definitions of every module end up in one program, with the names of
+/// imported modules qualified by their module, `Data.List::map`, so they
+/// can not clash. Types, constructors and methods are shared by every module.
+#[derive(Debug, Default)]
+pub struct Modules {
+    sources: Vec<Source>,
+    modules: HashMap<PathBuf, Module>,
+    /// Files that are still being loaded, the innermost import last.
+    loading: Vec<PathBuf>,
+    /// The directory of the file being compiled, as given and as an
+    /// absolute path.
+    dir: PathBuf,
+    root: PathBuf,
+    /// The file that declared each type, constructor and method.
+    types: HashMap<String, usize>,
+    constructors: HashMap<String, usize>,
+    methods: HashMap<String, usize>,
+}
+
+impl Modules {
+    /// Parses `src`, the file being compiled, along with everything it
+    /// imports. Imported modules come first in the returned program.
+    pub fn load(&mut self, filename: &str, src: &str) -> ParserResult {
+        let path = Path::new(filename);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        self.dir = dir.to_path_buf();
+        // `main.snow` has an empty parent, which does not canonicalize
+        let root = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        self.root = root.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.module(path, filename.into(), src.into(), String::new())
+    }
+
+    /// Every file of the program, the file being compiled first.
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// The file `span` points into.
+    pub fn source(&self, span: Span) -> Option<&Source> {
+        self.sources.get(span.file)
+    }
+
+    fn module(
+        &mut self,
+        path: PathBuf,
+        filename: String,
+        src: String,
+        name: String,
+    ) -> ParserResult {
+        let file = self.sources.len();
+        let tree = SyntaxTree::with_file(&src, file);
+        let mut tokens = tree.tokens();
+        self.sources.push(Source {
+            filename,
+            module: name.clone(),
+            src,
+        });
+        let errors = lex_errors(&mut tokens);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let (mut tokens, errors) = layout(tokens);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let own = Operators::collect(&mut tokens)?;
+        let (imports, definitions) = imports(definitions(tokens))?;
+
+        let mut ops = own.clone();
+        let mut ast = Vec::new();
+        let mut errors = Vec::new();
+        let mut resolved = Vec::new();
+        self.loading.push(path.clone());
+        for import in imports {
+            match self.import(&path, &import) {
+                Ok((dependency, program)) => {
+                    ast.extend(program);
+                    if !import.qualified {
+                        ops.extend(&self.modules[&dependency].ops);
+                    }
+                    resolved.push((import, dependency));
+                }
+                Err(error) => errors.extend(error),
+            }
+        }
+        self.loading.pop();
+
+        let mut definitions = parse_definitions(definitions, &ops).unwrap_or_else(|e| {
+            errors.extend(e);
+            Vec::new()
+        });
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        tree.document(&mut definitions);
+        self.declare(&definitions, file)?;
+        let module = Module::new(name, &definitions, own);
+        let imports = resolved
+            .iter()
+            .map(|(import, path)| (import, &self.modules[path]))
+            .collect::<Vec<_>>();
+        let mut resolver = Resolver::new(&module, &imports);
+        for definition in definitions.iter_mut() {
+            resolver.definition(definition);
+        }
+        if !resolver.errors.is_empty() {
+            return Err(resolver.errors);
+        }
+        self.modules.insert(path, module);
+        ast.extend(definitions);
+        Ok(ast)
+    }
+
+    /// Loads the module `import` refers to unless it already has been.
+    fn import(
+        &mut self,
+        from: &Path,
+        import: &Import,
+    ) -> std::result::Result<(PathBuf, Vec<Expr>), Vec<Error>> {
+        let dir = from.parent().unwrap_or(Path::new("."));
+        let mut file = import.path.iter().fold(dir.to_path_buf(), |p, s| p.join(s));
+        file.set_extension("snow");
+        let filename = self.display(&file);
+        let (Ok(path), Ok(src)) = (file.canonicalize(), std::fs::read_to_string(&file))
+        else {
+            return Err(vec![Error::ModuleNotFound(filename, import.span)]);
+        };
+        if let Some(idx) = self.loading.iter().position(|p| p == &path) {
+            let cycle = self.loading[idx..]
+                .iter()
+                .chain([&path])
+                .map(|p| self.display(p))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(vec![Error::ImportCycle(cycle, import.span)]);
+        }
+        if self.modules.contains_key(&path) {
+            return Ok((path, Vec::new()));
+        }
+        let name = self.module_name(&path, import);
+        let ast = self.module(path.clone(), filename, src, name)?;
+        Ok((path, ast))
+    }
+
+    /// Modules are named after their path from the file being compiled,
+    /// `Data/List.snow` is `Data.List`.
+    fn module_name(&self, path: &Path, import: &Import) -> String {
+        let name = match path.with_extension("").strip_prefix(&self.root) {
+            Ok(relative) => relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("."),
+            Err(_) => import.path.join("."),
+        };
+        if self.modules.values().all(|module| module.name != name) {
+            return name;
+        }
+        format!("{name}#{}", self.modules.len())
+    }
+
+    /// `path` the way the file being compiled was given.
+    fn display(&self, path: &Path) -> String {
+        match path.strip_prefix(&self.root) {
+            Ok(relative) => self.dir.join(relative).display().to_string(),
+            Err(_) => path.display().to_string(),
+        }
+    }
+
+    /// Types, constructors and methods are shared by every module, so two
+    /// modules can not declare the same one.
+    fn declare(
+        &mut self,
+        definitions: &[Expr],
+        file: usize,
+    ) -> std::result::Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        for definition in definitions.iter() {
+            match definition {
+                Expr::Enum(name, _, variants, _, span) => {
+                    declare(&mut self.types, name, file, *span, &mut errors);
+                    for (variant, _) in variants.iter() {
+                        declare(
+                            &mut self.constructors,
+                            variant,
+                            file,
+                            *span,
+                            &mut errors,
+                        );
+                    }
+                }
+                // A record is built with its own name.
+                Expr::Data(name, _, _, span) => {
+                    declare(&mut self.types, name, file, *span, &mut errors);
+                    declare(&mut self.constructors, name, file, *span, &mut errors);
+                }
+                Expr::Alias(name, _, _, span) => {
+                    declare(&mut self.types, name, file, *span, &mut errors);
+                }
+                Expr::Func(name, .., span) if method_name(name).is_some() => {
+                    declare(&mut self.methods, name, file, *span, &mut errors);
+                }
+                _ => {}
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(())
+    }
+}
+
+fn declare(
+    declared: &mut HashMap<String, usize>,
+    name: &str,
+    file: usize,
+    span: Span,
+    errors: &mut Vec<Error>,
+) {
+    match declared.get(name) {
+        Some(other) if *other != file => {
+            errors.push(Error::DuplicateName(name.to_string(), span))
+        }
+        _ => {
+            declared.insert(name.to_string(), file);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Module {
+    /// Empty for the file being compiled.
+    name: String,
+    /// Top level definitions along with the name they are compiled under.
+    values: HashMap<String, String>,
+    constructors: HashSet<String>,
+    /// Operators defined by the module itself.
+    ops: Operators,
+}
+
+impl Module {
+    fn new(name: String, definitions: &[Expr], ops: Operators) -> Self {
+        let mut values = HashMap::new();
+        let mut constructors = HashSet::new();
+        for definition in definitions.iter() {
+            match definition {
+                // methods belong to their type rather than the module
+                Expr::Func(value, ..)
+                    if name.is_empty() || method_name(value).is_some() =>
+                {
+                    values.insert(value.clone(), value.clone());
+                }
+                Expr::Func(value, ..) => {
+                    values.insert(value.clone(), format!("{name}::{value}"));
+                }
+                Expr::Enum(_, _, variants, ..) => {
+                    constructors.extend(variants.iter().map(|(v, _)| v.clone()));
+                }
+                Expr::Data(record, ..) => {
+                    constructors.insert(record.clone());
+                }
+                _ => {}
+            }
+        }
+        Self {
+            name,
+            values,
+            constructors,
+            ops,
+        }
+    }
+}
+
+/// `import Foo.Bar`, `import Foo.Bar (x, y)` or `import qualified Foo.Bar as B`.
+#[derive(Debug, Clone)]
+struct Import {
+    path: Vec<String>,
+    /// Only brings in qualified names.
+    qualified: bool,
+    alias: Option<String>,
+    /// The names brought in unqualified, all of them if there is no list.
+    names: Option<Vec<(String, Span)>>,
+    span: Span,
+}
+
+impl Import {
+    /// The module name used in front of `::`.
+    fn qualifier(&self) -> String {
+        self.alias.clone().unwrap_or_else(|| self.path.join("."))
+    }
+}
+
+/// The imports of a module and the tokens of its other definitions.
+type Imports = (Vec<Import>, Vec<Vec<Token>>);
+
+/// Takes the imports out of the top level definitions of a module.
+fn imports(definitions: Vec<Vec<Token>>) -> std::result::Result<Imports, Vec<Error>> {
+    let (imports, definitions): (Vec<_>, Vec<_>) =
+        definitions.into_iter().partition(|definition| {
+            matches!(definition.first(), Some(Token::KeyWord(KeyWord { lexme, .. })) if lexme == "import")
+        });
+    let mut errors = Vec::new();
+    let imports = imports
+        .into_iter()
+        .filter_map(|tokens| import(tokens).map_err(|e| errors.push(e)).ok())
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok((imports, definitions))
+}
+
+/// `import ::= "import" "qualified"? module ( "as" ident )? ( "(" names ")" )?`
+fn import(mut tokens: Vec<Token>) -> Result<Import> {
+    let start = tokens.remove(0).span();
+    let qualified = contextual_keyword(&mut tokens, "qualified");
+    let (name, mut end) = ident(&mut tokens, start)?;
+    let mut path = vec![name];
+    while consume(&mut tokens, ".") {
+        let (name, span) = ident(&mut tokens, end)?;
+        path.push(name);
+        end = span;
+    }
+    let mut alias = None;
+    if contextual_keyword(&mut tokens, "as") {
+        let (name, span) = ident(&mut tokens, end)?;
+        alias = Some(name);
+        end = span;
+    }
+    let mut names = None;
+    if consume(&mut tokens, "(") {
+        let mut list = vec![ident(&mut tokens, end)?];
+        while consume(&mut tokens, ",") {
+            list.push(ident(&mut tokens, end)?);
+        }
+        let Some(Token::Ctrl(Ctrl { lexme, span, .. })) = tokens.first().cloned() else {
+            return Err(Error::UnclosedParen(Span::from((start, list[0].1))));
+        };
+        if lexme != ")" {
+            return Err(Error::UnclosedParen(span));
+        }
+        tokens.remove(0);
+        end = span;
+        names = Some(list);
+    }
+    if let Some(token) = tokens.first() {
+        return Err(Error::UnexpectedToken(
+            "end of import".to_string(),
+            token.to_string(),
+            token.span(),
+        ));
+    }
+    Ok(Import {
+        path,
+        qualified,
+        alias,
+        names,
+        span: Span::from((start, end)),
+    })
+}
+
+fn ident(tokens: &mut Vec<Token>, last: Span) -> Result<(String, Span)> {
+    let Some(Token::Ident(Ident { lexme, span, .. })) = tokens.first().cloned() else {
+        let span = tokens.first().map(|t| t.span()).unwrap_or(last);
+        return Err(Error::MissingIdentifier(span));
+    };
+    tokens.remove(0);
+    Ok((lexme, span))
+}
+
+fn consume(tokens: &mut Vec<Token>, expected: &str) -> bool {
+    let is_expected = match tokens.first() {
+        Some(Token::Op(Op { lexme, .. }) | Token::Ctrl(Ctrl { lexme, .. })) => {
+            lexme == expected
+        }
+        _ => false,
+    };
+    if is_expected {
+        tokens.remove(0);
+    }
+    is_expected
+}
+
+/// `qualified` and `as` are only keywords inside of an import.
+fn contextual_keyword(tokens: &mut Vec<Token>, expected: &str) -> bool {
+    if !matches!(tokens.first(), Some(Token::Ident(Ident { lexme, .. })) if lexme == expected)
+    {
+        return false;
+    }
+    tokens.remove(0);
+    true
+}
+
+/// Points the names used by a module at the definitions they refer to.
+struct Resolver<'a> {
+    module: &'a Module,
+    /// Unqualified names brought in by imports, along with every definition
+    /// they could stand for.
+    imported: HashMap<String, Vec<String>>,
+    qualifiers: HashMap<String, &'a Module>,
+    /// Parameters and local bindings in scope, they shadow everything else.
+    locals: Vec<String>,
+    errors: Vec<Error>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(module: &'a Module, imports: &[(&Import, &'a Module)]) -> Self {
+        let mut resolver = Self {
+            module,
+            imported: HashMap::new(),
+            qualifiers: HashMap::new(),
+            locals: Vec::new(),
+            errors: Vec::new(),
+        };
+        for (import, dependency) in imports.iter() {
+            resolver.qualifiers.insert(import.qualifier(), dependency);
+            for (name, span) in import.names.iter().flatten() {
+                if !dependency.values.contains_key(name)
+                    && !dependency.constructors.contains(name)
+                {
+                    let error =
+                        Error::NotInModule(name.clone(), import.qualifier(), *span);
+                    resolver.errors.push(error);
+                }
+            }
+            if import.qualified {
+                continue;
+            }
+            for (name, value) in dependency.values.iter() {
+                let is_listed = match &import.names {
+                    Some(names) => names.iter().any(|(n, _)| n == name),
+                    None => true,
+                };
+                let candidates = resolver.imported.entry(name.clone()).or_default();
+                if is_listed && !candidates.contains(value) {
+                    candidates.push(value.clone());
+                }
+            }
+        }
+        resolver
+    }
+
+    fn definition(&mut self, definition: &mut Expr) {
+        if let Expr::Func(name, _, body, ..) = definition {
+            self.expr(body);
+            *name = self.module.values[name.as_str()].clone();
+        }
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Atom(Atom::Id(name, _, span)) => *name = self.resolve(name, *span),
+            Expr::Atom(_)
+            | Expr::Enum(..)
+            | Expr::Data(..)
+            | Expr::Alias(..)
+            | Expr::Error(_) => {}
+            Expr::App(App { name, args, .. }) => {
+                self.expr(name);
+                args.iter_mut().for_each(|arg| self.expr(arg));
+            }
+            Expr::Array(items, ..) | Expr::Tuple(items, _) => {
+                items.iter_mut().for_each(|item| self.expr(item))
+            }
+            Expr::Record(_, fields, _) => {
+                fields.iter_mut().for_each(|(_, value)| self.expr(value))
+            }
+            Expr::Field(expr, ..) => self.expr(expr),
+            Expr::Update(expr, fields, _) => {
+                self.expr(expr);
+                fields.iter_mut().for_each(|(_, value)| self.expr(value));
+            }
+            Expr::Binary(Binary { left, right, .. }) => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Unary(Unary { expr, .. }) => self.expr(expr),
+            Expr::IfElse(condition, then, r#else, _) => {
+                self.expr(condition);
+                self.expr(then);
+                self.expr(r#else);
+            }
+            Expr::Closure(head, body, _) => {
+                let scope = self.locals.len();
+                if let Expr::Atom(Atom::Id(name, ..)) = head.as_ref() {
+                    self.locals.push(name.clone());
+                }
+                self.expr(body);
+                self.locals.truncate(scope);
+            }
+            Expr::Func(_, _, body, ..) => self.expr(body),
+            Expr::Let(bindings, body, _) => {
+                let scope = self.locals.len();
+                for binding in bindings.iter() {
+                    if let Expr::Func(name, ..) = binding {
+                        self.locals.push(name.clone());
+                    }
+                }
+                bindings.iter_mut().for_each(|binding| self.expr(binding));
+                self.expr(body);
+                self.locals.truncate(scope);
+            }
+            Expr::Match(value, arms, _) => {
+                self.expr(value);
+                for (pattern, arm) in arms.iter_mut() {
+                    let scope = self.locals.len();
+                    bind(pattern, &mut self.locals);
+                    self.expr(arm);
+                    self.locals.truncate(scope);
+                }
+            }
+            Expr::Clauses(args, clauses, _) => {
+                args.iter_mut().for_each(|arg| self.expr(arg));
+                for clause in clauses.iter_mut() {
+                    let scope = self.locals.len();
+                    clause
+                        .patterns
+                        .iter()
+                        .for_each(|pattern| bind(pattern, &mut self.locals));
+                    for binding in clause.bindings.iter() {
+                        if let Expr::Func(name, ..) = binding {
+                            self.locals.push(name.clone());
+                        }
+                    }
+                    clause
+                        .bindings
+                        .iter_mut()
+                        .for_each(|binding| self.expr(binding));
+                    for (guard, body) in clause.bodies.iter_mut() {
+                        guard.iter_mut().for_each(|guard| self.expr(guard));
+                        self.expr(body);
+                    }
+                    self.locals.truncate(scope);
+                }
+            }
+        }
+    }
+
+    fn resolve(&mut self, name: &str, span: Span) -> String {
+        if let Some((qualifier, item)) = name.rsplit_once("::") {
+            let Some(module) = self.qualifiers.get(qualifier) else {
+                let error = Error::UnknownModule(qualifier.to_string(), span);
+                self.errors.push(error);
+                return name.to_string();
+            };
+            if let Some(value) = module.values.get(item) {
+                return value.clone();
+            }
+            if !module.constructors.contains(item) {
+                let error = Error::NotInModule(item.into(), qualifier.into(), span);
+                self.errors.push(error);
+            }
+            return item.to_string();
+        }
+        if self.locals.iter().any(|local| local == name) {
+            return name.to_string();
+        }
+        if let Some(value) = self.module.values.get(name) {
+            return value.clone();
+        }
+        match self.imported.get(name).map(Vec::as_slice) {
+            Some([value]) => value.clone(),
+            Some([_, _, ..]) => {
+                self.errors
+                    .push(Error::AmbiguousName(name.to_string(), span));
+                name.to_string()
+            }
+            _ => name.to_string(),
+        }
+    }
+}
+
+/// The names a pattern binds.
+fn bind(pattern: &Pattern, names: &mut Vec<String>) {
+    match pattern {
+        Pattern::Id(name, _) => names.push(name.clone()),
+        Pattern::Constructor(_, args, _) => args.iter().for_each(|arg| bind(arg, names)),
+        Pattern::Array(items, rest, _) => {
+            items.iter().for_each(|item| bind(item, names));
+            rest.iter().for_each(|rest| bind(rest, names));
+        }
+        Pattern::Tuple(items, _) => items.iter().for_each(|item| bind(item, names)),
+        Pattern::Wildcard(_) | Pattern::Literal(_) => {}
+    }
+}
+
+#[test]
+fn module_test() {
+    use pretty_assertions::assert_eq;
+    let filename = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../samples/modules/main.snow"
+    );
+    let src = std::fs::read_to_string(filename).unwrap();
+    let mut modules = Modules::default();
+    let ast = match modules.load(filename, &src) {
+        Ok(ast) => ast,
+        Err(err) => panic!("{:?}", err),
+    };
+    let names = ast.iter().map(|def| match def {
+        Expr::Func(name, ..) => name.as_str(),
+        _ => "",
+    });
+    assert_eq!(
+        names.collect::<Vec<_>>(),
+        vec![
+            "Data.List::map",
+            "Data.List::sum",
+            "Utils::(<+>)",
+            "Utils::double",
+            "Utils::total",
+            "double",
+            "main",
+        ]
+    );
+    assert_eq!(
+        ast[6].to_string(),
+        "<main:  = <print: (<Utils::(<+>): (<Data.List::map: (Utils::double, [1, 2])>, <Data.List::map: (double, [3])>)>, <Utils::total: ([1, 2, 3])>, <Data.List::sum: ([4, 5])>)>>"
+    );
+    let utils = modules
+        .source(Span {
+            file: 2,
+            ..Span::default()
+        })
+        .unwrap();
+    assert!(utils.filename.ends_with("Utils.snow"));
+
+    let dir = std::env::temp_dir().join("snowc-module-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("A.snow"), "import B\n\nmain = B::b").unwrap();
+    std::fs::write(dir.join("B.snow"), "import A\n\nb = 1").unwrap();
+    let filename = dir.join("A.snow").display().to_string();
+    let errors = Modules::default().load(&filename, "import B\n\nmain = B::b");
+    assert!(matches!(&errors.unwrap_err()[..], [Error::ImportCycle(..)]));
+
+    std::fs::write(dir.join("C.snow"), "c = 1").unwrap();
+    let src = "import C\n\nmain = C::d\n";
+    let filename = dir.join("D.snow").display().to_string();
+    let errors = Modules::default().load(&filename, src).unwrap_err();
+    assert_eq!(errors[0].to_string(), "`d` is not defined in module `C`");
+    assert!(errors[0].report("D.snow", src).contains("D.snow:3:8"));
+}
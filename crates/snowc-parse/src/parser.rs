@@ -1,43 +1,549 @@
 use crate::TypeInfo;
 
+use super::cst::SyntaxTree;
 use super::error::Error;
-use super::expr::{App, Atom, Binary, Expr, Unary};
+use super::expr::{method_name, App, Atom, Binary, Clause, Expr, Pattern, Unary};
+use super::layout::layout;
 use super::op::Op as Oper;
 use super::op::Op::*;
+use super::precedence::{Assoc, Fixity, Infix, Operators};
 use super::{ParserResult, Result};
-use snowc_lexer::{Ctrl, Ident, KeyWord, Op, Scanner, Span, Token, TokenPosition};
+use snowc_lexer::{
+    Ctrl, Error as LexError, Ident, KeyWord, Op, Part, Span, Str, Token, TokenPosition,
+};
 
 pub fn parse(src: &str) -> ParserResult {
-    let mut tokens: Vec<Token> = Scanner::new(src).collect();
+    let tree = SyntaxTree::parse(src);
+    let mut tokens = tree.tokens();
+    let errors = lex_errors(&mut tokens);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let (mut tokens, errors) = layout(tokens);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let ops = Operators::collect(&mut tokens)?;
+    let mut ast = parse_definitions(definitions(tokens), &ops)?;
+    tree.document(&mut ast);
+    Ok(ast)
+}
+
+/// Parses as much of `src` as it can instead of giving up on the first
+/// broken definition. Every definition that parses is kept, one that does
+/// not is left as an [`Expr::Error`] in its place, or as a function with an
+/// error for its body when its name and arguments could still be read.
+pub fn parse_with_recovery(src: &str) -> (Vec<Expr>, Vec<Error>) {
+    let tree = SyntaxTree::parse(src);
+    let mut tokens = tree.tokens();
+    let mut errors = lex_errors(&mut tokens);
+    let (mut tokens, layout_errors) = layout(tokens);
+    errors.extend(layout_errors);
+    let ops = Operators::collect(&mut tokens).unwrap_or_else(|fixity| {
+        errors.extend(fixity);
+        Operators::default()
+    });
+    let (mut ast, more) = recover_definitions(definitions(tokens), &ops);
+    tree.document(&mut ast);
+    errors.extend(more);
+    (ast, errors)
+}
+
+/// The scanner leaves what it could not read as an error token, they are
+/// taken out of `tokens` and reported. How the lexme starts tells what went
+/// wrong.
+pub(crate) fn lex_errors(tokens: &mut Vec<Token>) -> Vec<Error> {
+    let mut errors = Vec::new();
+    tokens.retain(|token| {
+        let Token::Error(LexError { lexme, span, .. }) = token else {
+            return true;
+        };
+        let error = match lexme.chars().next() {
+            Some('{') => Error::UnclosedComment(*span),
+            Some('"' | 'r') => Error::UnclosedString(*span),
+            Some('\\') => Error::InvalidEscape(*span),
+            Some('\'') => Error::InvalidCharLiteral(*span),
+            Some('0'..='9') => Error::InvalidNumber(*span),
+            _ => return true,
+        };
+        errors.push(error);
+        false
+    });
+    errors
+}
+
+pub(crate) fn parse_definitions(
+    definitions: Vec<Vec<Token>>,
+    ops: &Operators,
+) -> ParserResult {
+    let (ast, errors) = recover_definitions(definitions, ops);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(ast)
+}
+
+pub(crate) fn recover_definitions(
+    definitions: Vec<Vec<Token>>,
+    ops: &Operators,
+) -> (Vec<Expr>, Vec<Error>) {
     let mut ast = Vec::new();
     let mut errors = Vec::new();
-    while !tokens.is_empty() {
-        match function(&mut tokens) {
-            Ok(func) => {
-                ast.push(func);
+    let mut definitions = definitions
+        .into_iter()
+        .map(|tokens| {
+            let mut rest = tokens.clone();
+            match definition(&mut rest, ops) {
+                Ok(definition) => (definition, None),
+                Err(error) => (recover(tokens, ops), Some(error)),
             }
-
-            Err(error) => {
-                errors.push(error);
-                while let Some(token1) = tokens.get(0) {
-                    let pos2 = tokens
-                        .get(1)
-                        .map(|t| t.position())
-                        .cloned()
-                        .unwrap_or_default();
-                    if is_deliminator(*token1.position(), pos2) {
-                        tokens.remove(0);
-                        break;
+        })
+        .peekable();
+    while let Some((definition, error)) = definitions.next() {
+        errors.extend(error);
+        match definition {
+            Definition::Enum(expr) => {
+                ast.push(expr);
+            }
+            Definition::Clause(clause) => {
+                let mut clauses = vec![clause];
+                while let Some((Definition::Clause(clause), error)) = definitions.next_if(
+                    |(next, _)| matches!(next, Definition::Clause(c) if c.name == clauses[0].name),
+                ) {
+                    errors.extend(error);
+                    clauses.push(clause);
+                }
+                let span = Span::from((clauses[0].span, clauses[clauses.len() - 1].span));
+                match function(clauses) {
+                    Ok(func) => ast.push(func),
+                    Err(error) => {
+                        errors.push(error);
+                        ast.push(Expr::Error(span));
                     }
-                    tokens.remove(0);
                 }
             }
         }
     }
-    if !errors.is_empty() {
-        return Err(errors);
+    (ast, errors)
+}
+
+/// What is left of a definition that does not parse. A clause keeps its
+/// name and arguments as long as they can be read, so the function is still
+/// known to the rest of the program, and only its body becomes an error.
+fn recover(mut tokens: Vec<Token>, ops: &Operators) -> Definition {
+    let span = |tokens: &[Token]| match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => Span::from((first.span(), last.span())),
+        _ => Span::default(),
+    };
+    let whole = span(&tokens);
+    let Ok(head) = clause_head(&mut tokens, ops) else {
+        return Definition::Enum(Expr::Error(whole));
+    };
+    let has_body = matches!(tokens.first(), Some(Token::Ctrl(Ctrl { lexme, .. })) if lexme == "=")
+        || matches!(tokens.first(), Some(Token::Op(Op { lexme, .. })) if lexme == "|");
+    if !has_body {
+        return Definition::Enum(Expr::Error(whole));
     }
-    Ok(ast)
+    let body = span(&tokens);
+    Definition::Clause(FunctionClause {
+        bodies: vec![(None, Expr::Error(body))],
+        span: Span::from((head.span, body)),
+        ..head
+    })
+}
+
+/// Splits the program on the `;` the layout puts between top level
+/// definitions, so an error only ever loses the definition it is in.
+pub(crate) fn definitions(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+    let mut definitions = vec![Vec::new()];
+    let mut depth = 0usize;
+    for token in tokens {
+        match &token {
+            Token::Ctrl(Ctrl { lexme, .. }) if lexme == "{" => depth += 1,
+            Token::Ctrl(Ctrl { lexme, .. }) if lexme == "}" => {
+                depth = depth.saturating_sub(1)
+            }
+            Token::Ctrl(Ctrl { lexme, .. }) if lexme == ";" && depth == 0 => {
+                definitions.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(definition) = definitions.last_mut() {
+            definition.push(token);
+        }
+    }
+    definitions.retain(|definition| !definition.is_empty());
+    definitions
+}
+
+/// A top level definition, the clauses of a function are merged with the
+/// ones right after them.
+enum Definition {
+    /// An `enum`, `data` or `type` declaration.
+    Enum(Expr),
+    Clause(FunctionClause),
+}
+
+impl Definition {
+    fn span(&self) -> Span {
+        match self {
+            Self::Enum(expr) => expr.span(),
+            Self::Clause(clause) => clause.span,
+        }
+    }
+}
+
+/// A declaration has to use up every token of its definition, a line left
+/// over is indented too far to the left for the block it was meant for.
+fn definition(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Definition> {
+    let declaration = declaration(tokens, ops)?;
+    let Some(token) = tokens.first() else {
+        return Ok(declaration);
+    };
+    let span = token.span();
+    if span.row_start > declaration.span().row_end {
+        return Err(Error::Indentation(span));
+    }
+    Err(Error::UnexpectedToken(
+        "end of definition".to_string(),
+        token.to_string(),
+        span,
+    ))
+}
+
+fn declaration(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Definition> {
+    match tokens.first() {
+        Some(Token::KeyWord(kw)) if kw.lexme == "enum" => {
+            enum_declaration(tokens).map(Definition::Enum)
+        }
+        Some(Token::KeyWord(kw)) if kw.lexme == "data" => {
+            data_declaration(tokens).map(Definition::Enum)
+        }
+        Some(Token::KeyWord(kw)) if kw.lexme == "type" => {
+            alias_declaration(tokens).map(Definition::Enum)
+        }
+        Some(Token::KeyWord(kw)) if kw.lexme == "import" => {
+            Err(Error::ImportOutsideModule(kw.span))
+        }
+        _ => clause(tokens, ops).map(Definition::Clause),
+    }
+}
+
+/// Enums name a type along with the constructors that build it.
+/// ```hs
+/// enum Option a = Some a | None
+/// -- constructors are curried functions
+/// just = Some 1
+/// ```
+fn enum_declaration(tokens: &mut Vec<Token>) -> Result<Expr> {
+    let Some(Token::KeyWord(KeyWord { span: start, .. })) =
+        consume_keyword_if(tokens, "enum")
+    else {
+        panic!("expected `enum` keyword");
+    };
+    let name = constructor_name(tokens, start)?;
+    let mut params = Vec::new();
+    while let Some(Token::Ident(Ident { lexme, .. })) = tokens.first().cloned() {
+        params.push(lexme);
+        tokens.remove(0);
+    }
+    consume_ctrl(tokens, "=")?;
+
+    let mut variants = Vec::new();
+    let mut end = start;
+    loop {
+        let Some(Token::Ident(_)) = tokens.first() else {
+            let span = tokens.first().map(|t| t.span()).unwrap_or(end);
+            return Err(Error::MissingIdentifier(span));
+        };
+        let variant = constructor_name(tokens, end)?;
+        let mut fields = Vec::new();
+        while is_type_field_start(tokens.first()) {
+            let (field, span) = type_field(tokens)?;
+            fields.push(field);
+            end = span;
+        }
+        variants.push((variant, fields));
+        if consume_op_if(tokens, "|").is_none() {
+            break;
+        }
+    }
+    Ok(Expr::Enum(
+        name,
+        params,
+        variants,
+        None,
+        Span::from((start, end)),
+    ))
+}
+
+/// Records are types with named fields, they are built by naming every field
+/// and read with `.`.
+/// ```hs
+/// data Point = { x: Int, y: Int }
+/// origin = Point { x = 0, y = 0 }
+/// -- a copy of origin with a new x
+/// right = origin { x = 1 }
+/// ```
+fn data_declaration(tokens: &mut Vec<Token>) -> Result<Expr> {
+    let Some(Token::KeyWord(KeyWord { span: start, .. })) =
+        consume_keyword_if(tokens, "data")
+    else {
+        panic!("expected `data` keyword");
+    };
+    let name = constructor_name(tokens, start)?;
+    let mut params = Vec::new();
+    while let Some(Token::Ident(Ident { lexme, .. })) = tokens.first().cloned() {
+        params.push(lexme);
+        tokens.remove(0);
+    }
+    consume_ctrl(tokens, "=")?;
+    let (fields, end) = record_fields(tokens, |tokens| {
+        let field = field_name(tokens)?;
+        consume_ctrl(tokens, ":")?;
+        let (ty, _) = type_field(tokens)?;
+        Ok((field, ty))
+    })?;
+    Ok(Expr::Data(name, params, fields, Span::from((start, end))))
+}
+
+/// Aliases give a type another name, they are replaced by the type they
+/// stand for when type checking.
+/// ```hs
+/// type Grid = Array<Array<Int>>
+/// type Pair a = (a, a)
+/// ```
+fn alias_declaration(tokens: &mut Vec<Token>) -> Result<Expr> {
+    let Some(Token::KeyWord(KeyWord { span: start, .. })) =
+        consume_keyword_if(tokens, "type")
+    else {
+        panic!("expected `type` keyword");
+    };
+    let name = constructor_name(tokens, start)?;
+    let mut params = Vec::new();
+    while let Some(Token::Ident(Ident { lexme, .. })) = tokens.first().cloned() {
+        params.push(lexme);
+        tokens.remove(0);
+    }
+    let equals = consume_ctrl(tokens, "=")?;
+    if !is_type_field_start(tokens.first()) {
+        let span = tokens.first().map(|t| t.span()).unwrap_or(equals.span());
+        return Err(Error::ExpectedType(span));
+    }
+    // anything left after the type is reported by `definition`
+    let end = tokens.last().map(|t| t.span()).unwrap_or(equals.span());
+    let ty = type_info(tokens)?;
+    Ok(Expr::Alias(name, params, ty, Span::from((start, end))))
+}
+
+/// The fields of a record between `{` and `}`, separated by `,`. Returns them
+/// along with the span of the `}`.
+fn record_fields<T>(
+    tokens: &mut Vec<Token>,
+    mut field: impl FnMut(&mut Vec<Token>) -> Result<T>,
+) -> Result<(Vec<T>, Span)> {
+    consume_ctrl(tokens, "{")?;
+    let mut fields = Vec::new();
+    loop {
+        if let Some(close) = consume_ctrl_if(tokens, "}") {
+            return Ok((fields, close.span()));
+        }
+        if !fields.is_empty() {
+            consume_ctrl(tokens, ",")?;
+        }
+        fields.push(field(tokens)?);
+    }
+}
+
+fn field_name(tokens: &mut Vec<Token>) -> Result<String> {
+    match tokens.first().cloned() {
+        Some(Token::Ident(Ident { lexme, .. })) if !is_constructor(&lexme) => {
+            tokens.remove(0);
+            Ok(lexme)
+        }
+        token => {
+            let span = token.map(|t| t.span()).unwrap_or_default();
+            Err(Error::MissingIdentifier(span))
+        }
+    }
+}
+
+/// `field = value` pairs of a record being built or updated.
+fn field_values(
+    tokens: &mut Vec<Token>,
+    ops: &Operators,
+) -> Result<(Vec<(String, Expr)>, Span)> {
+    record_fields(tokens, |tokens| {
+        let field = field_name(tokens)?;
+        consume_ctrl(tokens, "=")?;
+        Ok((field, expression(tokens, ops)?))
+    })
+}
+
+fn constructor_name(tokens: &mut Vec<Token>, last: Span) -> Result<String> {
+    let Some(Token::Ident(Ident { lexme, span, .. })) = tokens.first().cloned() else {
+        let span = tokens.first().map(|t| t.span()).unwrap_or(last);
+        return Err(Error::MissingIdentifier(span));
+    };
+    if !is_constructor(&lexme) {
+        return Err(Error::InvalidConstructor(span));
+    }
+    tokens.remove(0);
+    Ok(lexme)
+}
+
+/// A single field of a constructor, `a`, `Array<Int>`, `(List a)` or
+/// `(Int, a)`.
+/// Returns the field along with the span of its last token.
+fn type_field(tokens: &mut Vec<Token>) -> Result<(String, Span)> {
+    let Some(token) = tokens.first().cloned() else {
+        return Err(Error::UnexpectedEndOfInput(Span::default()));
+    };
+    tokens.remove(0);
+    match token {
+        Token::Ident(Ident { lexme, span, .. }) => {
+            if consume_op_if(tokens, "<").is_none() {
+                return Ok((lexme, span));
+            }
+            let (inner, _) = type_field(tokens)?;
+            split_op(tokens, ">");
+            let span = consume_op(tokens, ">")?.span();
+            Ok((format!("{lexme}<{inner}>"), span))
+        }
+        Token::Ctrl(Ctrl { lexme, span, .. }) if lexme == "(" => {
+            let mut items = Vec::new();
+            let mut parts = Vec::new();
+            while is_type_field_start(tokens.first()) {
+                let (part, _) = type_field(tokens)?;
+                parts.push(match part.contains(' ') && !part.starts_with('(') {
+                    true => format!("({part})"),
+                    false => part,
+                });
+                if consume_ctrl_if(tokens, "->").is_some() {
+                    parts.push("->".into());
+                }
+                if consume_ctrl_if(tokens, ",").is_some() {
+                    items.push(std::mem::take(&mut parts).join(" "));
+                }
+            }
+            let Some(Token::Ctrl(Ctrl { span, .. })) = consume_ctrl_if(tokens, ")")
+            else {
+                return Err(Error::UnclosedParen(span));
+            };
+            if items.is_empty() {
+                return Ok((parts.join(" "), span));
+            }
+            items.push(parts.join(" "));
+            Ok((format!("({})", items.join(", ")), span))
+        }
+        token => Err(Error::ExpectedType(token.span())),
+    }
+}
+
+fn is_type_field_start(token: Option<&Token>) -> bool {
+    matches!(token, Some(Token::Ident(_)))
+        || matches!(token, Some(Token::Ctrl(Ctrl { lexme, .. })) if lexme == "(")
+}
+
+/// A single line of a function definition, see [`function`]. A signature on
+/// a line of its own is a clause without any bodies.
+struct FunctionClause {
+    name: String,
+    type_info: Option<TypeInfo>,
+    patterns: Vec<Pattern>,
+    bodies: Vec<(Option<Expr>, Expr)>,
+    bindings: Vec<Expr>,
+    span: Span,
+}
+
+/// Arguments are patterns and a body can be split up by guards, the first
+/// guard that holds picks the body.
+/// ```hs
+/// sign : Int -> Int
+/// sign 0 = 0
+/// sign n
+///   | n > 0 = 1
+///   | true = -1
+/// ```
+fn clause(tokens: &mut Vec<Token>, ops: &Operators) -> Result<FunctionClause> {
+    let FunctionClause {
+        name,
+        type_info,
+        patterns,
+        mut bodies,
+        mut bindings,
+        span: start,
+    } = clause_head(tokens, ops)?;
+    // a method always has `self`
+    let is_signature = patterns.len() == usize::from(method_name(&name).is_some())
+        && type_info.is_some()
+        && !matches!(tokens.first(), Some(Token::Ctrl(Ctrl { lexme, .. })) if lexme == "=")
+        && !matches!(tokens.first(), Some(Token::Op(Op { lexme, .. })) if lexme == "|");
+    if is_signature {
+        return Ok(FunctionClause {
+            name,
+            type_info,
+            patterns,
+            bodies,
+            bindings,
+            span: start,
+        });
+    }
+    while consume_op_if(tokens, "|").is_some() {
+        let guard = expression(tokens, ops)?;
+        consume_ctrl(tokens, "=")?;
+        bodies.push((Some(guard), get_block(tokens, ops)?));
+    }
+    if bodies.is_empty() {
+        consume_ctrl(tokens, "=")?;
+        bodies.push((None, get_block(tokens, ops)?));
+    }
+    if consume_keyword_if(tokens, "where").is_some() {
+        bindings = let_bindings(tokens, ops)?;
+    }
+    let end = bindings
+        .last()
+        .or(bodies.last().map(|(_, body)| body))
+        .map_or(start, Expr::span);
+    Ok(FunctionClause {
+        name,
+        type_info,
+        patterns,
+        bodies,
+        bindings,
+        span: Span::from((start, end)),
+    })
+}
+
+/// The name, patterns and signature of a clause, everything up to its
+/// bodies.
+fn clause_head(tokens: &mut Vec<Token>, ops: &Operators) -> Result<FunctionClause> {
+    let Some(Token::Ident(head)) = tokens.first().cloned() else {
+        let span = tokens.first().map(|t| t.span()).unwrap_or_default();
+        return Err(Error::NotAFunction(span));
+    };
+    tokens.remove(0);
+    let (name, start, is_method) = match method(tokens, &head) {
+        Some((name, _, span)) => (name, span, true),
+        None => (head.lexme, head.span, false),
+    };
+    let mut patterns = Vec::new();
+    while is_pattern_start(tokens) {
+        patterns.push(simple_pattern(tokens, ops)?);
+    }
+    if is_method {
+        patterns.push(Pattern::Id("self".into(), start));
+    }
+    let type_info = get_function_type_info(tokens)?;
+    Ok(FunctionClause {
+        name,
+        type_info,
+        patterns,
+        bodies: Vec::new(),
+        bindings: Vec::new(),
+        span: start,
+    })
 }
 
 /// Functions are just syntax sugar for closures.
@@ -46,29 +552,268 @@ pub fn parse(src: &str) -> ParserResult {
 /// -- is the same as
 /// add = (\x -> (\y -> x + y))
 /// ```
-fn function(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let Some(Token::Ident(Ident {
-        lexme: name,
-        span: start,
+/// A function written with more than one clause, or with patterns or guards,
+/// matches on its arguments inside of the closures.
+/// ```hs
+/// fromOption d None = d
+/// fromOption _ (Some x) = x
+/// -- is the same as
+/// fromOption = (\a -> (\b -> match a b on | d None -> d | _ (Some x) -> x))
+/// ```
+fn function(clauses: Vec<FunctionClause>) -> Result<Expr> {
+    let (signatures, clauses): (Vec<_>, Vec<_>) =
+        clauses.into_iter().partition(|c| c.bodies.is_empty());
+    let Some(first) = clauses.first() else {
+        let signature = &signatures[0];
+        return Err(Error::MissingDefinition(
+            signature.name.clone(),
+            signature.span,
+        ));
+    };
+    let name = first.name.clone();
+    let arity = first.patterns.len();
+    let end = clauses.last().map(|c| c.span).unwrap_or(first.span);
+    let span = Span::from((first.span, end));
+    if let Some(clause) = clauses.iter().find(|c| c.patterns.len() != arity) {
+        return Err(Error::ClauseArity(name, clause.span));
+    }
+    let type_info = signatures
+        .iter()
+        .chain(clauses.iter())
+        .find_map(|c| c.type_info.clone());
+    if let [FunctionClause {
+        patterns,
+        bodies,
+        bindings,
         ..
-    })) = tokens.get(0).cloned()
+    }] = clauses.as_slice()
+    {
+        let args = patterns
+            .iter()
+            .map(|pattern| match pattern {
+                Pattern::Id(name, span) => Some(Expr::Atom(Atom::Id(
+                    name.clone(),
+                    TokenPosition::Middle,
+                    *span,
+                ))),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        if let (Some(args), [(None, body)]) = (args, bodies.as_slice()) {
+            let body = match bindings.last() {
+                None => body.clone(),
+                Some(last) => {
+                    let span = Span::from((body.span(), last.span()));
+                    Expr::Let(bindings.clone(), Box::new(body.clone()), span)
+                }
+            };
+            let closures = create_closures(args, body);
+            return Ok(Expr::Func(name, type_info, Box::new(closures), None, span));
+        }
+    }
+    let args = (0..arity)
+        .map(|idx| {
+            Expr::Atom(Atom::Id(format!("__arg{idx}"), TokenPosition::Middle, span))
+        })
+        .collect::<Vec<_>>();
+    let clauses = clauses
+        .into_iter()
+        .map(|clause| Clause {
+            patterns: clause.patterns,
+            bodies: clause.bodies,
+            bindings: clause.bindings,
+            span: clause.span,
+        })
+        .collect();
+    let body = Expr::Clauses(args.clone(), clauses, span);
+    Ok(Expr::Func(
+        name,
+        type_info,
+        Box::new(create_closures(args, body)),
+        None,
+        span,
+    ))
+}
+
+/// Bindings are written like functions, one per line or separated by commas.
+/// ```hs
+/// let double x = x * 2, ten = 10 in double ten
+/// -- or after the body of a function
+/// area r = pi * r * r
+///   where
+///     pi = 3
+/// ```
+fn let_expression(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
+    let Some(Token::KeyWord(KeyWord { span: start, .. })) =
+        consume_keyword_if(tokens, "let")
     else {
-        let span = tokens.get(0).map(|t| t.span()).unwrap_or_default();
-        return Err(Error::NotAFunction(span));
+        panic!("expected `let` keyword");
     };
-    tokens.remove(0);
-    let args = get_function_args(tokens);
-    let type_info = get_function_type_info(tokens)?;
-    consume_ctrl(tokens, "=")?;
-    let body = get_block(tokens)?;
-    let end = body.span();
-    let closures = create_closures(args, body);
-    let span = Span::from((start, end));
-    Ok(Expr::Func(name, type_info, Box::new(closures), span))
+    let bindings = let_bindings(tokens, ops)?;
+    consume_keyword(tokens, "in")?;
+    let body = expression(tokens, ops)?;
+    let span = Span::from((start, body.span()));
+    Ok(Expr::Let(bindings, Box::new(body), span))
 }
 
-fn get_block(tokens: &mut Vec<Token>) -> Result<Expr> {
-    expression(tokens)
+enum Statement {
+    Bind(Pattern, Expr),
+    /// A `let` without `in`, its bindings are seen by the lines after it.
+    Let(Vec<Expr>, Span),
+    Action(Expr),
+}
+
+/// Each line of a `do` block is an action, a `pattern <- action` binding what
+/// the action gives back or a `let` without `in`. The block is desugared to
+/// `__bind`, which runs an action and passes what it gave to the rest.
+/// ```hs
+/// main = do
+///   name <- pure "snow"
+///   let loud = name + "!"
+///   print loud
+/// -- is read as
+/// main = __bind (pure "snow") (\name -> let loud = name + "!" in print loud)
+/// ```
+fn do_expression(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
+    let Some(Token::KeyWord(KeyWord {
+        pos, span: start, ..
+    })) = consume_keyword_if(tokens, "do")
+    else {
+        panic!("expected `do` keyword");
+    };
+    let mut statements = block(tokens, &[";"], |tokens| statement(tokens, ops))?;
+    let mut body = match statements.pop() {
+        Some(Statement::Action(action)) => action,
+        Some(Statement::Bind(pattern, _)) => {
+            return Err(Error::ExpectedAction(pattern.span()));
+        }
+        Some(Statement::Let(_, span)) => return Err(Error::ExpectedAction(span)),
+        None => unreachable!("empty block"),
+    };
+    // the first line spans the whole block, starting at `do`
+    for (idx, statement) in statements.into_iter().enumerate().rev() {
+        let line = match &statement {
+            _ if idx == 0 => start,
+            Statement::Bind(pattern, _) => pattern.span(),
+            Statement::Let(_, span) => *span,
+            Statement::Action(action) => action.span(),
+        };
+        let span = Span::from((line, body.span()));
+        body = match statement {
+            Statement::Bind(pattern, action) => {
+                let rest = bind(&pattern, body, pos, span);
+                primitive("__bind", vec![action, rest], pos, span)
+            }
+            Statement::Let(bindings, _) => Expr::Let(bindings, Box::new(body), span),
+            Statement::Action(action) => {
+                let rest = Expr::Closure(Box::new(item(pos, span)), Box::new(body), span);
+                primitive("__bind", vec![action, rest], pos, span)
+            }
+        };
+    }
+    Ok(body)
+}
+
+fn statement(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Statement> {
+    if is_generator(tokens) {
+        let (pattern, action) = generator(tokens, ops)?;
+        return Ok(Statement::Bind(pattern, action));
+    }
+    let Some(Token::KeyWord(KeyWord { span: start, .. })) =
+        consume_keyword_if(tokens, "let")
+    else {
+        return expression(tokens, ops).map(Statement::Action);
+    };
+    let bindings = let_bindings(tokens, ops)?;
+    if consume_keyword_if(tokens, "in").is_none() {
+        return Ok(Statement::Let(bindings, start));
+    }
+    let body = expression(tokens, ops)?;
+    let span = Span::from((start, body.span()));
+    Ok(Statement::Action(Expr::Let(bindings, Box::new(body), span)))
+}
+
+fn let_bindings(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Vec<Expr>> {
+    let clauses = block(tokens, &[";", ","], |tokens| clause(tokens, ops))?;
+    let mut clauses = clauses.into_iter().peekable();
+    let mut bindings = Vec::new();
+    while let Some(clause) = clauses.next() {
+        let mut function_clauses = vec![clause];
+        while let Some(clause) =
+            clauses.next_if(|next| next.name == function_clauses[0].name)
+        {
+            function_clauses.push(clause);
+        }
+        bindings.push(function(function_clauses)?);
+    }
+    dedented_binding(tokens, &bindings)?;
+    Ok(bindings)
+}
+
+/// The layout closes a block of bindings at a line left of its column, so a
+/// binding lined up a bit left of the others ends up after the block instead
+/// of in it.
+/// ```hs
+/// f x = let
+///     a = 1
+///    b = 2 -- one column too far to the left
+///   in a + b
+/// ```
+fn dedented_binding(tokens: &[Token], bindings: &[Expr]) -> Result<()> {
+    let (Some(token), Some(last)) = (tokens.first(), bindings.last()) else {
+        return Ok(());
+    };
+    let ends_block = match token {
+        Token::KeyWord(KeyWord { lexme, .. }) => {
+            matches!(lexme.as_str(), "in" | "then" | "else")
+        }
+        Token::Ctrl(Ctrl { lexme, .. }) => {
+            matches!(lexme.as_str(), ";" | "}" | ")" | "]" | ",")
+        }
+        _ => false,
+    };
+    let span = token.span();
+    if !ends_block && span.row_start > last.span().row_end {
+        return Err(Error::Indentation(span));
+    }
+    Ok(())
+}
+
+/// The items of a block between `{` and `}`, the braces usually come from
+/// the layout. Items are separated by any run of `separators`.
+fn block<T>(
+    tokens: &mut Vec<Token>,
+    separators: &[&str],
+    mut item: impl FnMut(&mut Vec<Token>) -> Result<T>,
+) -> Result<Vec<T>> {
+    consume_ctrl(tokens, "{")?;
+    let is_separator = |token: Option<&Token>| match token {
+        Some(Token::Ctrl(Ctrl { lexme, .. }) | Token::Op(Op { lexme, .. })) => {
+            separators.contains(&lexme.as_str())
+        }
+        _ => false,
+    };
+    let mut items = Vec::new();
+    loop {
+        while is_separator(tokens.first()) {
+            tokens.remove(0);
+        }
+        if let Some(close) = consume_ctrl_if(tokens, "}") {
+            if items.is_empty() {
+                return Err(Error::ExpectedBlock(close.span()));
+            }
+            return Ok(items);
+        }
+        items.push(item(tokens)?);
+        if !is_separator(tokens.first()) {
+            consume_ctrl(tokens, "}")?;
+            return Ok(items);
+        }
+    }
+}
+
+fn get_block(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
+    expression(tokens, ops)
 }
 
 fn create_closures(args: Vec<Expr>, body: Expr) -> Expr {
@@ -80,7 +825,7 @@ fn create_closures(args: Vec<Expr>, body: Expr) -> Expr {
 
 fn get_function_args(tokens: &mut Vec<Token>) -> Vec<Expr> {
     let mut args = Vec::new();
-    while let Some(Token::Ident(ident)) = tokens.get(0) {
+    while let Some(Token::Ident(ident)) = tokens.first() {
         args.push(Expr::Atom(Atom::Id(
             ident.lexme.clone(),
             ident.pos,
@@ -91,61 +836,102 @@ fn get_function_args(tokens: &mut Vec<Token>) -> Vec<Expr> {
     args
 }
 
-fn get_function_type_info(tokens: &mut Vec<Token>) -> Result<Vec<TypeInfo>> {
-    let mut types = Vec::new();
-    if consume_ctrl_if(tokens, ":").is_none() {
-        return Ok(types);
+fn get_function_type_info(tokens: &mut Vec<Token>) -> Result<Option<TypeInfo>> {
+    let Some(colon) = consume_ctrl_if(tokens, ":") else {
+        return Ok(None);
+    };
+    if !is_type_field_start(tokens.first()) {
+        let span = tokens.first().map(|t| t.span()).unwrap_or(colon.span());
+        return Err(Error::ExpectedType(span));
     }
+    type_info(tokens).map(Some)
+}
 
-    while !matches!(tokens.get(0), Some(Token::Ctrl(Ctrl{lexme, ..})) if lexme == "=") {
-        let Some(Token::Ident(ident)) = tokens.get(0).cloned() else {
-            break;
-        };
+/// `type ::= type_app ( "->" type )?`, functions associate to the right.
+fn type_info(tokens: &mut Vec<Token>) -> Result<TypeInfo> {
+    let param = type_app(tokens)?;
+    if consume_ctrl_if(tokens, "->").is_none() {
+        return Ok(param);
+    }
+    let ret = type_info(tokens)?;
+    Ok(TypeInfo::Fn(Box::new(param), Box::new(ret)))
+}
 
-        if matches!(tokens.get(1), Some(Token::Op(Op{lexme, ..})) if lexme == "<" && ident.lexme == "Array" )
-        {
-            tokens.remove(0);
-            let last = tokens.remove(0);
-            let Some(Token::Ident(ident)) = tokens.get(0).cloned() else {
-                return Err(Error::ExpectedType(last.span()).into());
-            };
-            tokens.remove(0);
-            types.push(TypeInfo::Array(Box::new(TypeInfo::from(ident))));
+/// `type_app ::= constructor type_atom* | type_atom`, `Option (List a)`.
+fn type_app(tokens: &mut Vec<Token>) -> Result<TypeInfo> {
+    let Some(Token::Ident(ident)) = tokens.first().cloned() else {
+        return type_atom(tokens);
+    };
+    if ident.lexme == "Array" {
+        return type_atom(tokens);
+    }
+    let TypeInfo::App(name, mut args) = TypeInfo::from(ident) else {
+        return type_atom(tokens);
+    };
+    tokens.remove(0);
+    while is_type_field_start(tokens.first()) {
+        args.push(type_atom(tokens)?);
+    }
+    Ok(TypeInfo::App(name, args))
+}
+
+/// `type_atom ::= name | "Array" "<" type ">" | "(" type ( "," type )* ")"`
+fn type_atom(tokens: &mut Vec<Token>) -> Result<TypeInfo> {
+    let Some(token) = tokens.first().cloned() else {
+        return Err(Error::UnexpectedEndOfInput(Span::default()));
+    };
+    tokens.remove(0);
+    match token {
+        Token::Ident(ident) if ident.lexme == "Array" => {
+            let last = consume_op(tokens, "<")?;
+            if !is_type_field_start(tokens.first()) {
+                return Err(Error::ExpectedType(last.span()));
+            }
+            let item = type_info(tokens)?;
+            split_op(tokens, ">");
             consume_op(tokens, ">")?;
-            consume_ctrl_if(tokens, "->");
-            continue;
+            Ok(TypeInfo::Array(Box::new(item)))
         }
-        types.push(TypeInfo::from(ident));
-        tokens.remove(0);
-        if consume_ctrl_if(tokens, "->").is_none() {
-            break;
+        Token::Ident(ident) => Ok(TypeInfo::from(ident)),
+        Token::Ctrl(Ctrl { lexme, span, .. }) if lexme == "(" => {
+            let mut items = vec![type_info(tokens)?];
+            while consume_ctrl_if(tokens, ",").is_some() {
+                items.push(type_info(tokens)?);
+            }
+            if consume_ctrl_if(tokens, ")").is_none() {
+                return Err(Error::UnclosedParen(span));
+            }
+            match items.len() {
+                1 => Ok(items.remove(0)),
+                _ => Ok(TypeInfo::Tuple(items)),
+            }
         }
+        token => Err(Error::ExpectedType(token.span())),
     }
-    Ok(types)
 }
 
-pub(crate) fn expression(tokens: &mut Vec<Token>) -> Result<Expr> {
-    match tokens.get(0) {
-        Some(Token::KeyWord(kw)) if kw.lexme == "if" => if_expression(tokens),
-        Some(Token::Ctrl(c)) if vec!["λ", "\\"].contains(&c.lexme.as_str()) => {
-            lambda_expression(tokens)
-        }
-        _ => logic_or(tokens),
+pub(crate) fn expression(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
+    match tokens.first() {
+        Some(Token::KeyWord(kw)) if kw.lexme == "if" => if_expression(tokens, ops),
+        Some(Token::KeyWord(kw)) if kw.lexme == "match" => match_expression(tokens, ops),
+        Some(Token::KeyWord(kw)) if kw.lexme == "let" => let_expression(tokens, ops),
+        Some(Token::KeyWord(kw)) if kw.lexme == "do" => do_expression(tokens, ops),
+        _ => binary(tokens, ops, 0),
     }
 }
 
-fn if_expression(tokens: &mut Vec<Token>) -> Result<Expr> {
+fn if_expression(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
     let Some(Token::KeyWord(KeyWord { span: start, .. })) =
         consume_keyword_if(tokens, "if")
     else {
         panic!("expected `if` keyword");
         // return equality(tokens);
     };
-    let condition = expression(tokens)?;
+    let condition = expression(tokens, ops)?;
     consume_keyword(tokens, "then")?;
-    let true_branch = expression(tokens)?;
+    let true_branch = expression(tokens, ops)?;
     consume_keyword(tokens, "else")?;
-    let false_branch = expression(tokens)?;
+    let false_branch = expression(tokens, ops)?;
     let span = Span::from((start, false_branch.span()));
     Ok(Expr::IfElse(
         Box::new(condition),
@@ -155,7 +941,166 @@ fn if_expression(tokens: &mut Vec<Token>) -> Result<Expr> {
     ))
 }
 
-fn lambda_expression(tokens: &mut Vec<Token>) -> Result<Expr> {
+/// Arms go on their own lines, or are separated by `|` on a single line.
+/// The leading `|` is optional.
+/// ```hs
+/// match x on
+///   | Some y -> y
+///   | None -> 0
+/// ```
+fn match_expression(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
+    let Some(Token::KeyWord(KeyWord { span: start, .. })) =
+        consume_keyword_if(tokens, "match")
+    else {
+        panic!("expected `match` keyword");
+    };
+    let expr = expression(tokens, ops)?;
+    consume_keyword(tokens, "on")?;
+    let arms = block(tokens, &[";", "|"], |tokens| {
+        let pattern = pattern(tokens, ops)?;
+        consume_ctrl(tokens, "->")?;
+        let arm = expression(tokens, ops)?;
+        Ok((pattern, arm))
+    })?;
+    let end = arms.last().map(|(_, arm)| arm.span()).unwrap_or(start);
+    let span = Span::from((start, end));
+    Ok(Expr::Match(Box::new(expr), arms, span))
+}
+
+/// Constructors start with an upper case letter and take their
+/// arguments without parentheses, `Some x` or `Cons x (Cons y rest)`.
+fn pattern(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Pattern> {
+    let Some(Token::Ident(Ident { lexme, span, .. })) = tokens.first().cloned() else {
+        return simple_pattern(tokens, ops);
+    };
+    if !is_constructor(&lexme) {
+        return simple_pattern(tokens, ops);
+    }
+    tokens.remove(0);
+    let mut args = Vec::new();
+    while is_pattern_start(tokens) {
+        args.push(simple_pattern(tokens, ops)?);
+    }
+    let end = args.last().map(Pattern::span).unwrap_or(span);
+    Ok(Pattern::Constructor(lexme, args, Span::from((span, end))))
+}
+
+fn simple_pattern(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Pattern> {
+    let Some(token) = tokens.first().cloned() else {
+        return Err(Error::UnexpectedEndOfInput(Span::default()));
+    };
+    match token {
+        Token::Ident(Ident { lexme, span, .. }) if lexme == "_" => {
+            tokens.remove(0);
+            Ok(Pattern::Wildcard(span))
+        }
+        Token::Ident(Ident { lexme, span, .. }) if is_constructor(&lexme) => {
+            tokens.remove(0);
+            Ok(Pattern::Constructor(lexme, Vec::new(), span))
+        }
+        Token::Ident(Ident { lexme, span, .. }) => {
+            tokens.remove(0);
+            Ok(Pattern::Id(lexme, span))
+        }
+        Token::Op(Op { lexme, span, .. }) if lexme == "-" => {
+            tokens.remove(0);
+            match primary(tokens, ops)? {
+                Expr::Atom(Atom::Int(int, pos, end)) => Ok(Pattern::Literal(Atom::Int(
+                    -int,
+                    pos,
+                    Span::from((span, end)),
+                ))),
+                Expr::Atom(Atom::Float(float, pos, end)) => Ok(Pattern::Literal(
+                    Atom::Float(-float, pos, Span::from((span, end))),
+                )),
+                expr => Err(Error::InvalidPattern(Span::from((span, expr.span())))),
+            }
+        }
+        Token::Ctrl(Ctrl { lexme, span, .. }) if lexme == "(" => {
+            tokens.remove(0);
+            let mut items = vec![pattern(tokens, ops)?];
+            while consume_ctrl_if(tokens, ",").is_some() {
+                items.push(pattern(tokens, ops)?);
+            }
+            let Some(close) = consume_ctrl_if(tokens, ")") else {
+                let end = items.last().map(Pattern::span).unwrap_or(span);
+                return Err(Error::UnclosedParen(Span::from((span, end))));
+            };
+            match items.len() {
+                1 => Ok(items.remove(0)),
+                _ => Ok(Pattern::Tuple(items, Span::from((span, close.span())))),
+            }
+        }
+        Token::Ctrl(Ctrl { lexme, span, .. }) if lexme == "[" => {
+            tokens.remove(0);
+            array_pattern(tokens, ops, span)
+        }
+        token if is_atom(Some(&token)) => match primary(tokens, ops)? {
+            Expr::Atom(atom @ Atom::Id(..)) => Err(Error::InvalidPattern(atom.span())),
+            Expr::Atom(atom) => Ok(Pattern::Literal(atom)),
+            expr => Err(Error::InvalidPattern(expr.span())),
+        },
+        token => Err(Error::InvalidPattern(token.span())),
+    }
+}
+
+fn array_pattern(
+    tokens: &mut Vec<Token>,
+    ops: &Operators,
+    start: Span,
+) -> Result<Pattern> {
+    let mut items = Vec::new();
+    let mut rest = None;
+    while !tokens.is_empty() {
+        if matches!(tokens.first(), Some(Token::Ctrl(Ctrl{lexme, ..})) if lexme == "]") {
+            break;
+        }
+        if let Some(Token::Op(Op { span, .. })) = consume_op_if(tokens, "..") {
+            let pattern = match tokens.first() {
+                Some(Token::Ident(_)) => simple_pattern(tokens, ops)?,
+                _ => Pattern::Wildcard(span),
+            };
+            if !matches!(pattern, Pattern::Id(..) | Pattern::Wildcard(..)) {
+                return Err(Error::InvalidPattern(pattern.span()));
+            }
+            rest = Some(Box::new(pattern));
+            break;
+        }
+        items.push(pattern(tokens, ops)?);
+        consume_ctrl_if(tokens, ",");
+    }
+    let Some(Token::Ctrl(Ctrl { span: end, .. })) = consume_ctrl_if(tokens, "]") else {
+        return Err(Error::UnclosedArray(start));
+    };
+    Ok(Pattern::Array(items, rest, Span::from((start, end))))
+}
+
+/// The value of an int literal, `None` when it does not fit in an `Int`.
+fn int(lexme: &str) -> Option<i64> {
+    let lexme = lexme.replace('_', "");
+    let (digits, radix) = match lexme.get(..2) {
+        Some("0x") => (&lexme[2..], 16),
+        Some("0o") => (&lexme[2..], 8),
+        Some("0b") => (&lexme[2..], 2),
+        _ => (&lexme[..], 10),
+    };
+    i64::from_str_radix(digits, radix).ok()
+}
+
+fn is_constructor(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+fn is_pattern_start(tokens: &[Token]) -> bool {
+    match tokens {
+        [Token::Op(Op { lexme, .. }), next, ..] if lexme == "-" => {
+            matches!(next, Token::Int(_) | Token::Float(_))
+        }
+        tokens => is_atom(tokens.first()),
+    }
+}
+
+fn lambda_expression(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
     let Token::Ctrl(Ctrl { span: start, .. }) = tokens.remove(0) else {
         panic!("expected `\\` or `λ` in lambda expression");
     };
@@ -163,10 +1108,10 @@ fn lambda_expression(tokens: &mut Vec<Token>) -> Result<Expr> {
     if args.len() != 1 {
         // TODO: only one argument is allowed error or no argument error checking or maybe no args
         // is fine
-        return Err(Error::ClosureArgumentsCanOnlyBeOne(start).into());
+        return Err(Error::ClosureArgumentsCanOnlyBeOne(start));
     }
     consume_ctrl(tokens, "->")?;
-    let body = expression(tokens)?;
+    let body = expression(tokens, ops)?;
     let span = Span::from((start, body.span()));
     Ok(Expr::Closure(
         Box::new(args[0].clone()),
@@ -175,130 +1120,176 @@ fn lambda_expression(tokens: &mut Vec<Token>) -> Result<Expr> {
     ))
 }
 
-fn logic_or(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let mut lhs = logic_and(tokens)?;
-    while let Some(op @ Or) = get_op(tokens.get(0)) {
-        tokens.remove(0);
-        let rhs = logic_and(tokens)?;
-        let pos = rhs.position();
-        let span = Span::from((lhs.span(), rhs.span()));
-        let left = Box::new(lhs);
-        let right = Box::new(rhs);
-        lhs = Expr::Binary(Binary {
-            op,
-            left,
-            right,
-            pos,
-            span,
-        });
+/// Precedence climbing over the infix operators, `min` is the lowest level
+/// an operator needs to be taken by this call. User defined operators are
+/// applied like functions, `a <> b` is `(<>) a b`.
+fn binary(tokens: &mut Vec<Token>, ops: &Operators, min: u8) -> Result<Expr> {
+    let mut lhs = unary(tokens, ops)?;
+    let mut last: Option<Fixity> = None;
+    while let Some((infix, fixity)) = ops.infix(tokens.first()) {
+        // `(2 *)` is a section, the operator is left for `primary`.
+        if fixity.level < min || is_close_paren(tokens.get(1)) {
+            break;
+        }
+        if let Some(last) = last.filter(|last| last.level == fixity.level) {
+            if last.assoc == Assoc::None || fixity.assoc == Assoc::None {
+                let span = tokens.first().map(|t| t.span()).unwrap_or_default();
+                return Err(Error::NonAssociative(Span::from((lhs.span(), span))));
+            }
+        }
+        let token = tokens.remove(0);
+        let next = match fixity.assoc {
+            Assoc::Right => fixity.level,
+            Assoc::Left | Assoc::None => fixity.level + 1,
+        };
+        let rhs = binary(tokens, ops, next)?;
+        lhs = apply_infix(infix, &token, lhs, rhs);
+        last = Some(fixity);
     }
     Ok(lhs)
 }
 
-fn logic_and(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let mut lhs = equality(tokens)?;
-    while let Some(op @ And) = get_op(tokens.get(0)) {
-        tokens.remove(0);
-        let rhs = equality(tokens)?;
-        let pos = rhs.position();
-        let span = Span::from((lhs.span(), rhs.span()));
-        let left = Box::new(lhs);
-        let right = Box::new(rhs);
-        lhs = Expr::Binary(Binary {
+fn apply_infix(infix: Infix, token: &Token, lhs: Expr, rhs: Expr) -> Expr {
+    let pos = rhs.position();
+    let span = Span::from((lhs.span(), rhs.span()));
+    match infix {
+        Infix::Builtin(LRPipe) => pipe(rhs, lhs, pos, span),
+        Infix::Builtin(RLPipe) => pipe(lhs, rhs, pos, span),
+        Infix::Builtin(op) => Expr::Binary(Binary {
             op,
-            left,
-            right,
+            left: Box::new(lhs),
+            right: Box::new(rhs),
             pos,
             span,
-        });
-    }
-    Ok(lhs)
-}
-
-fn equality(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let mut lhs = comparison(tokens)?;
-    while let Some(op @ (Eq | Neq)) = get_op(tokens.get(0)) {
-        tokens.remove(0);
-        let rhs = comparison(tokens)?;
-        let pos = rhs.position();
-        let span = Span::from((lhs.span(), rhs.span()));
-        let left = Box::new(lhs);
-        let right = Box::new(rhs);
-        lhs = Expr::Binary(Binary {
-            op,
-            left,
-            right,
+        }),
+        Infix::User(name) => Expr::App(App {
+            name: Box::new(Expr::Atom(Atom::Id(name, *token.position(), token.span()))),
+            args: vec![lhs, rhs],
             pos,
             span,
-        });
+        }),
     }
-    Ok(lhs)
 }
 
-fn comparison(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let mut lhs = term(tokens)?;
-    while let Some(op @ (Grt | Les | GrtEq | LesEq)) = get_op(tokens.get(0)) {
-        tokens.remove(0);
-        let rhs = term(tokens)?;
-        let pos = rhs.position();
-        let span = Span::from((lhs.span(), rhs.span()));
-        let left = Box::new(lhs);
-        let right = Box::new(rhs);
-        lhs = Expr::Binary(Binary {
-            op,
-            left,
-            right,
-            pos,
-            span,
-        });
+/// Operator sections are closures, `(+)` is `\__lhs -> \__rhs -> __lhs + __rhs`
+/// and `(== 0)` is `\__lhs -> __lhs == 0`. `(- x)` is still a negation. A
+/// user defined prefix operator like `(~)` is the function it names.
+fn section(tokens: &mut Vec<Token>, ops: &Operators, open: Span) -> Result<Option<Expr>> {
+    if let Some(name) = ops
+        .prefix(tokens.first())
+        .filter(|_| is_close_paren(tokens.get(1)))
+    {
+        let token = tokens.remove(0);
+        let name = Expr::Atom(Atom::Id(name, *token.position(), token.span()));
+        return close_section(tokens, open, vec![], name).map(Some);
     }
-    Ok(lhs)
+    let Some((infix, _)) = ops.infix(tokens.first()) else {
+        return Ok(None);
+    };
+    let is_negation = matches!(tokens.first(), Some(Token::Op(Op { lexme, .. })) if lexme == "-")
+        && !is_close_paren(tokens.get(1));
+    if is_negation {
+        return Ok(None);
+    }
+    let token = tokens.remove(0);
+    let (lhs, rhs) = (
+        section_param("__lhs", &token),
+        section_param("__rhs", &token),
+    );
+    if is_close_paren(tokens.first()) {
+        let body = apply_infix(infix, &token, lhs.clone(), rhs.clone());
+        return close_section(tokens, open, vec![lhs, rhs], body).map(Some);
+    }
+    let right = expression(tokens, ops)?;
+    let body = apply_infix(infix, &token, lhs.clone(), right);
+    close_section(tokens, open, vec![lhs], body).map(Some)
 }
 
-fn term(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let mut lhs = factor(tokens)?;
-    while let Some(op @ (Plus | Minus)) = get_op(tokens.get(0)) {
-        tokens.remove(0);
-        let rhs = factor(tokens)?;
-        let pos = rhs.position();
-        let span = Span::from((lhs.span(), rhs.span()));
-        let left = Box::new(lhs);
-        let right = Box::new(rhs);
-        lhs = Expr::Binary(Binary {
-            op,
-            left,
-            right,
+/// `(2 *)` is `\__rhs -> 2 * __rhs`, `lhs` is what was read after `open`.
+fn left_section(
+    tokens: &mut Vec<Token>,
+    ops: &Operators,
+    open: Span,
+    lhs: Expr,
+) -> Result<Expr> {
+    let Some((infix, _)) = ops.infix(tokens.first()) else {
+        return Err(Error::UnclosedParen(Span::from((open, lhs.span()))));
+    };
+    let token = tokens.remove(0);
+    let rhs = section_param("__rhs", &token);
+    let body = apply_infix(infix, &token, lhs, rhs.clone());
+    close_section(tokens, open, vec![rhs], body)
+}
+
+fn section_param(name: &str, token: &Token) -> Expr {
+    Expr::Atom(Atom::Id(
+        name.to_string(),
+        TokenPosition::Middle,
+        token.span(),
+    ))
+}
+
+fn close_section(
+    tokens: &mut Vec<Token>,
+    open: Span,
+    params: Vec<Expr>,
+    body: Expr,
+) -> Result<Expr> {
+    let close = consume_ctrl(tokens, ")")?;
+    let span = Span::from((open, close.span()));
+    let closure = params.into_iter().rev().fold(body, |body, param| {
+        Expr::Closure(Box::new(param), Box::new(body), span)
+    });
+    Ok(closure.map_position(|_| *close.position()))
+}
+
+fn is_close_paren(token: Option<&Token>) -> bool {
+    matches!(token, Some(Token::Ctrl(Ctrl { lexme, .. })) if lexme == ")")
+}
+
+/// Pipes are plain application, `xs |> map f` and `map f <| xs` are both
+/// `map f xs`.
+fn pipe(func: Expr, arg: Expr, pos: TokenPosition, span: Span) -> Expr {
+    match func {
+        Expr::App(App { name, mut args, .. }) => {
+            args.push(arg);
+            Expr::App(App {
+                name,
+                args,
+                pos,
+                span,
+            })
+        }
+        func => Expr::App(App {
+            name: Box::new(func),
+            args: vec![arg],
             pos,
             span,
-        });
+        }),
     }
-    Ok(lhs)
 }
 
-fn factor(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let mut lhs = unary(tokens)?;
-    while let Some(op @ (Mult | Div | Mod)) = get_op(tokens.get(0)) {
-        tokens.remove(0);
-        let rhs = unary(tokens)?;
+fn unary(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
+    if matches!(tokens.first(), Some(Token::Ctrl(c)) if ["λ", "\\"].contains(&c.lexme.as_str()))
+    {
+        return lambda_expression(tokens, ops);
+    }
+    if let Some(name) = ops.prefix(tokens.first()) {
+        let token = tokens.remove(0);
+        let rhs = unary(tokens, ops)?;
         let pos = rhs.position();
-        let span = Span::from((lhs.span(), rhs.span()));
-        let left = Box::new(lhs);
-        let right = Box::new(rhs);
-        lhs = Expr::Binary(Binary {
-            op,
-            left,
-            right,
+        let span = Span::from((token.span(), rhs.span()));
+        let name = Expr::Atom(Atom::Id(name, *token.position(), token.span()));
+        return Ok(Expr::App(App {
+            name: Box::new(name),
+            args: vec![rhs],
             pos,
             span,
-        });
+        }));
     }
-    Ok(lhs)
-}
-
-fn unary(tokens: &mut Vec<Token>) -> Result<Expr> {
-    if let Some(op @ (Minus | Not)) = get_op(tokens.get(0)) {
+    if let Some(op @ (Minus | Not)) = get_op(tokens.first()) {
         let token = tokens.remove(0);
-        let rhs = unary(tokens)?;
+        let rhs = unary(tokens, ops)?;
         let pos = rhs.position();
         let span = Span::from((token.span(), rhs.span()));
         let expr = Box::new(rhs);
@@ -309,68 +1300,59 @@ fn unary(tokens: &mut Vec<Token>) -> Result<Expr> {
             span,
         }));
     }
-    call(tokens)
+    call(tokens, ops)
 }
 
-fn call(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let expr = primary(tokens)?;
+fn call(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
+    let expr = primary(tokens, ops)?;
 
-    let (mut pos, start) = match &expr {
-        Expr::Atom(Atom::Id(_, pos, start)) => (*pos, *start),
-        Expr::Closure(_, tail, span) => (tail.position(), *span),
+    let start = match &expr {
+        Expr::Atom(Atom::Id(_, _, start)) => *start,
+        Expr::Closure(_, _, span) | Expr::Field(_, _, span) => *span,
         _ => return Ok(expr),
     };
-    let next_token = tokens.get(0);
-    let mut pos1 = next_token.map(|t| t.position()).cloned().unwrap_or(pos);
-    if !is_atom(next_token) || is_deliminator(pos, pos1) || is_keyword(next_token) {
+    let next_token = tokens.first();
+    if !is_atom(next_token) || is_keyword(next_token) {
         return Ok(expr);
     }
 
     let mut args = Vec::new();
-    while !tokens.is_empty() {
-        let next_token = tokens.get(0);
-        if !is_atom(next_token) || is_keyword(next_token) {
-            break;
-        }
-        let atom = primary(tokens)?;
-        pos = atom.position();
-
-        args.push(atom);
-
-        let next_token = tokens.get(0);
-        pos1 = next_token.map(|t| t.position()).cloned().unwrap_or(pos);
-
-        if is_deliminator(pos, pos1) {
-            break;
-        }
+    while is_atom(tokens.first()) && !is_keyword(tokens.first()) {
+        args.push(primary(tokens, ops)?);
     }
     let end = args.last().map(|e| e.span()).unwrap_or(start);
     let pos = args.last().map(|e| e.position()).unwrap_or(expr.position());
 
-    return Ok(Expr::App(App {
+    Ok(Expr::App(App {
         name: Box::new(expr),
         args,
         pos,
         span: Span::from((start, end)),
-    }));
+    }))
 }
 
-fn primary(tokens: &mut Vec<Token>) -> Result<Expr> {
-    let Some(_) = tokens.get(0) else {
+fn primary(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Expr> {
+    let Some(_) = tokens.first() else {
         return Err(Error::UnexpectedEndOfInput(Span::default()));
     };
     match tokens.remove(0) {
-        Token::Int(num) => Ok(Expr::Atom(Atom::Int(
-            num.lexme.parse().unwrap_or_default(),
-            num.pos,
-            num.span,
-        ))),
-        Token::Float(float) => Ok(Expr::Atom(Atom::Float(
-            float.lexme.parse().unwrap_or_default(),
-            float.pos,
-            float.span,
-        ))),
-        Token::Ident(id) => Ok(Expr::Atom(Atom::Id(id.lexme, id.pos, id.span))),
+        Token::Int(num) => match int(&num.lexme) {
+            Some(int) => Ok(Expr::Atom(Atom::Int(int, num.pos, num.span))),
+            None => Err(Error::InvalidNumber(num.span)),
+        },
+        Token::Float(float) => match float.lexme.replace('_', "").parse() {
+            Ok(value) => Ok(Expr::Atom(Atom::Float(value, float.pos, float.span))),
+            Err(_) => Err(Error::InvalidNumber(float.span)),
+        },
+        Token::Ident(id) if is_constructor(&id.lexme) && is_record_start(tokens) => {
+            let (fields, end) = field_values(tokens, ops)?;
+            let span = Span::from((id.span, end));
+            postfix(tokens, ops, Expr::Record(id.lexme, fields, span))
+        }
+        Token::Ident(id) => {
+            let atom = Expr::Atom(qualified(tokens, id));
+            postfix(tokens, ops, atom)
+        }
         Token::KeyWord(kw) if kw.lexme == "true" => Ok(Expr::Atom(Atom::Bool(
             kw.lexme.parse().unwrap_or_default(),
             kw.pos,
@@ -381,6 +1363,7 @@ fn primary(tokens: &mut Vec<Token>) -> Result<Expr> {
             kw.pos,
             kw.span,
         ))),
+        Token::Str(string) if !string.parts.is_empty() => interpolate(string, ops),
         Token::Str(string) => Ok(Expr::Atom(Atom::String(
             string.lexme,
             string.pos,
@@ -392,39 +1375,329 @@ fn primary(tokens: &mut Vec<Token>) -> Result<Expr> {
             c.span,
         ))),
         Token::Ctrl(c) if c.lexme == "(" => {
-            let expr = expression(tokens)?;
-            let Some(Token::Ctrl(Ctrl { pos, .. })) = consume_ctrl_if(tokens, ")") else {
-                return Err(Error::UnclosedParen(expr.span()));
+            if let Some(section) = section(tokens, ops, c.span)? {
+                return postfix(tokens, ops, section);
+            }
+            let expr = expression(tokens, ops)?;
+            if is_close_paren(tokens.get(1)) && ops.infix(tokens.first()).is_some() {
+                let section = left_section(tokens, ops, c.span, expr)?;
+                return postfix(tokens, ops, section);
+            }
+            let mut items = vec![expr];
+            while consume_ctrl_if(tokens, ",").is_some() {
+                items.push(expression(tokens, ops)?);
+            }
+            let Some(Token::Ctrl(Ctrl { pos, span, .. })) = consume_ctrl_if(tokens, ")")
+            else {
+                let end = items.last().map(Expr::span).unwrap_or(c.span);
+                return Err(Error::UnclosedParen(Span::from((c.span, end))));
+            };
+            let expr = match items.len() {
+                1 => items.remove(0).map_position(|_| pos),
+                _ => Expr::Tuple(items, Span::from((c.span, span))),
             };
-            Ok(expr.map_position(|_| pos))
+            postfix(tokens, ops, expr)
         }
-        Token::Ctrl(c) if c.lexme == "[" => array(tokens, c.span),
+        Token::Ctrl(c) if c.lexme == "[" => array(tokens, ops, c.span),
         token => Err(Error::UnexpectedToken(
-            "(, [, ident, str, int, float, true, false, char".to_string(),
-            format!("{token:?}\r\n{:?}", tokens.get(1)),
+            "expression".to_string(),
+            token.to_string(),
             token.span(),
         )),
     }
 }
 
-fn array(tokens: &mut Vec<Token>, start: Span) -> Result<Expr> {
+/// Reads fields with `.` and updates records with `{ field = value }`, both
+/// bind tighter than application, `f point.x` passes the field to `f`.
+fn postfix(tokens: &mut Vec<Token>, ops: &Operators, mut expr: Expr) -> Result<Expr> {
+    loop {
+        let is_field = matches!(tokens.first(), Some(Token::Op(Op { lexme, .. })) if lexme == ".")
+            && matches!(tokens.get(1), Some(Token::Ident(Ident { lexme, .. })) if !is_constructor(lexme));
+        if is_field {
+            tokens.remove(0);
+            let Token::Ident(Ident { lexme, span, .. }) = tokens.remove(0) else {
+                unreachable!();
+            };
+            let span = Span::from((expr.span(), span));
+            expr = Expr::Field(Box::new(expr), lexme, span);
+            continue;
+        }
+        if is_record_start(tokens) {
+            let (fields, end) = field_values(tokens, ops)?;
+            let span = Span::from((expr.span(), end));
+            expr = Expr::Update(Box::new(expr), fields, span);
+            continue;
+        }
+        return Ok(expr);
+    }
+}
+
+/// The layout only puts `{` after a keyword, so one after an expression
+/// always starts the fields of a record.
+fn is_record_start(tokens: &[Token]) -> bool {
+    matches!(tokens.first(), Some(Token::Ctrl(Ctrl { lexme, .. })) if lexme == "{")
+}
+
+/// Reads the `.map` after `Option` when `id` is a type, returning the name
+/// of the method along with where it ends.
+fn method(tokens: &mut Vec<Token>, id: &Ident) -> Option<(String, TokenPosition, Span)> {
+    if !is_constructor(&id.lexme) || !is_op(tokens.first(), ".") {
+        return None;
+    }
+    let Some(Token::Ident(end)) = tokens.get(1).cloned() else {
+        return None;
+    };
+    if is_constructor(&end.lexme) {
+        return None;
+    }
+    tokens.drain(..2);
+    let name = format!("{}.{}", id.lexme, end.lexme);
+    Some((name, end.pos, Span::from((id.span, end.span))))
+}
+
+/// Names from an imported module are written after the module and `::`,
+/// `Utils::double` or `Data.List::map`, and methods after their type,
+/// `Option.map`.
+fn qualified(tokens: &mut Vec<Token>, id: Ident) -> Atom {
+    let mut len = 0;
+    while matches!(tokens.get(len), Some(Token::Op(Op { lexme, .. })) if lexme == ".")
+        && matches!(tokens.get(len + 1), Some(Token::Ident(_)))
+    {
+        len += 2;
+    }
+    let is_qualified = matches!(tokens.get(len), Some(Token::Ctrl(Ctrl { lexme, .. })) if lexme == "::")
+        && matches!(tokens.get(len + 1), Some(Token::Ident(_)));
+    if !is_qualified {
+        return match method(tokens, &id) {
+            Some((name, pos, span)) => Atom::Id(name, pos, span),
+            None => Atom::Id(id.lexme, id.pos, id.span),
+        };
+    }
+    let path = tokens.drain(..len + 2).collect::<Vec<_>>();
+    let Some(Token::Ident(name)) = path.last() else {
+        unreachable!();
+    };
+    let module = path[..len]
+        .iter()
+        .map(ToString::to_string)
+        .collect::<String>();
+    let span = Span::from((id.span, name.span));
+    Atom::Id(
+        format!("{}{module}::{}", id.lexme, name.lexme),
+        name.pos,
+        span,
+    )
+}
+
+fn array(tokens: &mut Vec<Token>, ops: &Operators, start: Span) -> Result<Expr> {
     let mut exprs = Vec::new();
     while !tokens.is_empty() {
-        if matches!(tokens.get(0), Some(Token::Ctrl(Ctrl{lexme, ..})) if lexme == "]") {
+        if matches!(tokens.first(), Some(Token::Ctrl(Ctrl{lexme, ..})) if lexme == "]") {
             break;
         }
-        let expr = expression(tokens)?;
+        let expr = expression(tokens, ops)?;
         exprs.push(expr);
+        if is_op(tokens.first(), "..") {
+            return range(tokens, ops, start, exprs);
+        }
+        if is_op(tokens.first(), "|") && exprs.len() == 1 {
+            return comprehension(tokens, ops, start, exprs.remove(0));
+        }
         consume_ctrl_if(tokens, ",");
     }
-    let Some(Token::Ctrl(Ctrl { span: end, pos, .. })) = consume_ctrl_if(tokens, "]")
-    else {
-        return Err(Error::UnclosedArray(start).into());
-    };
+    let (pos, end) = close_array(tokens, start)?;
     let span = Span::from((start, end));
     Ok(Expr::Array(exprs, pos, span))
 }
 
+fn close_array(tokens: &mut Vec<Token>, start: Span) -> Result<(TokenPosition, Span)> {
+    let Some(Token::Ctrl(Ctrl { span, pos, .. })) = consume_ctrl_if(tokens, "]") else {
+        return Err(Error::UnclosedArray(start));
+    };
+    Ok((pos, span))
+}
+
+fn is_op(token: Option<&Token>, expected: &str) -> bool {
+    matches!(token, Some(Token::Op(Op { lexme, .. })) if lexme == expected)
+}
+
+/// `[1..10]` counts up to and including the end and `[0, 2 .. 20]` steps by
+/// the distance between the first two items, both are `__range start step end`.
+fn range(
+    tokens: &mut Vec<Token>,
+    ops: &Operators,
+    start: Span,
+    mut items: Vec<Expr>,
+) -> Result<Expr> {
+    let dots = tokens.remove(0);
+    if items.len() > 2 {
+        return Err(Error::UnexpectedToken(
+            "]".to_string(),
+            dots.to_string(),
+            dots.span(),
+        ));
+    }
+    let last = expression(tokens, ops)?;
+    let (pos, end) = close_array(tokens, start)?;
+    let span = Span::from((start, end));
+    let first = items.remove(0);
+    let step = match items.pop() {
+        Some(then) => Expr::Binary(Binary {
+            op: Minus,
+            span: then.span(),
+            left: Box::new(then),
+            right: Box::new(first.clone()),
+            pos,
+        }),
+        None => Expr::Atom(Atom::Int(1, pos, dots.span())),
+    };
+    Ok(primitive("__range", vec![first, step, last], pos, span))
+}
+
+enum Qualifier {
+    Generator(Pattern, Expr),
+    Guard(Expr),
+}
+
+/// `[f x | x <- xs, p x]` is `__map (\x -> f x) (__filter (\x -> p x) xs)`.
+fn comprehension(
+    tokens: &mut Vec<Token>,
+    ops: &Operators,
+    start: Span,
+    head: Expr,
+) -> Result<Expr> {
+    tokens.remove(0);
+    let mut qualifiers = vec![qualifier(tokens, ops)?];
+    while consume_ctrl_if(tokens, ",").is_some() {
+        qualifiers.push(qualifier(tokens, ops)?);
+    }
+    let (pos, end) = close_array(tokens, start)?;
+    let span = Span::from((start, end));
+    Ok(desugar_comprehension(head, qualifiers, pos, span))
+}
+
+fn qualifier(tokens: &mut Vec<Token>, ops: &Operators) -> Result<Qualifier> {
+    if !is_generator(tokens) {
+        return expression(tokens, ops).map(Qualifier::Guard);
+    }
+    let (pattern, source) = generator(tokens, ops)?;
+    Ok(Qualifier::Generator(pattern, source))
+}
+
+/// `pattern <- expression`, in a list comprehension or a `do` block.
+fn generator(tokens: &mut Vec<Token>, ops: &Operators) -> Result<(Pattern, Expr)> {
+    let pattern = pattern(tokens, ops)?;
+    if consume_op_if(tokens, "<-").is_none() {
+        let token = tokens.first();
+        return Err(Error::UnexpectedToken(
+            "<-".to_string(),
+            token.map(ToString::to_string).unwrap_or_default(),
+            token.map(Token::span).unwrap_or(pattern.span()),
+        ));
+    }
+    let source = expression(tokens, ops)?;
+    Ok((pattern, source))
+}
+
+/// A qualifier or a line of a `do` block is a generator when a `<-` shows up
+/// before the `,`, `;` or `]` ending it.
+fn is_generator(tokens: &[Token]) -> bool {
+    let mut depth = 0usize;
+    for token in tokens {
+        match token {
+            Token::Op(Op { lexme, .. }) if lexme == "<-" && depth == 0 => return true,
+            Token::Ctrl(Ctrl { lexme, .. }) => match lexme.as_str() {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" | "," | ";" if depth == 0 => return false,
+                ")" | "]" | "}" => depth -= 1,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Guards right after a generator filter it and the last generator maps the
+/// head over what is left. Earlier generators map to the lists built by the
+/// ones after them which are flattened with `__concat`. A guard before any
+/// generator picks between the rest of the list and `[]`.
+fn desugar_comprehension(
+    head: Expr,
+    mut qualifiers: Vec<Qualifier>,
+    pos: TokenPosition,
+    span: Span,
+) -> Expr {
+    if qualifiers.is_empty() {
+        return Expr::Array(vec![head], pos, span);
+    }
+    let (pattern, mut source) = match qualifiers.remove(0) {
+        Qualifier::Generator(pattern, source) => (pattern, source),
+        Qualifier::Guard(cond) => {
+            let then = desugar_comprehension(head, qualifiers, pos, span);
+            let r#else = Expr::Array(vec![], pos, span);
+            return Expr::IfElse(Box::new(cond), Box::new(then), Box::new(r#else), span);
+        }
+    };
+    // Items a constructor or literal pattern does not match are skipped.
+    if !matches!(pattern, Pattern::Id(..) | Pattern::Wildcard(..)) {
+        let yes = Expr::Atom(Atom::Bool(true, pos, span));
+        let no = Expr::Atom(Atom::Bool(false, pos, span));
+        let test = Expr::Match(
+            Box::new(item(pos, span)),
+            vec![(pattern.clone(), yes), (Pattern::Wildcard(span), no)],
+            span,
+        );
+        let test = Expr::Closure(Box::new(item(pos, span)), Box::new(test), span);
+        source = primitive("__filter", vec![test, source], pos, span);
+    }
+    while matches!(qualifiers.first(), Some(Qualifier::Guard(..))) {
+        let Qualifier::Guard(cond) = qualifiers.remove(0) else {
+            unreachable!();
+        };
+        let test = bind(&pattern, cond, pos, span);
+        source = primitive("__filter", vec![test, source], pos, span);
+    }
+    if qualifiers.is_empty() {
+        let body = bind(&pattern, head, pos, span);
+        return primitive("__map", vec![body, source], pos, span);
+    }
+    let rest = desugar_comprehension(head, qualifiers, pos, span);
+    let body = bind(&pattern, rest, pos, span);
+    let lists = primitive("__map", vec![body, source], pos, span);
+    primitive("__concat", vec![lists], pos, span)
+}
+
+/// A closure binding `pattern` to its argument.
+fn bind(pattern: &Pattern, body: Expr, pos: TokenPosition, span: Span) -> Expr {
+    let (param, body) = match pattern {
+        Pattern::Id(name, id_span) => {
+            (Expr::Atom(Atom::Id(name.clone(), pos, *id_span)), body)
+        }
+        pattern => {
+            let arms = vec![(pattern.clone(), body)];
+            (
+                item(pos, span),
+                Expr::Match(Box::new(item(pos, span)), arms, span),
+            )
+        }
+    };
+    Expr::Closure(Box::new(param), Box::new(body), span)
+}
+
+fn item(pos: TokenPosition, span: Span) -> Expr {
+    Expr::Atom(Atom::Id("__item".to_string(), pos, span))
+}
+
+fn primitive(name: &str, args: Vec<Expr>, pos: TokenPosition, span: Span) -> Expr {
+    Expr::App(App {
+        name: Box::new(Expr::Atom(Atom::Id(name.to_string(), pos, span))),
+        args,
+        pos,
+        span,
+    })
+}
+
 fn is_atom(token: Option<&Token>) -> bool {
     let Some(token) = token else { return false };
     let lexme = match token {
@@ -438,10 +1711,7 @@ fn is_atom(token: Option<&Token>) -> bool {
         _ => return false,
     };
 
-    match lexme {
-        "true" | "false" | "(" | "[" => true,
-        _ => false,
-    }
+    matches!(lexme, "true" | "false" | "(" | "[")
 }
 
 fn is_keyword(token: Option<&Token>) -> bool {
@@ -495,19 +1765,65 @@ fn test_get_op() {
     assert_eq!(keyword("mod"), Some(Oper::Mod));
 }
 
+/// `"Hello {name}!"` is `"Hello " + name + "!"`, a string starting with
+/// code gets an empty string in front so `+` always joins strings.
+fn interpolate(string: Str, ops: &Operators) -> Result<Expr> {
+    let Str {
+        parts, pos, span, ..
+    } = string;
+    let text = |text| Expr::Atom(Atom::String(text, pos, span));
+    let mut pieces = Vec::new();
+    if matches!(parts.first(), Some(Part::Code(_))) {
+        pieces.push(text(String::new()));
+    }
+    for part in parts {
+        match part {
+            Part::Text(string) => pieces.push(text(string)),
+            Part::Code(mut tokens) => {
+                if tokens.is_empty() {
+                    return Err(Error::UnexpectedToken(
+                        "expression".to_string(),
+                        "}".to_string(),
+                        span,
+                    ));
+                }
+                pieces.push(expression(&mut tokens, ops)?);
+                if let Some(token) = tokens.first() {
+                    return Err(Error::UnexpectedToken(
+                        "}".to_string(),
+                        token.to_string(),
+                        token.span(),
+                    ));
+                }
+            }
+        }
+    }
+    let mut pieces = pieces.into_iter();
+    let first = pieces.next().unwrap_or_else(|| text(String::new()));
+    Ok(pieces.fold(first, |left, right| {
+        Expr::Binary(Binary {
+            op: Plus,
+            left: Box::new(left),
+            right: Box::new(right),
+            pos,
+            span,
+        })
+    }))
+}
+
 fn consume_ctrl(tokens: &mut Vec<Token>, expected: &str) -> Result<Token> {
-    let Some(Token::Ctrl(Ctrl { lexme, span, .. })) = tokens.get(0) else {
-        let span = tokens.get(0).map(|t| t.span()).unwrap_or_default();
+    let Some(Token::Ctrl(Ctrl { lexme, span, .. })) = tokens.first() else {
+        let span = tokens.first().map(|t| t.span()).unwrap_or_default();
         return Err(Error::UnexpectedToken(
             expected.to_string(),
-            tokens.get(0).map(|t| t.to_string()).unwrap_or_default(),
+            tokens.first().map(|t| t.to_string()).unwrap_or_default(),
             span,
         ));
     };
     if lexme != expected {
         return Err(Error::UnexpectedToken(
             expected.to_string(),
-            tokens.get(0).map(|t| t.to_string()).unwrap_or_default(),
+            tokens.first().map(|t| t.to_string()).unwrap_or_default(),
             *span,
         ));
     }
@@ -515,39 +1831,70 @@ fn consume_ctrl(tokens: &mut Vec<Token>, expected: &str) -> Result<Token> {
 }
 
 fn consume_ctrl_if(tokens: &mut Vec<Token>, expected: &str) -> Option<Token> {
-    let token = tokens.get(0);
+    let token = tokens.first();
     if !matches!(&token, Some(Token::Ctrl(Ctrl{lexme, ..})) if lexme == expected) {
         return None;
     }
-    return Some(tokens.remove(0));
+    Some(tokens.remove(0))
 }
 
 fn consume_op(tokens: &mut Vec<Token>, expected: &str) -> Result<Token> {
-    let token = tokens.get(0);
+    let token = tokens.first();
     if matches!(&token, Some(Token::Op(Op{lexme, ..})) if lexme != expected) {
         let span = token.map(|t| t.span()).unwrap_or_default();
         return Err(Error::UnexpectedToken(
             expected.to_string(),
-            tokens.get(0).map(|t| t.to_string()).unwrap_or_default(),
+            tokens.first().map(|t| t.to_string()).unwrap_or_default(),
             span,
         ));
     }
     Ok(tokens.remove(0))
 }
 
+fn consume_op_if(tokens: &mut Vec<Token>, expected: &str) -> Option<Token> {
+    let token = tokens.first();
+    if !matches!(&token, Some(Token::Op(Op{lexme, ..})) if lexme == expected) {
+        return None;
+    }
+    Some(tokens.remove(0))
+}
+
+/// Operators are scanned as long as possible, so the `>>` closing
+/// `Array<Array<Int>>` is split back up when a single `>` is expected.
+fn split_op(tokens: &mut Vec<Token>, expected: &str) {
+    let Some(Token::Op(op)) = tokens.first_mut() else {
+        return;
+    };
+    if op.lexme.len() <= expected.len() || !op.lexme.starts_with(expected) {
+        return;
+    }
+    let lexme = op.lexme.split_off(expected.len());
+    let mut span = op.span;
+    span.idx_start += expected.len();
+    span.col_start += expected.len();
+    op.span.idx_end = span.idx_start;
+    op.span.col_end = span.col_start;
+    let pos = std::mem::replace(&mut op.pos, TokenPosition::Middle);
+    let rest = match lexme.as_str() {
+        "=" | "->" | ":" | "::" => Token::Ctrl(Ctrl { lexme, pos, span }),
+        _ => Token::Op(Op { lexme, pos, span }),
+    };
+    tokens.insert(1, rest);
+}
+
 fn consume_keyword(tokens: &mut Vec<Token>, expected: &str) -> Result<Token> {
-    let Some(Token::KeyWord(KeyWord { lexme, span, .. })) = tokens.get(0) else {
-        let span = tokens.get(0).map(|t| t.span()).unwrap_or_default();
+    let Some(Token::KeyWord(KeyWord { lexme, span, .. })) = tokens.first() else {
+        let span = tokens.first().map(|t| t.span()).unwrap_or_default();
         return Err(Error::UnexpectedToken(
             expected.to_string(),
-            tokens.get(0).map(|t| t.to_string()).unwrap_or_default(),
+            tokens.first().map(|t| t.to_string()).unwrap_or_default(),
             span,
         ));
     };
     if lexme != expected {
         return Err(Error::UnexpectedToken(
             expected.to_string(),
-            tokens.get(0).map(|t| t.to_string()).unwrap_or_default(),
+            tokens.first().map(|t| t.to_string()).unwrap_or_default(),
             *span,
         ));
     }
@@ -555,30 +1902,540 @@ fn consume_keyword(tokens: &mut Vec<Token>, expected: &str) -> Result<Token> {
 }
 
 fn consume_keyword_if(tokens: &mut Vec<Token>, expected: &str) -> Option<Token> {
-    let token = tokens.get(0);
+    let token = tokens.first();
     if !matches!(&token, Some(Token::KeyWord(KeyWord{lexme, ..})) if lexme == expected) {
         return None;
     }
-    return Some(tokens.remove(0));
+    Some(tokens.remove(0))
 }
 
-fn is_deliminator(pos1: TokenPosition, pos2: TokenPosition) -> bool {
-    use TokenPosition::*;
-    match (pos1, pos2) {
-        (_, FullSpan) => true,
-        (End, Start | End) => true,
-        _ => false,
+#[test]
+fn parse_test() {
+    use pretty_assertions::assert_eq;
+    let src = include_str!("./../../../samples/other.snow");
+    let ast = parse(src);
+    let left = match ast {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<map:  = (\\f -> (\\arr -> (if ((== <length: (arr)> 0)) then [] else (+ [<f: (<head: (arr)>)>] <map: (f, <tail: (arr)>)>))))>",
+            "<addOne:  = (\\x -> (+ x 1))>",
+            "<main:  = <print: (<map: (addOne, [1, 2])>, \n)>>",
+        ]
+    );
+}
+
+#[test]
+fn match_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+unwrap x
+  = match x on
+  | Some (Pair a _) -> a
+  | None -> -1
+
+firstTwo xs = match xs on [a, b, ..rest] -> a + b | [_] -> 0 | _ -> "empty"
+"#;
+    let ast = parse(src);
+    let left = match ast {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<unwrap:  = (\\x -> (match x on | (Some (Pair a _)) -> a | None -> (- 1)))>",
+            "<firstTwo:  = (\\xs -> (match xs on | [a, b, ..rest] -> (+ a b) | [_] -> 0 | _ -> empty))>",
+        ]
+    );
+}
+
+#[test]
+fn enum_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+enum Option a
+  = Some a
+  | None
+
+enum List a = Cons a (List a) | Nil
+
+enum Grid = Grid (Array<Int>)
+
+just = Some 1
+"#;
+    let ast = parse(src);
+    let left = match ast {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<Option a: (Some, [a]), (None, [])>",
+            "<List a: (Cons, [a, List a]), (Nil, [])>",
+            "<Grid: (Grid, [Array<Int>])>",
+            "<just:  = <Some: (1)>>",
+        ]
+    );
+}
+
+#[test]
+fn let_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+area r = pi * r * r where pi = 3
+
+main = let double x = x * 2, ten = 10 in double ten
+"#;
+    let ast = parse(src);
+    let left = match ast {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<area:  = (\\r -> (let <pi:  = 3> in (* (* pi r) r)))>",
+            "<main:  = (let <double:  = (\\x -> (* x 2))>, <ten:  = 10> in <double: (ten)>)>",
+        ]
+    );
+}
+
+#[test]
+fn operator_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+infixr 6 <>
+infix 4 ===
+
+`<>` xs ys = xs + ys
+
+`~` n = 0 - n
+
+main = [1] <> [2] <> [3] + [4] * 5 === ~ 6 >>= 7 >>= 8
+"#;
+    let ast = parse(src);
+    let left = match ast {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<(<>):  = (\\xs -> (\\ys -> (+ xs ys)))>",
+            "<(~):  = (\\n -> (- 0 n))>",
+            "<main:  = <(===): (<(<>): ([1], <(<>): ([2], (+ [3] (* [4] 5)))>)>, <(>>=): (<(>>=): (<(~): (6)>, 7)>, 8)>)>>",
+        ]
+    );
+    assert!(matches!(
+        &parse("infixl 10 <>").unwrap_err()[..],
+        [Error::InvalidFixity(..)]
+    ));
+    assert!(matches!(
+        &parse("infix 4 ===\n\nmain = 1 === 2 === 3").unwrap_err()[..],
+        [Error::NonAssociative(..)]
+    ));
+}
+
+#[test]
+fn pipe_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+right = add 1 <| 2
+
+left = 2 |> add 1
+
+both = 2 |> add <| 1
+
+chain = [1, 2] |> map f |> print <| 3 + 4
+"#;
+    let ast = parse(src);
+    let left = match ast {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<right:  = <add: (1, 2)>>",
+            "<left:  = <add: (1, 2)>>",
+            "<both:  = <add: (1, 2)>>",
+            "<chain:  = <print: ((+ 3 4), <map: (f, [1, 2])>)>>",
+        ]
+    );
+}
+
+#[test]
+fn layout_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+describe x y = match x on
+  | Some a -> match y on
+    | Some b -> a + b
+    | None -> a
+  | None -> total
+  where
+    total = 0
+    other = 1
+
+main =
+  let double x = x * 2
+      ten = 10
+  in [double ten, (match ten on 10 -> 1 | _ -> 2)]
+"#;
+    let ast = parse(src);
+    let left = match ast {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<describe:  = (\\x -> (\\y -> (let <total:  = 0>, <other:  = 1> in (match x on | (Some a) -> (match y on | (Some b) -> (+ a b) | None -> a) | None -> total))))>",
+            "<main:  = (let <double:  = (\\x -> (* x 2))>, <ten:  = 10> in [<double: (ten)>, (match ten on | 10 -> 1 | _ -> 2)])>",
+        ]
+    );
+    let errors = |src: &str| parse(src).unwrap_err();
+    assert!(matches!(
+        &errors("main = match 1 on\n    | 1 -> 2\n  | _ -> 3")[..],
+        [Error::Indentation(..)]
+    ));
+    assert!(matches!(
+        &errors("f x = let\n    a = 1\n   b = 2\n  in a + b")[..],
+        [Error::Indentation(..)]
+    ));
+    assert!(matches!(
+        &errors("main = do\n  let\n      a = 1\n     b = 2\n  print a")[..],
+        [Error::Indentation(..)]
+    ));
+    for src in [
+        "main = let a = 1\n             b = 2\n       in a + b",
+        "f x =\nx + 1\n\nmain = f 1",
+        "f x = 1\n  g y = 2\n\nmain = f 1",
+    ] {
+        assert!(
+            matches!(&errors(src)[..], [Error::Indentation(..)]),
+            "{src}"
+        );
     }
+    assert!(matches!(
+        &errors("area r = pi * r where\n\nmain = 1")[..],
+        [Error::ExpectedBlock(..)]
+    ));
+    let src = "main = 1 +\n";
+    let report = errors(src)[0].report("t.snow", src);
+    assert!(report.contains("t.snow:1:10"), "{report}");
+    assert!(matches!(
+        &errors("main = foo\n  where foo = (1\nbar = 2")[..],
+        [Error::UnclosedParen(..)]
+    ));
 }
 
 #[test]
-fn parse_test() {
+fn clauses_test() {
     use pretty_assertions::assert_eq;
-    let src = include_str!("./../../../samples/other.snow");
+    let src = r#"
+fromOption d None = d
+fromOption _ (Some x) = x
+
+sign : Int -> Int
+sign 0 = 0
+sign n
+  | n > 0 = 1
+  | true = small
+  where small = -1
+
+main = let
+    isZero 0 = true
+    isZero _ = false
+  in isZero 1
+"#;
+    let ast = parse(src);
+    let left = match ast {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<fromOption:  = (\\__arg0 -> (\\__arg1 -> (match __arg0 __arg1 on | d None -> d | _ (Some x) -> x)))>",
+            "<sign: Int -> Int = (\\__arg0 -> (match __arg0 on | 0 -> 0 | n when (> n 0) -> 1 when true -> small where <small:  = (- 1)>))>",
+            "<main:  = (let <isZero:  = (\\__arg0 -> (match __arg0 on | 0 -> true | _ -> false))> in <isZero: (1)>)>",
+        ]
+    );
+    let errors = |src: &str| parse(src).unwrap_err();
+    assert!(matches!(
+        &errors("f 0 = 1\nf x y = 2")[..],
+        [Error::ClauseArity(name, ..)] if name == "f"
+    ));
+    assert!(matches!(
+        &errors("f : Int -> Int\n\nmain = 1")[..],
+        [Error::MissingDefinition(name, ..)] if name == "f"
+    ));
+}
+
+#[test]
+fn record_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+data Pair a b = { first: a, second: (Int, b) }
+
+swap (a, b) : (a, b) -> (b, a) = (b, a)
+
+main = f (Pair { first = 1, second = (2, 3) }) { first = 4 }.second p.first.x
+"#;
     let ast = parse(src);
     let left = match ast {
         Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
         Err(err) => panic!("{:?}", err),
     };
-    assert_eq!(left, vec!["<add: (\\x -> (\\y -> (+ x y)))>"]);
+    assert_eq!(
+        left,
+        vec![
+            "<Pair a b: { first: a, second: (Int, b) }>",
+            "<swap: (a, b) -> (b, a) = (\\__arg0 -> (match __arg0 on | (a, b) -> (b, a)))>",
+            "<main:  = <f: ((Pair { first = 1, second = (2, 3) } { first = 4 }).second, p.first.x)>>",
+        ]
+    );
+}
+
+#[test]
+fn method_test() {
+    use pretty_assertions::assert_eq;
+    let src = r#"
+Option.map : (a -> b) -> Option a -> Option b
+Option.map f = match self on Some x -> Some (f x) | None -> None
+
+main = f (Option.map g opt) (opt.map g) Data.List::map
+"#;
+    let left = match parse(src) {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            [
+                r"<Option.map: (a -> b) -> Option a -> Option b = (\f -> (\self -> ",
+                "(match self on | (Some x) -> <Some: (<f: (x)>)> | None -> None)))>",
+            ]
+            .concat(),
+            "<main:  = <f: (<Option.map: (g, opt)>, <opt.map: (g)>, Data.List::map)>>"
+                .into(),
+        ]
+    );
+}
+
+#[test]
+fn alias_test() {
+    use pretty_assertions::assert_eq;
+    let src = "type Grid = Array<Array<Int>>\n\ntype Step a = (a -> a) -> Grid";
+    let ast = parse(src).unwrap();
+    let left = ast.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        left,
+        vec![
+            "<type Grid = Array<Array<Int>>>",
+            "<type Step a = (a -> a) -> Grid>",
+        ]
+    );
+    assert!(matches!(
+        &parse("type Grid =\n\nmain = 1").unwrap_err()[..],
+        [Error::ExpectedType(..)]
+    ));
+}
+
+#[test]
+fn recovery_test() {
+    use pretty_assertions::assert_eq;
+    let src = "add x y = x + y\n\nbroken x = x +\n\nenum = 1\n\nmain = add 1 2";
+    let (ast, errors) = parse_with_recovery(src);
+    let left = ast.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        left,
+        vec![
+            "<add:  = (\\x -> (\\y -> (+ x y)))>",
+            "<broken:  = (\\x -> Error)>",
+            "Error",
+            "<main:  = <add: (1, 2)>>",
+        ]
+    );
+    assert_eq!(errors.len(), 2);
+    assert_eq!(parse(src).unwrap_err().len(), 2);
+}
+
+#[test]
+fn unclosed_comment_test() {
+    let src = "main = print 1\n\n{- open {- nested -}\nfoo = 2\n";
+    let errors = parse(src).unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [Error::UnclosedComment(span)] if span.row_start == 2 && span.range() == (16..18)
+    ));
+    assert_eq!(parse_with_recovery(src).1.len(), 1);
+    assert!(parse("{- {- -} -}\nmain = print 1 {-}-}\n").is_ok());
+}
+
+#[test]
+fn string_test() {
+    let src = r#"greet name = "Hi {name}!"
+shout x = "{x + "!"}"
+raw = r"\n{x}"
+"#;
+    let left = match parse(src) {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            r"<greet:  = (\name -> (+ (+ Hi  name) !))>",
+            r"<shout:  = (\x -> (+  (+ x !)))>",
+            r"<raw:  = \n{x}>",
+        ]
+    );
+    let errors = |src: &str| parse(src).unwrap_err();
+    assert!(matches!(
+        &errors("main = \"never closed\n")[..],
+        [Error::UnclosedString(span)] if span.range() == (7..8)
+    ));
+    assert!(matches!(
+        &errors("main = \"a \\q\"")[..],
+        [Error::InvalidEscape(span)] if span.range() == (10..12)
+    ));
+    assert!(matches!(
+        &errors("main = 'ab'")[..],
+        [Error::InvalidCharLiteral(span)] if span.range() == (7..11)
+    ));
+    assert!(matches!(
+        &errors("main = \"a {} b\"")[..],
+        [Error::UnexpectedToken(expected, ..)] if expected == "expression"
+    ));
+    assert!(matches!(
+        &errors("main = \"a {x y)} b\"")[..],
+        [Error::UnexpectedToken(expected, found, ..)] if expected == "}" && found == ")"
+    ));
+}
+
+#[test]
+fn number_test() {
+    let src = "ints = [1_000, 0xff, 0o17, 0b101, 9223372036854775807]\nfloats = [1.5, 2e3, 2.5e-1]\nneg -1.5 = 0";
+    let left = match parse(src) {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left,
+        vec![
+            "<ints:  = [1000, 255, 15, 5, 9223372036854775807]>",
+            "<floats:  = [1.5, 2000.0, 0.25]>",
+            "<neg:  = (\\__arg0 -> (match __arg0 on | -1.5 -> 0))>",
+        ]
+    );
+    let errors = |src: &str| parse(src).unwrap_err();
+    assert!(matches!(
+        &errors("main = 9223372036854775808")[..],
+        [Error::InvalidNumber(span)] if span.range() == (7..26)
+    ));
+    assert!(matches!(
+        &errors("main = 0b102")[..],
+        [Error::InvalidNumber(span)] if span.range() == (7..12)
+    ));
+}
+
+#[test]
+fn section_test() {
+    let src = "infixl 6 <>\n\
+               `<>` a b = a\n\
+               sections = [(+), (== 0), (2 *), (<> 1), (1 <>), (- 1), (-)]";
+    let left = match parse(src) {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left[1],
+        [
+            "<sections:  = [",
+            r"(\__lhs -> (\__rhs -> (+ __lhs __rhs))), ",
+            r"(\__lhs -> (== __lhs 0)), ",
+            r"(\__rhs -> (* 2 __rhs)), ",
+            r"(\__lhs -> <(<>): (__lhs, 1)>), ",
+            r"(\__rhs -> <(<>): (1, __rhs)>), ",
+            "(- 1), ",
+            r"(\__lhs -> (\__rhs -> (- __lhs __rhs)))",
+            "]>",
+        ]
+        .concat()
+    );
+    assert!(matches!(
+        &parse("main = (== 0").unwrap_err()[..],
+        [Error::UnexpectedToken(..)]
+    ));
+
+    let src = "`~` n = 0 - n\n\nmain = map (~) [1, 2]";
+    let ast = parse(src).unwrap();
+    assert_eq!(ast[1].to_string(), "<main:  = <map: ((~), [1, 2])>>");
+    assert!(matches!(
+        &parse("main = [1, )").unwrap_err()[..],
+        [Error::UnexpectedToken(expected, found, _)] if expected == "expression" && found == ")"
+    ));
+}
+
+#[test]
+fn comprehension_test() {
+    let src = "ranges = [[1..10], [0, 2 .. n]]\n\
+               squares xs = [x * x | x <- xs, x > 0]\n\
+               pairs = [(x, y) | x <- xs, Some y <- ys]";
+    let left = match parse(src) {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left[0],
+        "<ranges:  = [<__range: (1, 1, 10)>, <__range: (0, (- 2 0), n)>]>"
+    );
+    assert_eq!(
+        left[1],
+        r"<squares:  = (\xs -> <__map: ((\x -> (* x x)), <__filter: ((\x -> (> x 0)), xs)>)>)>"
+    );
+    assert_eq!(
+        left[2],
+        [
+            r"<pairs:  = <__concat: (<__map: ((\x -> <__map: (",
+            r"(\__item -> (match __item on | (Some y) -> (x, y))), ",
+            r"<__filter: ((\__item -> (match __item on | (Some y) -> true | _ -> false)), ys)>",
+            ")>), xs)>)>>",
+        ]
+        .concat()
+    );
+    assert!(matches!(
+        &parse("main = [1, 2, 3 .. 9]").unwrap_err()[..],
+        [Error::UnexpectedToken(..)]
+    ));
+}
+
+#[test]
+fn do_test() {
+    let src = "main = do\n  x <- pure 1\n  let y = x + 1\n  print y\n  Some z <- pure (Some y)\n  print z";
+    let left = match parse(src) {
+        Ok(ast) => ast.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        Err(err) => panic!("{:?}", err),
+    };
+    assert_eq!(
+        left[0],
+        [
+            r"<main:  = <__bind: (<pure: (1)>, (\x -> (let <y:  = (+ x 1)> in ",
+            r"<__bind: (<print: (y)>, (\__item -> <__bind: (<pure: (<Some: (y)>)>, ",
+            r"(\__item -> (match __item on | (Some z) -> <print: (z)>)))>))>)))>>",
+        ]
+        .concat()
+    );
+    assert!(matches!(
+        &parse("main = do\n  print 1\n  x <- pure 2").unwrap_err()[..],
+        [Error::ExpectedAction(..)]
+    ));
 }
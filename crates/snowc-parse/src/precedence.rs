@@ -1,49 +1,213 @@
-use super::Token;
-#[allow(unused)]
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
-pub enum Precedence {
+use super::error::Error;
+use super::op::Op;
+use snowc_lexer::{Ctrl, Ident, Int, KeyWord, Token};
+use std::collections::{HashMap, HashSet};
+
+/// Operators that are never treated as a user defined infix operator.
+const RESERVED: [&str; 4] = ["|", ".", "..", "!"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
     None,
-    Primary,
-    LRPipe,     // |>
-    Or,         // or
-    And,        // and
-    Term,       // + -
-    Factor,     // * / %
-    Equality,   // == !=
-    Comparison, // < > <= >=
-    Assignment, // =
-    RLPipe,     // <|
-    Call,       // . ()
-    Fn,         // fn function declaration
-    Unary,      // ! -
 }
 
-impl TryFrom<Token> for Precedence {
-    type Error = String;
-    fn try_from(value: Token) -> Result<Self, Self::Error> {
-        match value {
-            Token::Int(..)
-            | Token::Float(..)
-            | Token::String(..)
-            | Token::Char(..)
-            | Token::Id(..) => Ok(Self::Primary),
-            Token::KeyWord(ref b, ..) if b == "true" || b == "false" => Ok(Self::Primary),
-            Token::KeyWord(ref b, ..) if b == "and" => Ok(Self::And),
-            Token::KeyWord(ref b, ..) if b == "or" => Ok(Self::Or),
-            Token::KeyWord(ref b, ..) if b == "mod" => Ok(Self::Factor),
-            Token::KeyWord(..) => Ok(Self::None),
-            Token::Eof(..) => Ok(Self::None),
-            Token::Op(ref op, ..) => match op.as_str() {
-                "+" | "-" => Ok(Precedence::Term),
-                "*" | "/" => Ok(Precedence::Factor),
-                ">" | "<" | ">=" | "<=" => Ok(Precedence::Comparison),
-                "==" | "!=" => Ok(Precedence::Equality),
-                "=" => Ok(Precedence::Assignment),
-                "|>" => Ok(Precedence::LRPipe),
-                "<|" => Ok(Precedence::RLPipe),
-                _ => Ok(Precedence::None),
+/// How tightly an infix operator binds, levels go from `0` to `9`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixity {
+    pub assoc: Assoc,
+    pub level: u8,
+}
+
+impl Fixity {
+    /// Operators without a fixity declaration are `infixl 9`.
+    pub const DEFAULT: Self = Self {
+        assoc: Assoc::Left,
+        level: 9,
+    };
+
+    /// The levels of the built in operators, declared operators sit around
+    /// them.
+    /// ```hs
+    /// |>              0
+    /// <|              1 (right)
+    /// or              2
+    /// and             3
+    /// == !=           4
+    /// < > <= >=       5
+    /// + -             6
+    /// * / mod         7
+    /// ```
+    pub fn builtin(op: Op) -> Option<Self> {
+        let (assoc, level) = match op {
+            Op::LRPipe => (Assoc::Left, 0),
+            Op::RLPipe => (Assoc::Right, 1),
+            Op::Or => (Assoc::Left, 2),
+            Op::And => (Assoc::Left, 3),
+            Op::Eq | Op::Neq => (Assoc::Left, 4),
+            Op::Grt | Op::Les | Op::GrtEq | Op::LesEq => (Assoc::Left, 5),
+            Op::Plus | Op::Minus => (Assoc::Left, 6),
+            Op::Mult | Op::Div | Op::Mod => (Assoc::Left, 7),
+            Op::Not | Op::Equals => return None,
+        };
+        Some(Self { assoc, level })
+    }
+}
+
+/// An infix operator found while parsing, user defined operators are
+/// applied like functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Infix {
+    Builtin(Op),
+    User(String),
+}
+
+/// The operators a program defines along with their fixity declarations,
+/// gathered before parsing so an operator can be used above its definition.
+#[derive(Debug, Clone, Default)]
+pub struct Operators {
+    fixities: HashMap<String, Fixity>,
+    /// Operators defined with a single parameter, `` `!!` x = ... ``.
+    prefix: HashSet<String>,
+    /// Every operator with a definition, including the built in ones
+    /// that have been redefined.
+    defined: HashSet<String>,
+}
+
+impl Operators {
+    /// Records the operator definitions in `tokens` and removes the fixity
+    /// declarations from it.
+    /// ```hs
+    /// infixr 6 <>
+    /// `<>` xs ys = xs + ys
+    /// ```
+    pub fn collect(tokens: &mut Vec<Token>) -> Result<Self, Vec<Error>> {
+        let mut ops = Self::default();
+        let mut errors = vec![];
+        let mut idx = 0;
+        while idx < tokens.len() {
+            match &tokens[idx] {
+                Token::KeyWord(KeyWord { lexme, .. })
+                    if matches!(lexme.as_str(), "infixl" | "infixr" | "infix") =>
+                {
+                    if let Err(error) = ops.fixity(tokens, idx) {
+                        errors.push(error);
+                    }
+                    continue;
+                }
+                Token::Ident(Ident { lexme, .. }) if lexme.starts_with('(') => {
+                    let op = lexme[1..lexme.len() - 1].to_string();
+                    let args = tokens[idx + 1..]
+                        .iter()
+                        .take_while(|t| matches!(t, Token::Ident(..)))
+                        .count();
+                    let is_definition = matches!(
+                        tokens.get(idx + args + 1),
+                        Some(Token::Ctrl(Ctrl { lexme, .. })) if lexme == "=" || lexme == ":"
+                    );
+                    if is_definition {
+                        if args == 1 {
+                            ops.prefix.insert(op.clone());
+                        }
+                        ops.defined.insert(op);
+                    }
+                }
+                _ => {}
+            }
+            idx += 1;
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(ops)
+    }
+
+    /// Makes the operators of an imported module usable, a fixity declared
+    /// by the importing module wins.
+    pub fn extend(&mut self, other: &Self) {
+        for (op, fixity) in other.fixities.iter() {
+            self.fixities.entry(op.clone()).or_insert(*fixity);
+        }
+        self.prefix.extend(other.prefix.iter().cloned());
+        self.defined.extend(other.defined.iter().cloned());
+    }
+
+    /// `fixity ::= ("infixl" | "infixr" | "infix") int op ( "," op )*`
+    fn fixity(&mut self, tokens: &mut Vec<Token>, idx: usize) -> Result<(), Error> {
+        let Token::KeyWord(KeyWord { lexme, span, .. }) = tokens.remove(idx) else {
+            unreachable!();
+        };
+        let assoc = match lexme.as_str() {
+            "infixl" => Assoc::Left,
+            "infixr" => Assoc::Right,
+            _ => Assoc::None,
+        };
+        let level = match tokens.get(idx) {
+            Some(Token::Int(Int { lexme, span, .. })) => match lexme.parse::<u8>() {
+                Ok(level) if level <= 9 => level,
+                _ => return Err(Error::InvalidFixity(*span)),
             },
-            Token::Error(c, ..) => Err(format!("Unknown char {c}")),
+            _ => return Err(Error::InvalidFixity(span)),
+        };
+        tokens.remove(idx);
+        loop {
+            let op = match tokens.get(idx) {
+                Some(Token::Op(op)) => op.lexme.clone(),
+                Some(Token::Ident(Ident { lexme, .. })) if lexme.starts_with('(') => {
+                    lexme[1..lexme.len() - 1].to_string()
+                }
+                token => {
+                    let span = token.map(|t| t.span()).unwrap_or(span);
+                    return Err(Error::InvalidFixity(span));
+                }
+            };
+            tokens.remove(idx);
+            self.fixities.insert(op, Fixity { assoc, level });
+            match tokens.get(idx) {
+                Some(Token::Ctrl(Ctrl { lexme, .. })) if lexme == "," => {
+                    tokens.remove(idx);
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// The infix operator `token` stands for, if any.
+    pub fn infix(&self, token: Option<&Token>) -> Option<(Infix, Fixity)> {
+        let (lexme, is_op) = match token? {
+            Token::Op(op) => (op.lexme.as_str(), true),
+            Token::KeyWord(KeyWord { lexme, .. }) => (lexme.as_str(), false),
+            _ => return None,
+        };
+        let builtin = Op::try_from(lexme).ok().and_then(Fixity::builtin);
+        if self.prefix.contains(lexme) {
+            return None;
+        }
+        if self.defined.contains(lexme) || (is_op && builtin.is_none()) {
+            if !self.defined.contains(lexme) && RESERVED.contains(&lexme) {
+                return None;
+            }
+            let fixity = self
+                .fixities
+                .get(lexme)
+                .copied()
+                .or(builtin)
+                .unwrap_or(Fixity::DEFAULT);
+            return Some((Infix::User(format!("({lexme})")), fixity));
+        }
+        let op = Op::try_from(lexme).ok()?;
+        Some((Infix::Builtin(op), builtin?))
+    }
+
+    /// The name of the function behind `token` when it is a user defined
+    /// prefix operator.
+    pub fn prefix(&self, token: Option<&Token>) -> Option<String> {
+        match token? {
+            Token::Op(op) if self.prefix.contains(&op.lexme) => {
+                Some(format!("({})", op.lexme))
+            }
+            _ => None,
         }
     }
 }
@@ -1,4 +1,4 @@
-use snowc_parse::{parse, Expr};
+use snowc_parse::{parse, Clause, Expr, Pattern};
 
 pub fn snapshot_parsing(input: &str) -> String {
     let ast = match parse(input) {
@@ -21,7 +21,7 @@ pub fn snapshot_parsing(input: &str) -> String {
                 ast.push_front(node);
                 break;
             }
-            output += format_node(&input, &node).as_str();
+            output += format_node(&node).as_str();
             output += "\n";
             // output += &" ".repeat(node.span().col_start);
             // output += &"^".repeat(node.span().len());
@@ -37,6 +37,9 @@ enum ExprVisitor<'a> {
     Unary(&'a Expr),
     Binary(&'a Expr, &'a Expr),
     IfElse(&'a Expr, &'a Expr, &'a Expr),
+    Match(&'a Expr, &'a [(Pattern, Expr)]),
+    Clauses(&'a [Expr], &'a [Clause]),
+    Let(&'a [Expr], &'a Expr),
     Closure(&'a Expr, &'a Expr),
     Func(&'a Expr),
     App(&'a Expr, &'a [Expr]),
@@ -53,58 +56,82 @@ fn get_inner_expr<'a>(expr: &'a Expr) -> ExprVisitor<'a> {
         Expr::IfElse(condition, then, r#else, ..) => {
             ExprVisitor::IfElse(condition.as_ref(), then.as_ref(), r#else.as_ref())
         }
+        Expr::Match(expr, arms, ..) => ExprVisitor::Match(expr.as_ref(), arms),
+        Expr::Clauses(args, clauses, ..) => ExprVisitor::Clauses(args, clauses),
+        Expr::Let(bindings, body, ..) => ExprVisitor::Let(bindings, body.as_ref()),
         Expr::Closure(head, tail, ..) => {
             ExprVisitor::Closure(head.as_ref(), tail.as_ref())
         }
         Expr::Func(_, _, node, ..) => ExprVisitor::Func(node.as_ref()),
         Expr::App(app) => ExprVisitor::App(app.name.as_ref(), &app.args),
-        Expr::Array(nodes, ..) => ExprVisitor::Array(nodes),
+        Expr::Array(nodes, ..) | Expr::Tuple(nodes, ..) => ExprVisitor::Array(nodes),
+        Expr::Field(expr, ..) => ExprVisitor::Unary(expr.as_ref()),
+        Expr::Record(..) | Expr::Update(..) | Expr::Data(..) | Expr::Alias(..) => {
+            ExprVisitor::Root
+        }
         Expr::Enum(..) => ExprVisitor::Root,
         Expr::Error(..) => ExprVisitor::Root,
     }
 }
 
-fn format_node<'a>(src: &str, node: &'a Expr) -> String {
+fn format_node(node: &Expr) -> String {
     let mut result = String::new();
-    loop {
-        match get_inner_expr(node) {
-            ExprVisitor::Root => break,
-            ExprVisitor::Unary(expr) => {
-                result += format_node(src, expr).as_str();
-                break;
+    match get_inner_expr(node) {
+        ExprVisitor::Root => {}
+        ExprVisitor::Unary(expr) => {
+            result += format_node(expr).as_str();
+        }
+        ExprVisitor::Binary(lhs, rhs) => {
+            result += format_node(lhs).as_str();
+            result += format_node(rhs).as_str();
+        }
+        ExprVisitor::IfElse(cond, then, r#else) => {
+            result += format_node(cond).as_str();
+            result += format_node(then).as_str();
+            result += format_node(r#else).as_str();
+        }
+        ExprVisitor::Match(expr, arms) => {
+            result += format_node(expr).as_str();
+            for (_, arm) in arms {
+                result += format_node(arm).as_str();
             }
-            ExprVisitor::Binary(lhs, rhs) => {
-                result += format_node(src, lhs).as_str();
-                result += format_node(src, rhs).as_str();
-                break;
+        }
+        ExprVisitor::Clauses(args, clauses) => {
+            for arg in args {
+                result += format_node(arg).as_str();
             }
-            ExprVisitor::IfElse(cond, then, r#else) => {
-                result += format_node(src, cond).as_str();
-                result += format_node(src, then).as_str();
-                result += format_node(src, r#else).as_str();
-                break;
+            for binding in clauses.iter().flat_map(|clause| clause.bindings.iter()) {
+                result += format_node(binding).as_str();
             }
-            ExprVisitor::Closure(head, tail) => {
-                result += format_node(src, head).as_str();
-                result += format_node(src, tail).as_str();
-                break;
+            for (guard, body) in clauses.iter().flat_map(|clause| clause.bodies.iter()) {
+                if let Some(guard) = guard {
+                    result += format_node(guard).as_str();
+                }
+                result += format_node(body).as_str();
             }
-            ExprVisitor::Func(body) => {
-                result += format_node(src, body).as_str();
-                break;
+        }
+        ExprVisitor::Let(bindings, body) => {
+            for binding in bindings {
+                result += format_node(binding).as_str();
             }
-            ExprVisitor::App(name, args) => {
-                result += format_node(src, name).as_str();
-                for arg in args {
-                    result += format_node(src, arg).as_str();
-                }
-                break;
+            result += format_node(body).as_str();
+        }
+        ExprVisitor::Closure(head, tail) => {
+            result += format_node(head).as_str();
+            result += format_node(tail).as_str();
+        }
+        ExprVisitor::Func(body) => {
+            result += format_node(body).as_str();
+        }
+        ExprVisitor::App(name, args) => {
+            result += format_node(name).as_str();
+            for arg in args {
+                result += format_node(arg).as_str();
             }
-            ExprVisitor::Array(array) => {
-                for item in array {
-                    result += format_node(src, item).as_str();
-                }
-                break;
+        }
+        ExprVisitor::Array(array) => {
+            for item in array {
+                result += format_node(item).as_str();
             }
         }
     }
@@ -111,7 +111,7 @@ fn execute_return_command(
         repl.clear_input();
         return Ok(());
     }
-    match compile(&repl, scope) {
+    match compile(repl, scope) {
         Ok(Some(v)) => {
             terminal.print(&v.to_string().yellow().to_string())?;
             terminal.new_line()?;
@@ -148,11 +148,10 @@ fn execute_builtin_repl_command(
                 return Ok(true);
             };
             repl.input = src.clone();
-            let result = compile(&repl, scope);
-            if result.is_err() {
+            if let Err(errors) = compile(repl, scope) {
                 terminal.print(&format!("failed to compile {filename}"))?;
                 terminal.new_line()?;
-                terminal.print(&result.unwrap_err().join("\n"))?;
+                terminal.print(&errors.join("\n"))?;
                 terminal.new_line()?;
                 return Ok(true);
             }
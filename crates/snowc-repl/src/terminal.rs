@@ -22,14 +22,13 @@ pub enum Command {
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Size {
-    pub width: u16,
     pub height: u16,
 }
 
 impl Size {
     pub fn new() -> Result<Self> {
-        let (width, height) = crossterm::terminal::size()?;
-        Ok(Self { width, height })
+        let (_, height) = crossterm::terminal::size()?;
+        Ok(Self { height })
     }
 }
 
@@ -17,11 +17,40 @@ pub enum RuntimeError {
     IdxOutOfBounds(Span),
     #[error("invalid binary operation")]
     InvalidBinaryOp(Span),
+    #[error("division by zero")]
+    DivisionByZero(Span),
+    #[error("Int overflow")]
+    IntOverflow(Span),
     #[error("empty array")]
     EmptyArray(Span),
+    #[error("not a function")]
+    NotAFunction(Span),
+    #[error("no pattern matched the value")]
+    NonExhaustiveMatch(Span),
+    #[error("no field named '{0}'")]
+    UnknownField(String, Span),
+    #[error("missing field '{0}'")]
+    MissingField(String, Span),
 }
 
 impl RuntimeError {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Undefined(_, span)
+            | Self::InvalidArguments(span)
+            | Self::IdxOutOfBounds(span)
+            | Self::InvalidBinaryOp(span)
+            | Self::DivisionByZero(span)
+            | Self::IntOverflow(span)
+            | Self::EmptyArray(span)
+            | Self::NotAFunction(span)
+            | Self::NonExhaustiveMatch(span)
+            | Self::UnknownField(_, span)
+            | Self::MissingField(_, span) => *span,
+            Self::MissingMainFunction => Span::default(),
+        }
+    }
+
     pub fn report(&self, filename: &str, src: &str) -> String {
         match self {
             Self::Undefined(name, span) => {
@@ -50,7 +79,13 @@ impl RuntimeError {
                 let snippet = snippet_builder(filename, src, &label, *span);
                 DisplayList::from(snippet).to_string()
             }
-            Self::EmptyArray(span) => {
+            Self::DivisionByZero(span)
+            | Self::IntOverflow(span)
+            | Self::EmptyArray(span)
+            | Self::NotAFunction(span)
+            | Self::NonExhaustiveMatch(span)
+            | Self::UnknownField(_, span)
+            | Self::MissingField(_, span) => {
                 let label = self.to_string();
                 let snippet = snippet_builder(filename, src, &label, *span);
                 DisplayList::from(snippet).to_string()
@@ -77,7 +112,7 @@ fn snippet_builder<'a>(
         footer: vec![],
         slices: vec![Slice {
             source: src,
-            line_start: span.col_start,
+            line_start: 1,
             origin: Some(filename),
             fold: true,
             annotations: vec![SourceAnnotation {
@@ -5,60 +5,84 @@ mod value;
 pub use error::RuntimeError;
 use snowc_parse::{
     expr::{App, Binary},
-    Atom, Expr, Op, Span, TokenPosition, Unary,
+    is_primitive, method_name, Atom, Clause, Expr, Op, Pattern, Span, TokenPosition,
+    Unary, BUILTINS,
 };
+use std::cell::OnceCell;
 use std::collections::HashMap;
-pub use value::Value;
+use std::rc::Rc;
+pub use value::{Action, Group, Value};
 
-type Env = HashMap<String, Expr>;
+type Env = HashMap<String, Value>;
+type Globals = HashMap<String, Expr>;
+type Constructors = HashMap<String, (String, usize)>;
+type Records = HashMap<String, Vec<String>>;
+type Methods = HashMap<String, Globals>;
 type Result<T> = std::result::Result<T, RuntimeError>;
 
-fn builtin(op: Op) -> Expr {
-    let pos = TokenPosition::Middle;
-    let span = Span::default();
-    let left = Box::new(Expr::Atom(Atom::Id("x".into(), pos, span)));
-    let right = Box::new(Expr::Atom(Atom::Id("y".into(), pos, span)));
-    let binary = Binary {
-        op,
-        left: left.clone(),
-        right: right.clone(),
-        pos: TokenPosition::End,
-        span,
-    };
-    let body = Expr::Binary(binary);
-    let x = Expr::Closure(left, Box::new(body), span);
-    Expr::Closure(right, Box::new(x), span)
-}
-
-#[derive(Debug, Clone)]
+/// `local` holds the values bound by the closures we are currently inside of,
+/// `global` holds the top level definitions which are evaluated on use and
+/// `constructors` holds the enum each constructor builds along with the number
+/// of fields it takes, `records` the fields of each record in the order they
+/// were declared and `methods` the methods of each type.
+#[derive(Debug, Clone, Default)]
 pub struct Scope {
     pub local: Env,
-    pub global: Env,
+    pub global: Rc<Globals>,
+    pub constructors: Rc<Constructors>,
+    pub records: Rc<Records>,
+    pub methods: Rc<Methods>,
 }
 
 impl Scope {
     pub fn get(&self, name: &str) -> Option<&Expr> {
-        self.local.get(name).or(self.global.get(name))
+        match method_name(name) {
+            Some((ty, method)) => self.methods.get(ty)?.get(method),
+            None => self.global.get(name),
+        }
     }
 
+    /// Methods go in the namespace of their type, `Option.map` is `map` of
+    /// `Option`.
     fn insert_global(&mut self, k: String, v: Expr) {
-        self.global.insert(k, v);
+        let Some((ty, method)) = method_name(&k) else {
+            Rc::make_mut(&mut self.global).insert(k, v);
+            return;
+        };
+        let methods = Rc::make_mut(&mut self.methods);
+        methods
+            .entry(ty.into())
+            .or_default()
+            .insert(method.into(), v);
     }
-    fn insert_local(&mut self, k: String, v: Expr) {
-        self.local.insert(k, v);
+
+    fn insert_enum(&mut self, name: &str, variants: &[(String, Vec<String>)]) {
+        let constructors = Rc::make_mut(&mut self.constructors);
+        for (variant, fields) in variants.iter() {
+            constructors.insert(variant.clone(), (name.to_string(), fields.len()));
+        }
     }
-}
-impl Default for Scope {
-    fn default() -> Self {
-        let mut scope = Self {
-            local: Env::default(),
-            global: Env::default(),
-        };
-        scope.insert_global("(+)".into(), builtin(Op::Plus));
-        scope.insert_global("(-)".into(), builtin(Op::Minus));
-        scope.insert_global("(*)".into(), builtin(Op::Mult));
-        scope.insert_global("(/)".into(), builtin(Op::Div));
-        scope
+
+    fn insert_data(&mut self, name: &str, fields: &[(String, String)]) {
+        let fields = fields.iter().map(|(field, _)| field.clone()).collect();
+        Rc::make_mut(&mut self.records).insert(name.to_string(), fields);
+    }
+
+    fn with_local(&self, local: Env) -> Self {
+        Self {
+            local,
+            global: Rc::clone(&self.global),
+            constructors: Rc::clone(&self.constructors),
+            records: Rc::clone(&self.records),
+            methods: Rc::clone(&self.methods),
+        }
+    }
+
+    fn is_builtin(&self, name: &str) -> bool {
+        is_primitive(name)
+            || BUILTINS.contains(&name)
+                && !self.local.contains_key(name)
+                && !self.global.contains_key(name)
     }
 }
 
@@ -66,7 +90,11 @@ fn expr_unary(unary: &Unary, scope: &Scope) -> Result<Value> {
     let Unary { op, expr, .. } = unary;
     let atom = walk_expr(expr, scope)?;
     match (op, atom) {
-        (Op::Minus, Value::Int(int, span)) => Ok(Value::Int(-int, span)),
+        (Op::Minus, Value::Int(int, span)) => int
+            .checked_neg()
+            .map(|int| Value::Int(int, span))
+            .ok_or(RuntimeError::IntOverflow(span)),
+        (Op::Minus, Value::Float(float, span)) => Ok(Value::Float(-float, span)),
         (Op::Not, Value::Bool(b, span)) => Ok(Value::Bool(!b, span)),
         _ => unimplemented!("for operator '{op:?}'"),
     }
@@ -83,8 +111,11 @@ fn expr_binary(binary: &Binary, scope: &Scope) -> Result<Value> {
     let lhs_atom = walk_expr(left, scope)?;
     let rhs_atom = walk_expr(right, scope)?;
     match (op, lhs_atom, rhs_atom) {
-        (Op::Plus, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Int(lhs + rhs, *span))
+        (op, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
+            int_binary(*op, lhs, rhs, *span)
+        }
+        (op, Value::Float(lhs, ..), Value::Float(rhs, ..)) => {
+            float_binary(*op, lhs, rhs, *span)
         }
         (Op::Plus, Value::String(lhs, ..), Value::String(rhs, ..)) => {
             Ok(Value::String(format!("{lhs}{rhs}"), *span))
@@ -93,36 +124,6 @@ fn expr_binary(binary: &Binary, scope: &Scope) -> Result<Value> {
             lhs.iter().cloned().chain(rhs.iter().cloned()).collect(),
             *span,
         )),
-        (Op::Minus, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Int(lhs - rhs, *span))
-        }
-        (Op::Mult, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Int(lhs * rhs, *span))
-        }
-        (Op::Div, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Int(lhs / rhs, *span))
-        }
-        (Op::Mod, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Int(lhs % rhs, *span))
-        }
-        (Op::Grt, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Bool(lhs > rhs, *span))
-        }
-        (Op::GrtEq, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Bool(lhs >= rhs, *span))
-        }
-        (Op::Les, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Bool(lhs < rhs, *span))
-        }
-        (Op::LesEq, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Bool(lhs <= rhs, *span))
-        }
-        (Op::Eq, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Bool(lhs == rhs, *span))
-        }
-        (Op::Neq, Value::Int(lhs, ..), Value::Int(rhs, ..)) => {
-            Ok(Value::Bool(lhs != rhs, *span))
-        }
         (Op::Eq, Value::String(lhs, ..), Value::String(rhs, ..)) => {
             Ok(Value::Bool(lhs == rhs, *span))
         }
@@ -139,15 +140,52 @@ fn expr_binary(binary: &Binary, scope: &Scope) -> Result<Value> {
     }
 }
 
+/// Overflowing an Int and dividing it by zero are both errors.
+fn int_binary(op: Op, lhs: i64, rhs: i64, span: Span) -> Result<Value> {
+    let checked = |int: Option<i64>| {
+        int.map(|int| Value::Int(int, span))
+            .ok_or(RuntimeError::IntOverflow(span))
+    };
+    match op {
+        Op::Plus => checked(lhs.checked_add(rhs)),
+        Op::Minus => checked(lhs.checked_sub(rhs)),
+        Op::Mult => checked(lhs.checked_mul(rhs)),
+        Op::Div | Op::Mod if rhs == 0 => Err(RuntimeError::DivisionByZero(span)),
+        Op::Div => checked(lhs.checked_div(rhs)),
+        Op::Mod => Ok(Value::Int(lhs.wrapping_rem(rhs), span)),
+        Op::Grt => Ok(Value::Bool(lhs > rhs, span)),
+        Op::GrtEq => Ok(Value::Bool(lhs >= rhs, span)),
+        Op::Les => Ok(Value::Bool(lhs < rhs, span)),
+        Op::LesEq => Ok(Value::Bool(lhs <= rhs, span)),
+        Op::Eq => Ok(Value::Bool(lhs == rhs, span)),
+        Op::Neq => Ok(Value::Bool(lhs != rhs, span)),
+        _ => Err(RuntimeError::InvalidBinaryOp(span)),
+    }
+}
+
+fn float_binary(op: Op, lhs: f64, rhs: f64, span: Span) -> Result<Value> {
+    match op {
+        Op::Plus => Ok(Value::Float(lhs + rhs, span)),
+        Op::Minus => Ok(Value::Float(lhs - rhs, span)),
+        Op::Mult => Ok(Value::Float(lhs * rhs, span)),
+        Op::Div => Ok(Value::Float(lhs / rhs, span)),
+        Op::Mod => Ok(Value::Float(lhs % rhs, span)),
+        Op::Grt => Ok(Value::Bool(lhs > rhs, span)),
+        Op::GrtEq => Ok(Value::Bool(lhs >= rhs, span)),
+        Op::Les => Ok(Value::Bool(lhs < rhs, span)),
+        Op::LesEq => Ok(Value::Bool(lhs <= rhs, span)),
+        Op::Eq => Ok(Value::Bool(lhs == rhs, span)),
+        Op::Neq => Ok(Value::Bool(lhs != rhs, span)),
+        _ => Err(RuntimeError::InvalidBinaryOp(span)),
+    }
+}
+
 fn expr_conditional(
     condition: &Expr,
     then: &Expr,
     r#else: &Expr,
     scope: &Scope,
 ) -> Result<Value> {
-    eprintln!("condition: {condition} | {:?}", _typeofexpr(condition));
-    eprintln!("then: {then} | {:?}", _typeofexpr(then));
-    eprintln!("else: {} | {:?}", r#else, _typeofexpr(r#else));
     match walk_expr(condition, scope)? {
         Value::Bool(true, _) => walk_expr(then, scope),
         Value::Bool(false, _) => walk_expr(r#else, scope),
@@ -155,77 +193,342 @@ fn expr_conditional(
     }
 }
 
-fn expr_closure_with_args(
-    head: &Expr,
-    tail: &Expr,
+fn expr_match(
+    expr: &Expr,
+    arms: &[(Pattern, Expr)],
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    let value = walk_expr(expr, scope)?;
+    for (pattern, arm) in arms.iter() {
+        let mut env = scope.local.clone();
+        if match_pattern(pattern, &value, &mut env) {
+            return walk_expr(arm, &scope.with_local(env));
+        }
+    }
+    Err(RuntimeError::NonExhaustiveMatch(span))
+}
+
+/// Tries the clauses of a function in order, the first one whose patterns
+/// match the arguments and whose guard holds gives the result. `span` is the
+/// call the arguments came from.
+fn expr_clauses(
     args: &[Expr],
+    clauses: &[Clause],
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    let values = args
+        .iter()
+        .map(|arg| walk_expr(arg, scope))
+        .collect::<Result<Vec<_>>>()?;
+    for clause in clauses.iter() {
+        let mut env = scope.local.clone();
+        let matches = clause
+            .patterns
+            .iter()
+            .zip(values.iter())
+            .all(|(pattern, value)| match_pattern(pattern, value, &mut env));
+        if !matches {
+            continue;
+        }
+        let scope = let_scope(&clause.bindings, clause.span, &scope.with_local(env));
+        for (guard, body) in clause.bodies.iter() {
+            let holds = match guard {
+                Some(guard) => matches!(walk_expr(guard, &scope)?, Value::Bool(true, _)),
+                None => true,
+            };
+            if holds {
+                return walk_expr(body, &scope);
+            }
+        }
+    }
+    Err(RuntimeError::NonExhaustiveMatch(span))
+}
+
+/// Checks if `value` has the shape of `pattern`, binding the names in the
+/// pattern into `env` along the way.
+fn match_pattern(pattern: &Pattern, value: &Value, env: &mut Env) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard(..), _) => true,
+        (Pattern::Id(name, ..), _) => {
+            env.insert(name.clone(), value.clone());
+            true
+        }
+        (Pattern::Literal(atom), value) => match (atom, value) {
+            (Atom::Int(lhs, ..), Value::Int(rhs, ..)) => lhs == rhs,
+            (Atom::Float(lhs, ..), Value::Float(rhs, ..)) => lhs == rhs,
+            (Atom::Bool(lhs, ..), Value::Bool(rhs, ..)) => lhs == rhs,
+            (Atom::String(lhs, ..), Value::String(rhs, ..)) => lhs == rhs,
+            (Atom::Char(lhs, ..), Value::Char(rhs, ..)) => lhs == rhs,
+            _ => false,
+        },
+        (Pattern::Constructor(name, args, ..), Value::Variant(variant, values, ..)) => {
+            name == variant
+                && args.len() == values.len()
+                && args
+                    .iter()
+                    .zip(values.iter())
+                    .all(|(pattern, value)| match_pattern(pattern, value, env))
+        }
+        (Pattern::Tuple(items, ..), Value::Tuple(values, ..)) => {
+            items.len() == values.len()
+                && items
+                    .iter()
+                    .zip(values.iter())
+                    .all(|(pattern, value)| match_pattern(pattern, value, env))
+        }
+        (Pattern::Array(items, rest, ..), Value::Array(values, span)) => {
+            let len_matches = match rest {
+                Some(_) => values.len() >= items.len(),
+                None => values.len() == items.len(),
+            };
+            len_matches
+                && items
+                    .iter()
+                    .zip(values.iter())
+                    .all(|(pattern, value)| match_pattern(pattern, value, env))
+                && rest.as_ref().is_none_or(|rest| {
+                    let tail = Value::Array(values[items.len()..].to_vec(), *span);
+                    match_pattern(rest, &tail, env)
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Builds a record with its fields in the order they were declared.
+fn expr_record(
+    name: &str,
+    fields: &[(String, Expr)],
+    span: Span,
     scope: &Scope,
 ) -> Result<Value> {
-    let Some(arg) = args.first() else {
-        unimplemented!("not sure what to do here");
+    let Some(declared) = scope.records.get(name) else {
+        return Err(RuntimeError::Undefined(name.into(), span));
     };
-    let Expr::Atom(Atom::Id(name, _, span)) = head else {
-        unimplemented!("shouldnt get here i think?");
+    if let Some((field, value)) = fields.iter().find(|(f, _)| !declared.contains(f)) {
+        return Err(RuntimeError::UnknownField(field.clone(), value.span()));
+    }
+    let mut values = Vec::new();
+    for field in declared.iter() {
+        let Some((_, value)) = fields.iter().find(|(f, _)| f == field) else {
+            return Err(RuntimeError::MissingField(field.clone(), span));
+        };
+        values.push((field.clone(), walk_expr(value, scope)?));
+    }
+    Ok(Value::Record(name.into(), values, span))
+}
+
+/// Reads a field of a record, anything else is looked up in the methods of
+/// the type of the value.
+fn expr_field(expr: &Expr, field: &str, span: Span, scope: &Scope) -> Result<Value> {
+    let value = walk_expr(expr, scope)?;
+    let ty = match &value {
+        Value::Record(name, fields, _) => {
+            let value = fields.iter().find(|(name, _)| name == field);
+            if let Some((_, value)) = value {
+                return Ok(value.clone());
+            }
+            name.clone()
+        }
+        Value::Variant(name, ..) => match scope.constructors.get(name) {
+            Some((ty, _)) => ty.clone(),
+            None => return Err(RuntimeError::UnknownField(field.into(), span)),
+        },
+        _ => return Err(RuntimeError::UnknownField(field.into(), span)),
+    };
+    let Some(method) = scope
+        .methods
+        .get(&ty)
+        .and_then(|methods| methods.get(field))
+    else {
+        return Err(RuntimeError::UnknownField(field.into(), span));
     };
-    let mut scope = scope.clone();
-    scope.insert_local(name.clone(), arg.clone());
-    if tail.is_app() {
-        return expr_app(tail, &args[1..], *span, &scope);
+    bind_self(&format!("{ty}.{field}"), method, value, span, scope)
+}
+
+/// `opt.map` is `Option.map` waiting for the arguments that come before
+/// `self`, it is called with `opt` once they are given.
+fn bind_self(
+    name: &str,
+    method: &Expr,
+    receiver: Value,
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    let mut arity = 0;
+    let mut body = method;
+    while let Expr::Closure(_, inner, ..) = body {
+        arity += 1;
+        body = inner;
     }
-    walk_expr(tail, &scope)
+    let id = |name: &str| Expr::Atom(Atom::Id(name.into(), TokenPosition::Middle, span));
+    if arity <= 1 {
+        let func = walk_expr(method, &scope.with_local(Env::default()))?;
+        return apply(func, receiver, span, scope);
+    }
+    let params = (0..arity - 1)
+        .map(|idx| id(&format!("__arg{idx}")))
+        .collect::<Vec<_>>();
+    let call = Expr::App(App {
+        name: Box::new(id(name)),
+        args: params.iter().cloned().chain([id("self")]).collect(),
+        pos: TokenPosition::Middle,
+        span,
+    });
+    let closure = params.into_iter().rev().fold(call, |body, param| {
+        Expr::Closure(Box::new(param), Box::new(body), span)
+    });
+    let env = Env::from([("self".to_string(), receiver)]);
+    Ok(Value::Func(closure, env, span))
 }
 
-fn expr_app(expr: &Expr, args: &[Expr], span: Span, scope: &Scope) -> Result<Value> {
-    let Expr::Atom(Atom::Id(name, _, span)) = expr else {
-        let (Some(head), Some(tail)) = (expr.get_head(), expr.get_tail()) else {
-            let Expr::App(App {
-                name, args, span, ..
-            }) = expr
-            else {
-                return Err(RuntimeError::InvalidArguments(span));
-            };
-            return expr_app(name, args, *span, scope);
+/// Copies a record, replacing the given fields.
+fn expr_update(
+    expr: &Expr,
+    updates: &[(String, Expr)],
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    let value = walk_expr(expr, scope)?;
+    let Value::Record(name, mut fields, _) = value else {
+        let field = updates.first().map(|(f, _)| f.clone()).unwrap_or_default();
+        return Err(RuntimeError::UnknownField(field, span));
+    };
+    for (field, expr) in updates.iter() {
+        let Some((_, value)) = fields.iter_mut().find(|(f, _)| f == field) else {
+            return Err(RuntimeError::UnknownField(field.clone(), expr.span()));
         };
-        return expr_closure_with_args(head, tail, args, scope);
+        *value = walk_expr(expr, scope)?;
+    }
+    Ok(Value::Record(name, fields, span))
+}
+
+/// Binds `arg` to the parameter of `func` and evaluates the body inside the
+/// scope the closure was created in. Constructors collect their arguments
+/// until every field has been given.
+fn apply(func: Value, arg: Value, span: Span, scope: &Scope) -> Result<Value> {
+    if let Value::Variant(name, mut values, start) = func {
+        let fields = scope
+            .constructors
+            .get(&name)
+            .map_or(0, |(_, fields)| *fields);
+        if values.len() >= fields {
+            return Err(RuntimeError::NotAFunction(span));
+        }
+        values.push(arg);
+        return Ok(Value::Variant(name, values, Span::from((start, span))));
+    }
+    let Value::Func(Expr::Closure(head, tail, ..), mut env, ..) = func else {
+        return Err(RuntimeError::NotAFunction(span));
     };
-    match name.as_str() {
-        // Prints any item to console
+    let Expr::Atom(Atom::Id(name, ..)) = head.as_ref() else {
+        unimplemented!("closure parameter '{head}'");
+    };
+    env.insert(name.clone(), arg);
+    if let Expr::Clauses(args, clauses, ..) = tail.as_ref() {
+        return expr_clauses(args, clauses, span, &scope.with_local(env));
+    }
+    walk_expr(&tail, &scope.with_local(env))
+}
+
+fn expr_let(bindings: &[Expr], body: &Expr, span: Span, scope: &Scope) -> Result<Value> {
+    walk_expr(body, &let_scope(bindings, span, scope))
+}
+
+/// Binds every name of a `let` group lazily on top of the current scope, so
+/// local functions can call themselves and each other.
+fn let_scope(bindings: &[Expr], span: Span, scope: &Scope) -> Scope {
+    let group = bindings
+        .iter()
+        .map(|binding| match binding {
+            Expr::Func(name, _, closure, ..) => {
+                (name.clone(), *closure.clone(), OnceCell::new())
+            }
+            _ => unreachable!("let binding '{binding}'"),
+        })
+        .collect::<Group>();
+    let group = Rc::new(group);
+    let mut env = scope.local.clone();
+    for (name, ..) in group.iter() {
+        let value =
+            Value::Lazy(name.clone(), Rc::clone(&group), scope.local.clone(), span);
+        env.insert(name.clone(), value);
+    }
+    scope.with_local(env)
+}
+
+/// Evaluates the binding `name` of `group` inside the scope it was defined in,
+/// remembering the value so later lookups don't evaluate it again.
+fn force(
+    name: &str,
+    group: &Rc<Group>,
+    base: &Env,
+    span: Span,
+    scope: &Scope,
+) -> Result<Value> {
+    let Some((_, expr, cell)) = group.iter().find(|(binding, ..)| binding == name) else {
+        return Err(RuntimeError::Undefined(name.into(), span));
+    };
+    if let Some(value) = cell.get() {
+        return Ok(value.clone());
+    }
+    let mut env = base.clone();
+    for (binding, ..) in group.iter() {
+        let value = Value::Lazy(binding.clone(), Rc::clone(group), base.clone(), span);
+        env.insert(binding.clone(), value);
+    }
+    let value = walk_expr(expr, &scope.with_local(env))?;
+    // a closure is cheap to build again, and keeping it would leak the group
+    // through the scope it captured
+    if !matches!(value, Value::Func(..)) {
+        let _ = cell.set(value.clone());
+    }
+    Ok(value)
+}
+
+fn expr_app(expr: &Expr, args: &[Expr], span: Span, scope: &Scope) -> Result<Value> {
+    let builtin = match expr {
+        Expr::Atom(Atom::Id(name, ..)) if scope.is_builtin(name) => Some(name.as_str()),
+        _ => None,
+    };
+    let Some(name) = builtin else {
+        let mut func = walk_expr(expr, scope)?;
+        for arg in args.iter() {
+            let arg = walk_expr(arg, scope)?;
+            func = apply(func, arg, span, scope)?;
+        }
+        return Ok(func);
+    };
+    match name {
+        // Prints any item to console once the action is run
         "print" => {
             let mut eval_args = vec![];
             for expr in args.iter() {
                 let value = walk_expr(expr, scope)?;
                 eval_args.push(value);
             }
-            let formated =
-                eval_args
-                    .iter()
-                    .enumerate()
-                    .fold("".into(), |acc, (idx, item)| {
-                        let item = match item {
-                            Value::Array(array, ..) => format!(
-                                "{}]",
-                                array.iter().fold("[".into(), |acc, item| {
-                                    if acc == "[" {
-                                        return format!("{acc}{item}");
-                                    }
-                                    format!("{acc}, {item}")
-                                })
-                            ),
-                            _ => item.to_string(),
-                        };
-                        if idx == 0 {
-                            return item;
-                        }
-                        format!("{acc} {item}")
-                    });
-            print!("{formated}");
-            Ok(eval_args[0].clone())
+            Ok(Value::IO(Action::Print(eval_args), span))
+        }
+        "pure" => {
+            let value = walk_expr(&args[0], scope)?;
+            Ok(Value::IO(Action::Pure(Box::new(value)), span))
+        }
+        // `do` blocks are desugared to this
+        "__bind" => {
+            let action = walk_expr(&args[0], scope)?;
+            let func = walk_expr(&args[1], scope)?;
+            Ok(Value::IO(
+                Action::Bind(Box::new(action), Box::new(func)),
+                span,
+            ))
         }
         // use this function to index into an array
         "nth" => {
             let atom = walk_expr(&args[0], scope)?;
             let Value::Array(array, span) = atom else {
-                return Err(RuntimeError::InvalidArguments(*span));
+                return Err(RuntimeError::InvalidArguments(span));
             };
             let atom = walk_expr(&args[1], scope)?;
             let Value::Int(idx, span) = &atom else {
@@ -241,10 +544,10 @@ fn expr_app(expr: &Expr, args: &[Expr], span: Span, scope: &Scope) -> Result<Val
         // use this function to get the length of an array
         "length" => {
             let Value::Array(array, span) = walk_expr(&args[0], scope)? else {
-                return Ok(Value::Int(0, *span));
+                return Ok(Value::Int(0, span));
             };
             let len = array.len();
-            Ok(Value::Int(len as i32, span))
+            Ok(Value::Int(len as i64, span))
         }
         // use this function to push to the end of an array
         "push" => {
@@ -259,12 +562,11 @@ fn expr_app(expr: &Expr, args: &[Expr], span: Span, scope: &Scope) -> Result<Val
                     array.push(value);
                     Ok(Value::Array(array, span))
                 }
-                _ => Err(RuntimeError::InvalidArguments(*span)),
+                _ => Err(RuntimeError::InvalidArguments(span)),
             }
         }
         "tail" => {
             let iter = walk_expr(&args[0], scope)?;
-            eprintln!("tail: {iter} | {:?}", _typeofvalue(&iter));
             match iter {
                 Value::String(string, span) => {
                     if string.is_empty() {
@@ -278,7 +580,7 @@ fn expr_app(expr: &Expr, args: &[Expr], span: Span, scope: &Scope) -> Result<Val
                     }
                     Ok(Value::Array(array[1..].to_vec(), span))
                 }
-                _ => Err(RuntimeError::InvalidArguments(*span)),
+                _ => Err(RuntimeError::InvalidArguments(span)),
             }
         }
         "head" => {
@@ -296,41 +598,82 @@ fn expr_app(expr: &Expr, args: &[Expr], span: Span, scope: &Scope) -> Result<Val
                     }
                     Ok(array[0].clone())
                 }
-                _ => Err(RuntimeError::InvalidArguments(*span)),
+                _ => Err(RuntimeError::InvalidArguments(span)),
             }
         }
-        _ => {
-            let Some(mut func) = scope.get(name).cloned() else {
-                return walk_expr(expr, scope);
-            };
-            let mut scope = scope.clone();
+        "toFloat" => match walk_expr(&args[0], scope)? {
+            Value::Int(int, span) => Ok(Value::Float(int as f64, span)),
+            value => Err(RuntimeError::InvalidArguments(value.span())),
+        },
+        // drops the fraction, out of range floats saturate and NaN is 0
+        "toInt" => match walk_expr(&args[0], scope)? {
+            Value::Float(float, span) => Ok(Value::Int(float as i64, span)),
+            value => Err(RuntimeError::InvalidArguments(value.span())),
+        },
+        // ranges and list comprehensions are desugared to these four
+        "__range" => {
+            let mut ints = vec![];
             for arg in args.iter() {
-                match func.get_head() {
-                    Some(Expr::Atom(Atom::Id(name, pos, ..))) => {
-                        let value = match walk_expr(&arg.clone(), &scope) {
-                            Ok(v) => into_expr(&v, *pos),
-                            Err(_) => arg.clone(),
-                        };
-                        scope.insert_local(name.clone(), value);
-                        let Some(t) = func.get_tail() else {
-                            unreachable!()
-                        };
-                        func = t.clone();
-                    }
-                    _ => {
-                        let value = match walk_expr(&func, &scope) {
-                            Ok(v) => into_expr(&v, arg.position()),
-                            Err(_) => arg.clone(),
-                        };
-                        func = value;
-                    }
+                match walk_expr(arg, scope)? {
+                    Value::Int(int, ..) => ints.push(int),
+                    value => return Err(RuntimeError::InvalidArguments(value.span())),
                 }
             }
-
-            walk_expr(&func, &scope)
+            let [start, step, end] = ints[..] else {
+                return Err(RuntimeError::InvalidArguments(span));
+            };
+            if step == 0 {
+                return Err(RuntimeError::InvalidArguments(span));
+            }
+            let items = std::iter::successors(Some(start), |int| int.checked_add(step))
+                .take_while(|int| if step > 0 { *int <= end } else { *int >= end })
+                .map(|int| Value::Int(int, span))
+                .collect();
+            Ok(Value::Array(items, span))
         }
+        "__map" => {
+            let func = walk_expr(&args[0], scope)?;
+            let Value::Array(array, span) = walk_expr(&args[1], scope)? else {
+                return Err(RuntimeError::InvalidArguments(span));
+            };
+            let array = array
+                .into_iter()
+                .map(|item| apply(func.clone(), item, span, scope))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Value::Array(array, span))
+        }
+        "__filter" => {
+            let func = walk_expr(&args[0], scope)?;
+            let Value::Array(array, span) = walk_expr(&args[1], scope)? else {
+                return Err(RuntimeError::InvalidArguments(span));
+            };
+            let mut kept = vec![];
+            for item in array {
+                match apply(func.clone(), item.clone(), span, scope)? {
+                    Value::Bool(true, ..) => kept.push(item),
+                    Value::Bool(false, ..) => {}
+                    value => return Err(RuntimeError::InvalidArguments(value.span())),
+                }
+            }
+            Ok(Value::Array(kept, span))
+        }
+        "__concat" => {
+            let Value::Array(arrays, span) = walk_expr(&args[0], scope)? else {
+                return Err(RuntimeError::InvalidArguments(span));
+            };
+            let mut items = vec![];
+            for array in arrays {
+                let Value::Array(array, ..) = array else {
+                    return Err(RuntimeError::InvalidArguments(array.span()));
+                };
+                items.extend(array);
+            }
+            Ok(Value::Array(items, span))
+        }
+        _ => unreachable!("builtin '{name}'"),
     }
 }
+
 fn _typeofexpr(expr: &Expr) -> String {
     match expr {
         Expr::Atom(atom) => match atom {
@@ -349,9 +692,19 @@ fn _typeofexpr(expr: &Expr) -> String {
         Expr::Unary(..) => "unary".to_string(),
         Expr::Binary(..) => "binary".to_string(),
         Expr::IfElse(..) => "if".to_string(),
+        Expr::Match(..) => "match".to_string(),
+        Expr::Clauses(..) => "clauses".to_string(),
+        Expr::Let(..) => "let".to_string(),
         Expr::Enum(..) => "enum".to_string(),
+        Expr::Data(..) => "data".to_string(),
+        Expr::Alias(..) => "alias".to_string(),
+        Expr::Field(..) => "field".to_string(),
+        Expr::Record(..) => "record".to_string(),
+        Expr::Tuple(..) => "tuple".to_string(),
+        Expr::Update(..) => "update".to_string(),
     }
 }
+
 fn _typeofvalue(expr: &Value) -> String {
     match expr {
         Value::Int(..) => "int".to_string(),
@@ -361,38 +714,59 @@ fn _typeofvalue(expr: &Value) -> String {
         Value::Char(..) => "char".to_string(),
         Value::Array(..) => "array".to_string(),
         Value::Func(..) => "function".to_string(),
+        Value::Variant(..) => "variant".to_string(),
+        Value::Tuple(..) => "tuple".to_string(),
+        Value::Record(..) => "record".to_string(),
+        Value::Lazy(..) => "lazy".to_string(),
+        Value::IO(..) => "io".to_string(),
     }
 }
-fn into_expr(v: &Value, pos: TokenPosition) -> Expr {
-    match v {
-        Value::Int(i, span) => Expr::Atom(Atom::Int(*i, pos, *span)),
-        Value::Float(i, span) => Expr::Atom(Atom::Float(i.clone(), pos, *span)),
-        Value::Bool(i, span) => Expr::Atom(Atom::Bool(*i, pos, *span)),
-        Value::String(i, span) => Expr::Atom(Atom::String(i.clone(), pos, *span)),
-        Value::Char(i, span) => Expr::Atom(Atom::Char(*i, pos, *span)),
-        Value::Array(array, span) => {
-            let array = array.iter().map(|v| into_expr(v, pos)).collect();
-            Expr::Array(array, pos, *span)
+
+/// Performs an action and gives back what it produced, anything else is
+/// given back as it is.
+fn run(mut value: Value, scope: &Scope) -> Result<Value> {
+    loop {
+        let Value::IO(action, span) = value else {
+            return Ok(value);
+        };
+        match action {
+            Action::Print(values) => {
+                let formated = values
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                print!("{formated}");
+                return Ok(values[0].clone());
+            }
+            Action::Pure(value) => return Ok(*value),
+            Action::Bind(action, func) => {
+                let arg = run(*action, scope)?;
+                value = apply(*func, arg, span, scope)?;
+            }
         }
-        _ => unreachable!("{v}"),
     }
 }
 
-fn expr_closure(head: &Expr, tail: &Expr, scope: &Scope) -> Result<Value> {
-    walk_expr(head, scope)?;
-    walk_expr(tail, scope)
-}
-
 fn walk_atom(atom: &Atom, scope: &Scope) -> Result<Value> {
     match atom {
         Atom::Id(name, _, span) => {
+            if let Some(Value::Lazy(name, group, base, span)) = scope.local.get(name) {
+                return force(name, group, base, *span, scope);
+            }
+            if let Some(value) = scope.local.get(name) {
+                return Ok(value.clone());
+            }
+            if scope.constructors.contains_key(name) {
+                return Ok(Value::Variant(name.clone(), vec![], *span));
+            }
             let Some(expr) = scope.get(name) else {
                 return Err(RuntimeError::Undefined(name.into(), *span));
             };
-            walk_expr(expr, scope)
+            walk_expr(expr, &scope.with_local(Env::default()))
         }
         Atom::Int(i, _, span) => Ok(Value::Int(*i, *span)),
-        Atom::Float(f, _, span) => Ok(Value::Float(f.clone(), *span)),
+        Atom::Float(f, _, span) => Ok(Value::Float(*f, *span)),
         Atom::Bool(b, _, span) => Ok(Value::Bool(*b, *span)),
         Atom::String(string, _, span) => Ok(Value::String(string.clone(), *span)),
         Atom::Char(c, _, span) => Ok(Value::Char(*c, *span)),
@@ -407,7 +781,12 @@ fn walk_expr(expr: &Expr, scope: &Scope) -> Result<Value> {
         Expr::IfElse(condition, then, r#else, ..) => {
             expr_conditional(condition, then, r#else, scope)
         }
-        Expr::Closure(head, tail, ..) => expr_closure(head, tail, scope),
+        Expr::Match(expr, arms, span) => expr_match(expr, arms, *span, scope),
+        Expr::Clauses(args, clauses, span) => expr_clauses(args, clauses, *span, scope),
+        Expr::Let(bindings, body, span) => expr_let(bindings, body, *span, scope),
+        Expr::Closure(.., span) => {
+            Ok(Value::Func(expr.clone(), scope.local.clone(), *span))
+        }
         Expr::App(App {
             name, args, span, ..
         }) => expr_app(name, args, *span, scope),
@@ -422,9 +801,20 @@ fn walk_expr(expr: &Expr, scope: &Scope) -> Result<Value> {
             }
             Ok(Value::Array(result, span))
         }
-        Expr::Enum(..) => unimplemented!("enum"),
-
+        Expr::Tuple(items, span) => {
+            let items = items
+                .iter()
+                .map(|item| walk_expr(item, scope))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Value::Tuple(items, *span))
+        }
+        Expr::Record(name, fields, span) => expr_record(name, fields, *span, scope),
+        Expr::Field(expr, field, span) => expr_field(expr, field, *span, scope),
+        Expr::Update(expr, fields, span) => expr_update(expr, fields, *span, scope),
         // should never get to theres
+        Expr::Enum(..) => unreachable!("enum"),
+        Expr::Data(..) => unreachable!("data"),
+        Expr::Alias(..) => unreachable!("alias"),
         Expr::Func(..) => unreachable!("func"),
         Expr::Error(..) => unreachable!("error"),
     }
@@ -441,6 +831,9 @@ pub fn walk(ast: &[Expr]) -> std::result::Result<Option<Value>, Vec<RuntimeError
             Expr::Func(name, _, closure, ..) => {
                 scope.insert_global(name.to_string(), *closure.clone());
             }
+            Expr::Enum(name, _, variants, ..) => scope.insert_enum(name, variants),
+            Expr::Data(name, _, fields, ..) => scope.insert_data(name, fields),
+            Expr::Alias(..) => {}
             _ => unreachable!("{:?}", expr),
         }
     }
@@ -449,21 +842,14 @@ pub fn walk(ast: &[Expr]) -> std::result::Result<Option<Value>, Vec<RuntimeError
         return Err(vec![RuntimeError::MissingMainFunction]);
     };
 
-    let mut errors: Vec<RuntimeError> = Vec::new();
-    if !errors.is_empty() {
-        return Err(errors);
-    }
     let main_function = &ast[idx];
     let Expr::Func(_, _, closure, ..) = main_function else {
         panic!("maybe you added a new prameter to Expr::Func?");
     };
-    match walk_expr(closure, &scope) {
-        Ok(v) => Ok(Some(v)),
-        Err(err) => {
-            errors.push(err);
-            Err(errors)
-        }
-    }
+    walk_expr(closure, &scope)
+        .and_then(|value| run(value, &scope))
+        .map(Some)
+        .map_err(|err| vec![err])
 }
 
 pub fn eval_expr_with_scope(
@@ -475,7 +861,18 @@ pub fn eval_expr_with_scope(
             scope.insert_global(name.to_string(), *closure.clone());
             Ok(None)
         }
-        _ => walk_expr(expr, scope).map(Some),
+        Expr::Enum(name, _, variants, ..) => {
+            scope.insert_enum(name, variants);
+            Ok(None)
+        }
+        Expr::Data(name, _, fields, ..) => {
+            scope.insert_data(name, fields);
+            Ok(None)
+        }
+        Expr::Alias(..) => Ok(None),
+        _ => walk_expr(expr, scope)
+            .and_then(|value| run(value, scope))
+            .map(Some),
     }
 }
 
@@ -486,5 +883,361 @@ fn test_walk() {
     let src = include_str!("./../../../samples/other.snow");
     let ast = parse(src);
     let result = walk(&ast.unwrap()).unwrap();
-    assert_eq!(result, None);
+    assert_eq!(result.map(|v| v.to_string()), Some("[2, 3]".to_string()));
+}
+
+#[test]
+fn test_match() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+sum xs
+  = match xs on
+  | [] -> 0
+  | [x, ..rest] -> x + sum rest
+
+describe n
+  = match n on
+  | 0 -> "zero"
+  | -1 -> "minus one"
+  | _ -> "many"
+
+second xs = match xs on [_, y, .._] -> y | _ -> 0
+
+main = [sum [1, 2, 3], second [4, 5, 6], second [7]] + [describe 0, describe (0 - 1), describe 2]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some("[6, 5, 0, zero, minus one, many]".to_string())
+    );
+}
+
+#[test]
+fn test_enum() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
+
+enum Pair a b = Pair a b
+
+withDefault d o
+  = match o on
+  | Some x -> x
+  | None -> d
+
+pairUp = Pair (Some 1)
+
+main = [withDefault 0 (Some 5), withDefault 0 None] + [pairUp None, None]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some("[5, 0, Pair (Some 1) None, None]".to_string())
+    );
+}
+
+#[test]
+fn test_let() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+fact n = go n 1 where go n acc = if n == 0 then acc else go (n - 1) (acc * n)
+
+adder x = \y -> x + y + one where one = 1
+
+main = let
+    even n = if n == 0 then true else odd (n - 1),
+    odd n = if n == 0 then false else even (n - 1),
+    n = 3
+  in [fact 5, adder n 10, let n = 4 in n * n, n] + [even 10, odd 10]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some("[120, 14, 16, 3, true, false]".to_string())
+    );
+
+    // every binding uses the one before it twice, so this only finishes when
+    // each of them is evaluated once
+    let bindings = (1..=40)
+        .map(|idx| format!("a{idx} = a{} + a{}", idx - 1, idx - 1))
+        .collect::<Vec<_>>();
+    let src = format!("main = let a0 = 1, {} in a40", bindings.join(", "));
+    let ast = parse(&src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some((1i64 << 40).to_string())
+    );
+}
+
+#[test]
+fn test_clauses() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
+
+fromOption d None = d
+fromOption _ (Some x) = x
+
+fib 0 = 0
+fib 1 = 1
+fib n = fib (n - 1) + fib (n - 2)
+
+size xs
+  | len > 2 = "big"
+  | len > 0 = "small"
+  where len = length xs
+size _ = "empty"
+
+main = [fromOption 3 None, fromOption 3 (Some 7), fib 10] + [size [1, 2, 3], size [1], size []]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some("[3, 7, 55, big, small, empty]".to_string())
+    );
+
+    let src = "pos n\n  | n > 0 = n\n\nmain = [pos 1, pos 0]";
+    let ast = parse(src).unwrap();
+    let errors = walk(&ast).unwrap_err();
+    let [RuntimeError::NonExhaustiveMatch(span)] = &errors[..] else {
+        panic!("{errors:?}");
+    };
+    assert_eq!(&src[span.idx_start..span.idx_end], "pos 0");
+
+    // the guards and the body share `c`, so this only finishes when it is
+    // evaluated once for each call
+    let src = r#"
+double 0 = 1
+double n
+  | c < 0 = 0
+  | c >= 0 = c + c
+  where c = double (n - 1)
+
+main = double 20
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some((1i64 << 20).to_string())
+    );
+}
+
+#[test]
+fn test_records() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+data Point = { x: Int, y: Int }
+
+swap (a, b) = (b, a)
+
+norm p = p.x * p.x + p.y * p.y
+
+main =
+  let origin = Point { y = 2, x = 1 }
+      moved = origin { x = 3 }
+  in [norm origin, moved.x, moved.y] + [swap (1, "a"), moved]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some("[5, 3, 2, (a, 1), Point { x = 3, y = 2 }]".to_string())
+    );
+
+    let src = "data Point = { x: Int }\n\nmain = (Point { x = 1 }).z";
+    let ast = parse(src).unwrap();
+    let errors = walk(&ast).unwrap_err();
+    assert!(
+        matches!(&errors[..], [RuntimeError::UnknownField(field, _)] if field == "z")
+    );
+}
+
+#[test]
+fn test_numbers() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+max = 9_223_372_036_854_775_807
+
+main =
+  [ toFloat (max - 1), toFloat (0xff + 0b10 + 0o7), toFloat (-7 / 2), toFloat (-7 % 2)
+  , 1.5 * 2.0, 2.5e-1 - 1.0, 7.5 % 2.0, toFloat 3 / 2.0, toFloat (toInt (-2.9))
+  , toFloat (toInt (1.0 / 0.0))
+  ]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some(
+            "[9.223372036854776e18, 264.0, -3.0, -1.0, 3.0, -0.75, 1.5, 1.5, -2.0, 9.223372036854776e18]"
+                .to_string()
+        )
+    );
+
+    let src = "main = [1.5 < 2.0, 0.1 + 0.2 == 0.3, 2.0 >= 2.0]";
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some("[true, false, true]".to_string())
+    );
+
+    let src = "main = 1 + 7 % 0";
+    let ast = parse(src).unwrap();
+    let errors = walk(&ast).unwrap_err();
+    let [RuntimeError::DivisionByZero(span)] = &errors[..] else {
+        panic!("{errors:?}");
+    };
+    assert_eq!(&src[span.idx_start..span.idx_end], "7 % 0");
+
+    for src in [
+        "main = 9_223_372_036_854_775_807 + 1",
+        "main = 0 - 2 - 9_223_372_036_854_775_807",
+    ] {
+        let ast = parse(src).unwrap();
+        let errors = walk(&ast).unwrap_err();
+        assert!(
+            matches!(&errors[..], [RuntimeError::IntOverflow(..)]),
+            "{errors:?}"
+        );
+    }
+}
+
+#[test]
+fn test_error_report() {
+    use snowc_parse::parse;
+    let src = "f 0 = 1\n\nmain = f 1\n\n";
+    let ast = parse(src).unwrap();
+    let errors = walk(&ast).unwrap_err();
+    let [error @ RuntimeError::NonExhaustiveMatch(..)] = &errors[..] else {
+        panic!("{errors:?}");
+    };
+    assert!(error.report("t.snow", src).contains("t.snow:3:8"));
+}
+
+#[test]
+fn test_sections() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+`<+>` xs ys : Array<a> -> Array<a> -> Array<a> = xs + ys
+
+`~` n = 0 - n
+
+fold f acc xs = if length xs == 0 then acc else fold f (f acc (head xs)) (tail xs)
+
+main = [fold (+) 0 [1, 2, 3], fold (*) 1 [2, 3], (10 -) 1, (- 1) + 3, (/ 2) 9]
+  + [length (fold (<+>) [] [[1], [2, 3]]), length ((<+> [1]) [2]), (~) 3]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some("[6, 6, 9, 2, 4, 3, 2, -3]".to_string())
+    );
+}
+
+#[test]
+fn test_methods() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
+
+enum List a = Cons a (List a) | Nil
+
+data Point = { x: Int, y: Int }
+
+Option.map f = match self on Some x -> Some (f x) | None -> None
+
+List.map f = match self on Cons x xs -> Cons (f x) (xs.map f) | Nil -> Nil
+
+Option.withDefault d = match self on Some x -> x | None -> d
+
+Point.norm = self.x * self.x + self.y * self.y
+
+main = [Option.map (\x -> x + 1) (Some 1), (Some 2).map (\x -> x * 2), (None).map (\x -> x)]
+  + [(Cons 1 (Cons 2 Nil)).map (\x -> x + 1), (None).withDefault 7, (Point { x = 3, y = 4 }).norm]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some("[Some 2, Some 4, None, Cons 2 (Cons 3 Nil), 7, 25]".to_string())
+    );
+    let ast = parse("enum Option a = Some a | None\n\nmain = (Some 1).map").unwrap();
+    let errors = walk(&ast).unwrap_err();
+    assert!(
+        matches!(&errors[..], [RuntimeError::UnknownField(field, _)] if field == "map")
+    );
+}
+
+#[test]
+fn test_do_blocks() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
+
+twice action = do
+  x <- action
+  y <- action
+  pure (x + y)
+
+main = do
+  let quiet = print "never run"
+  x <- twice (pure 20)
+  Some y <- pure (Some (x + 2))
+  pure [x, y]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(result.map(|v| v.to_string()), Some("[40, 42]".to_string()));
+    let src = "enum Option a = Some a | None\nmain = do\n  Some x <- pure None\n  pure x";
+    let ast = parse(src).unwrap();
+    let errors = walk(&ast).unwrap_err();
+    assert!(matches!(
+        &errors[..],
+        [RuntimeError::NonExhaustiveMatch(..)]
+    ));
+}
+
+#[test]
+fn test_ranges_and_comprehensions() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
+
+main = [[1..5], [10, 7 .. 0], [3..1], [x * x | x <- [1..6], x mod 2 == 0]]
+  + [[x + y | x <- [1, 2], y <- [10, 20]], [x | Some x <- [Some 1, None, Some 3]]]
+"#;
+    let ast = parse(src).unwrap();
+    let result = walk(&ast).unwrap();
+    assert_eq!(
+        result.map(|v| v.to_string()),
+        Some(
+            "[[1, 2, 3, 4, 5], [10, 7, 4, 1], [], [4, 16, 36], [11, 21, 12, 22], [1, 3]]"
+                .to_string()
+        )
+    );
+    let ast = parse("main = [1, 1 .. 5]").unwrap();
+    let errors = walk(&ast).unwrap_err();
+    assert!(matches!(&errors[..], [RuntimeError::InvalidArguments(..)]));
+    // definitions do not change what the sugar means
+    let src = "__map f xs = xs\n\n__range a b c = []\n\nmain = [x * 2 | x <- [1..3]]";
+    let result = walk(&parse(src).unwrap()).unwrap();
+    assert_eq!(result.map(|v| v.to_string()), Some("[2, 4, 6]".to_string()));
 }
@@ -1,15 +1,42 @@
 use snowc_parse::{Expr, Span};
+use std::cell::OnceCell;
+use std::collections::HashMap;
 use std::fmt;
+use std::rc::Rc;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
-    Int(i32, Span),
-    Float(String, Span),
+    Int(i64, Span),
+    Float(f64, Span),
     Bool(bool, Span),
     String(String, Span),
     Char(char, Span),
     Array(Vec<Self>, Span),
-    Func(Expr, Span),
+    Func(Expr, HashMap<String, Value>, Span),
+    Variant(String, Vec<Self>, Span),
+    Tuple(Vec<Self>, Span),
+    /// The fields of a record, in the order they were declared.
+    Record(String, Vec<(String, Self)>, Span),
+    /// A `let` or `where` binding which is evaluated the first time it is
+    /// looked up, holding every binding of its group so they can refer to
+    /// each other.
+    Lazy(String, Rc<Group>, HashMap<String, Value>, Span),
+    /// An action which is performed when `main` runs it, not when it is built.
+    IO(Action, Span),
+}
+
+/// The bindings of a `let` group next to the values they were forced to.
+pub type Group = Vec<(String, Expr, OnceCell<Value>)>;
+
+/// What an `IO` value does once it is run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Prints the values and gives back the first one.
+    Print(Vec<Value>),
+    Pure(Box<Value>),
+    /// Runs the action and hands what it gave to the function, which gives
+    /// back the action to run next.
+    Bind(Box<Value>, Box<Value>),
 }
 
 impl Value {
@@ -21,7 +48,12 @@ impl Value {
             Self::String(_, span) => *span,
             Self::Char(_, span) => *span,
             Self::Array(_, span) => *span,
-            Self::Func(_, span) => *span,
+            Self::Func(.., span) => *span,
+            Self::Variant(.., span) => *span,
+            Self::Tuple(.., span) => *span,
+            Self::Record(.., span) => *span,
+            Self::Lazy(.., span) => *span,
+            Self::IO(.., span) => *span,
         }
     }
 }
@@ -30,7 +62,7 @@ impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Int(i, ..) => write!(f, "{i}"),
-            Self::Float(i, ..) => write!(f, "{i}"),
+            Self::Float(i, ..) => write!(f, "{i:?}"),
             Self::Bool(b, ..) => write!(f, "{b}"),
             Self::String(s, ..) => write!(f, "{s}"),
             Self::Char(s, ..) => write!(f, "{s}"),
@@ -48,7 +80,32 @@ impl fmt::Display for Value {
                 a += "]";
                 write!(f, "{a}")
             }
+            Self::Tuple(items, ..) => {
+                let items = items.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "({})", items.join(", "))
+            }
+            Self::Record(name, fields, ..) => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, value)| format!("{field} = {value}"))
+                    .collect::<Vec<_>>();
+                write!(f, "{name} {{ {} }}", fields.join(", "))
+            }
             Self::Func(expr, ..) => write!(f, "{expr}"),
+            Self::Lazy(name, ..) => write!(f, "{name}"),
+            Self::IO(..) => write!(f, "<io>"),
+            Self::Variant(name, values, ..) => {
+                write!(f, "{name}")?;
+                for value in values.iter() {
+                    match value {
+                        Self::Variant(_, values, ..) if !values.is_empty() => {
+                            write!(f, " ({value})")?
+                        }
+                        _ => write!(f, " {value}")?,
+                    }
+                }
+                Ok(())
+            }
         }
     }
 }
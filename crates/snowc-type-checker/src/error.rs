@@ -0,0 +1,253 @@
+use crate::Type;
+use annotate_snippets::{
+    display_list::{DisplayList, FormatOptions},
+    snippet::{Annotation, AnnotationType, Slice, Snippet, SourceAnnotation},
+};
+use snowc_parse::{Op, Span};
+use thiserror::Error;
+
+/// The expression that decided the type another expression was expected to
+/// have, pointed at along with the mismatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// The `then` branch of an `if`.
+    IfBranch(Span),
+    /// The first arm of a `match`.
+    MatchArm(Span),
+    /// The first body of a function with more than one clause.
+    Clause(Span),
+    /// The first item of an array.
+    ArrayItem(Span),
+    /// The left hand side of a binary operator.
+    Operand(Span),
+}
+
+impl Origin {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::IfBranch(s)
+            | Self::MatchArm(s)
+            | Self::Clause(s)
+            | Self::ArrayItem(s)
+            | Self::Operand(s) => *s,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::IfBranch(..) => "expected because of this branch",
+            Self::MatchArm(..) => "expected because of the first arm",
+            Self::Clause(..) => "expected because of the first clause",
+            Self::ArrayItem(..) => "expected because of the first item",
+            Self::Operand(..) => "expected because of this operand",
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TypeError {
+    #[error("mismatched types")]
+    Mismatch {
+        expected: Type,
+        found: Type,
+        span: Span,
+        origin: Option<Origin>,
+    },
+    #[error("infinite type")]
+    InfiniteType(Type, Type, Span),
+    #[error("undefined identifier '{0}'")]
+    Undefined(String, Span),
+    #[error("unknown type '{0}'")]
+    UnknownType(String, Span),
+    #[error("wrong number of type arguments")]
+    TypeArguments(String, usize, usize, Span),
+    #[error("wrong number of constructor arguments")]
+    ConstructorArguments(String, usize, usize, Span),
+    #[error("invalid operand for '{0}'")]
+    InvalidOperand(Op, Type, Span),
+    #[error("signature is more general than the definition")]
+    SignatureTooGeneral(String, Type, Span),
+    #[error("unknown field '{0}'")]
+    UnknownField(String, Type, Span),
+    #[error("ambiguous field '{0}'")]
+    AmbiguousField(String, Span),
+    #[error("missing field '{1}'")]
+    MissingField(String, String, Span),
+    #[error("cyclic type alias '{0}'")]
+    CyclicAlias(String, String, Span),
+}
+
+impl TypeError {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Mismatch { span, .. }
+            | Self::InfiniteType(.., span)
+            | Self::Undefined(_, span)
+            | Self::UnknownType(_, span)
+            | Self::TypeArguments(.., span)
+            | Self::ConstructorArguments(.., span)
+            | Self::InvalidOperand(.., span)
+            | Self::SignatureTooGeneral(.., span)
+            | Self::UnknownField(.., span)
+            | Self::AmbiguousField(_, span)
+            | Self::MissingField(.., span)
+            | Self::CyclicAlias(.., span) => *span,
+        }
+    }
+
+    /// What went wrong, shown under the primary span.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Mismatch {
+                expected, found, ..
+            } => format!("expected '{expected}' but found '{found}'"),
+            Self::InfiniteType(var, ty, _) => format!("'{var}' occurs in '{ty}'"),
+            Self::Undefined(..) => "not found in this scope".into(),
+            Self::UnknownType(..) => "not declared".into(),
+            Self::TypeArguments(name, expected, found, _) => {
+                format!("'{name}' takes {expected} type arguments but {found} were given")
+            }
+            Self::ConstructorArguments(name, expected, found, _) => {
+                format!("'{name}' takes {expected} arguments but {found} were given")
+            }
+            Self::InvalidOperand(op, ty, _) => {
+                format!("'{op}' can not be used on '{ty}'")
+            }
+            Self::SignatureTooGeneral(name, ty, _) => {
+                format!("'{name}' is only defined for '{ty}'")
+            }
+            Self::UnknownField(field, Type::Var(_), _) => {
+                format!("no record has a field named '{field}'")
+            }
+            Self::UnknownField(field, ty, _) => format!("'{ty}' has no field '{field}'"),
+            Self::AmbiguousField(field, _) => {
+                format!("more than one type has a field or method named '{field}'")
+            }
+            Self::MissingField(name, field, _) => {
+                format!("'{name}' needs a value for '{field}'")
+            }
+            Self::CyclicAlias(name, cycle, _) => {
+                format!("'{name}' refers to itself through {cycle}")
+            }
+        }
+    }
+
+    pub fn help(&self) -> Option<String> {
+        match self {
+            Self::Mismatch {
+                expected, found, ..
+            } => match (expected, found) {
+                (Type::Func(..), Type::Func(..)) => None,
+                (_, Type::Func(..)) => {
+                    Some("this function may be missing some arguments".into())
+                }
+                (Type::Func(..), _) => Some("only functions can be applied".into()),
+                _ => None,
+            },
+            Self::InfiniteType(..) => {
+                Some("this happens when a function is applied to itself".into())
+            }
+            Self::Undefined(name, _) => Some(format!(
+                "'{name}' needs to be a definition, a parameter or a local binding"
+            )),
+            Self::UnknownType(..) => {
+                Some("new types are declared with 'enum' or 'data'".into())
+            }
+            Self::InvalidOperand(op, ..) => {
+                let types = match op {
+                    Op::Plus => "Int, Float, String and Array",
+                    Op::Grt | Op::Les | Op::GrtEq | Op::LesEq => {
+                        "Int, Float, Char and String"
+                    }
+                    _ => "Int and Float",
+                };
+                Some(format!("'{op}' works on {types}"))
+            }
+            Self::SignatureTooGeneral(_, ty, _) => {
+                Some(format!("change the signature to '{ty}'"))
+            }
+            Self::AmbiguousField(..) => {
+                Some("a signature can tell which type is meant".into())
+            }
+            Self::CyclicAlias(..) => {
+                Some("recursive types are declared with 'enum' or 'data'".into())
+            }
+            Self::TypeArguments(..)
+            | Self::ConstructorArguments(..)
+            | Self::UnknownField(..)
+            | Self::MissingField(..) => None,
+        }
+    }
+
+    pub fn report(&self, filename: &str, src: &str) -> String {
+        let span = self.span();
+        if span.idx_end > src.len() {
+            return format!("{self} at {filename} {span:?}, {}", self.label());
+        }
+        let origin = match self {
+            Self::Mismatch { origin, .. } => *origin,
+            _ => None,
+        };
+        let spans = std::iter::once(span).chain(origin.map(|origin| origin.span()));
+        let (start, end, line_start) = excerpt(src, spans);
+        let range = |span: Span| (span.idx_start - start, span.idx_end - start);
+        let title = self.to_string();
+        let label = self.label();
+        let mut annotations = vec![SourceAnnotation {
+            label: &label,
+            annotation_type: AnnotationType::Error,
+            range: range(span),
+        }];
+        if let Some(origin) = origin {
+            annotations.push(SourceAnnotation {
+                label: origin.label(),
+                annotation_type: AnnotationType::Info,
+                range: range(origin.span()),
+            });
+        }
+        let help = self.help();
+        let footer = help
+            .iter()
+            .map(|help| Annotation {
+                label: Some(help),
+                id: None,
+                annotation_type: AnnotationType::Help,
+            })
+            .collect();
+        let snippet = Snippet {
+            title: Some(Annotation {
+                label: Some(&title),
+                id: None,
+                annotation_type: AnnotationType::Error,
+            }),
+            footer,
+            slices: vec![Slice {
+                source: &src[start..end],
+                line_start,
+                origin: Some(filename),
+                fold: true,
+                annotations,
+            }],
+            opt: FormatOptions {
+                color: true,
+                ..Default::default()
+            },
+        };
+        DisplayList::from(snippet).to_string()
+    }
+}
+
+/// The lines of `src` covering every span, as the index of the first and
+/// last character along with the line number they start on.
+fn excerpt(src: &str, spans: impl Iterator<Item = Span>) -> (usize, usize, usize) {
+    let (lo, hi) = spans.fold((usize::MAX, 0), |(lo, hi), span| {
+        (lo.min(span.idx_start), hi.max(span.idx_end))
+    });
+    let start = src[..lo].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let end = src[hi..]
+        .find('\n')
+        .map(|idx| hi + idx)
+        .unwrap_or(src.len());
+    let line_start = src[..start].matches('\n').count() + 1;
+    (start, end, line_start)
+}
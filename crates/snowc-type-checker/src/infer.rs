@@ -0,0 +1,1495 @@
+use crate::{Origin, Scheme, Type, TypeError, Types};
+use snowc_parse::{
+    is_primitive, method_name, App, Atom, Binary, Expr, Op, Pattern, Span, TypeInfo,
+    Unary, BUILTINS,
+};
+use std::collections::HashMap;
+use std::iter::Peekable;
+
+/// The kinds of types an operator works on. These are checked once every
+/// definition they show up in has been inferred, defaulting to `Int` when
+/// nothing else is known. An operand that is still a parameter of the
+/// definition stays open, `cat a b = a + b` takes anything that can be added
+/// and is checked again wherever it is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operand {
+    Numeric,
+    Addable,
+    Ordered,
+}
+
+impl Operand {
+    fn accepts(self, ty: &Type) -> bool {
+        matches!(
+            (self, ty),
+            (_, Type::Int | Type::Float)
+                | (Self::Addable, Type::String | Type::Array(_))
+                | (Self::Ordered, Type::String | Type::Char)
+        )
+    }
+}
+
+/// A type alias, `type Pair a = (a, a)`.
+struct Alias {
+    params: Vec<String>,
+    ty: TypeInfo,
+    /// Refers to itself, the error is reported once where it is declared.
+    is_cyclic: bool,
+}
+
+/// The fields of a record, their types are kept as a function from the record
+/// to a tuple of the fields, `Pair a b -> (a, b)`, so instantiating it keeps
+/// the record and its fields in step.
+struct Record {
+    fields: Vec<String>,
+    scheme: Scheme,
+}
+
+enum Failure {
+    Mismatch,
+    Occurs(usize, Type),
+}
+
+/// Algorithm W over the whole program. Definitions are checked one strongly
+/// connected group at a time so a function can be used at different types
+/// by the functions that come after it.
+#[derive(Default)]
+pub(crate) struct Infer {
+    bindings: Vec<Option<Type>>,
+    /// How many type arguments each enum and record takes.
+    enums: HashMap<String, usize>,
+    records: HashMap<String, Record>,
+    aliases: HashMap<String, Alias>,
+    /// How many arguments each method takes before `self`.
+    methods: HashMap<String, usize>,
+    globals: Types,
+    builtins: Types,
+    operands: Vec<(Operand, Op, Type, Span)>,
+    errors: Vec<TypeError>,
+}
+
+impl Infer {
+    pub(crate) fn program(mut self, ast: &[Expr]) -> Result<Types, Vec<TypeError>> {
+        self.define_builtins();
+        for expr in ast.iter() {
+            match expr {
+                Expr::Enum(name, params, ..) | Expr::Data(name, params, ..) => {
+                    self.enums.insert(name.clone(), params.len());
+                }
+                Expr::Alias(name, params, ty, _) => {
+                    let alias = Alias {
+                        params: params.clone(),
+                        ty: ty.clone(),
+                        is_cyclic: false,
+                    };
+                    self.aliases.insert(name.clone(), alias);
+                }
+                _ => {}
+            }
+        }
+        for expr in ast.iter() {
+            if let Expr::Alias(name, _, _, span) = expr {
+                self.check_alias(name, *span);
+            }
+        }
+        for expr in ast.iter() {
+            match expr {
+                Expr::Enum(name, params, variants, _, span) => {
+                    self.define_enum(name, params, variants, *span)
+                }
+                Expr::Data(name, params, fields, span) => {
+                    self.define_data(name, params, fields, *span)
+                }
+                _ => {}
+            }
+        }
+
+        let funcs = ast
+            .iter()
+            .filter_map(|expr| match expr {
+                Expr::Func(name, types, body, _, span) => {
+                    Some((name, types, body, *span))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (name, _, body, span) in funcs.iter() {
+            self.define_method(name, body, *span);
+        }
+        let names = funcs
+            .iter()
+            .map(|(name, ..)| name.as_str())
+            .collect::<Vec<_>>();
+        let deps = funcs
+            .iter()
+            .map(|(_, _, body, _)| {
+                let mut refs = vec![];
+                references(body, &mut vec![], &mut refs);
+                refs.iter()
+                    .flat_map(|reference| {
+                        let names = names.iter().enumerate();
+                        names
+                            .filter(|(_, name)| refers_to(reference, name))
+                            .map(|(idx, _)| idx)
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+
+        for group in components(&deps) {
+            let mut signatures = vec![];
+            for &idx in group.iter() {
+                let (name, types, _, span) = funcs[idx];
+                let ty = self.method_type(name);
+                let vars = match types {
+                    None => HashMap::new(),
+                    Some(types) => {
+                        let (sig, vars) = self.signature(types, span);
+                        self.unify(&sig, &ty, span);
+                        vars
+                    }
+                };
+                self.globals.insert(name.clone(), Scheme::mono(ty.clone()));
+                signatures.push((ty, vars));
+            }
+            for (&idx, (ty, _)) in group.iter().zip(signatures.iter()) {
+                let (_, _, body, _) = funcs[idx];
+                self.check(body, ty, &Types::new());
+            }
+            let open = self.check_operands(&signatures);
+            for (&idx, (ty, vars)) in group.iter().zip(signatures.iter()) {
+                let (name, _, _, span) = funcs[idx];
+                if !self.is_rigid(vars) {
+                    let [ty] = &Type::rename(&[&self.zonk(ty)])[..] else {
+                        unreachable!();
+                    };
+                    let error =
+                        TypeError::SignatureTooGeneral(name.clone(), ty.clone(), span);
+                    self.errors.push(error);
+                }
+                let mut scheme = self.generalize(ty);
+                scheme.operands = open
+                    .iter()
+                    .filter(|(var, ..)| scheme.vars.contains(var))
+                    .copied()
+                    .collect();
+                self.globals.insert(name.clone(), scheme);
+            }
+        }
+
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+        Ok(self.globals)
+    }
+
+    fn define_builtins(&mut self) {
+        let (a, b) = (self.fresh(), self.fresh());
+        let array = Type::array(a.clone());
+        let builtins = [
+            ("print", Type::func(a.clone(), Type::io(a.clone()))),
+            ("length", Type::func(array.clone(), Type::Int)),
+            ("head", Type::func(array.clone(), a.clone())),
+            ("tail", Type::func(array.clone(), array.clone())),
+            (
+                "nth",
+                Type::func(array.clone(), Type::func(Type::Int, a.clone())),
+            ),
+            (
+                "push",
+                Type::func(array.clone(), Type::func(a.clone(), array.clone())),
+            ),
+            ("toFloat", Type::func(Type::Int, Type::Float)),
+            ("toInt", Type::func(Type::Float, Type::Int)),
+            (
+                "__range",
+                Type::func(
+                    Type::Int,
+                    Type::func(Type::Int, Type::func(Type::Int, Type::array(Type::Int))),
+                ),
+            ),
+            (
+                "__map",
+                Type::func(
+                    Type::func(a.clone(), b.clone()),
+                    Type::func(array.clone(), Type::array(b.clone())),
+                ),
+            ),
+            (
+                "__filter",
+                Type::func(
+                    Type::func(a.clone(), Type::Bool),
+                    Type::func(array.clone(), array.clone()),
+                ),
+            ),
+            ("__concat", Type::func(Type::array(array.clone()), array)),
+            ("pure", Type::func(a.clone(), Type::io(a.clone()))),
+            (
+                "__bind",
+                Type::func(
+                    Type::io(a.clone()),
+                    Type::func(Type::func(a, Type::io(b.clone())), Type::io(b)),
+                ),
+            ),
+        ];
+        for (name, ty) in builtins {
+            let scheme = self.generalize(&ty);
+            self.builtins.insert(name.into(), scheme);
+        }
+    }
+
+    /// Methods are checked like any other definition, remembering how many
+    /// arguments come before `self` for when they are used as `opt.map`.
+    fn define_method(&mut self, name: &str, body: &Expr, span: Span) {
+        let Some((ty, _)) = method_name(name) else {
+            return;
+        };
+        if !self.enums.contains_key(ty) {
+            self.errors.push(TypeError::UnknownType(ty.into(), span));
+            return;
+        }
+        let mut arity = 0usize;
+        let mut body = body;
+        while let Expr::Closure(_, inner, ..) = body {
+            arity += 1;
+            body = inner;
+        }
+        self.methods.insert(name.into(), arity.saturating_sub(1));
+    }
+
+    /// A fresh type for the definition `name`, where `self` of a method has
+    /// the type the method belongs to, `Option.map f : a -> Option b -> c`.
+    fn method_type(&mut self, name: &str) -> Type {
+        let Some(&arity) = self.methods.get(name) else {
+            return self.fresh();
+        };
+        let Some((ty, _)) = method_name(name) else {
+            unreachable!("method '{name}'");
+        };
+        let args = (0..self.enums[ty]).map(|_| self.fresh()).collect();
+        let receiver = Type::Con(ty.into(), args);
+        let ret = self.fresh();
+        (0..arity).fold(Type::func(receiver, ret), |ret, _| {
+            Type::func(self.fresh(), ret)
+        })
+    }
+
+    /// `opt.map` is `Option.map` given `opt` for `self`, leaving the
+    /// arguments that come before it, `(a -> b) -> Option b`.
+    fn bind_self(&mut self, method: &str, receiver: &Type, span: Span) -> Type {
+        let ty = self.lookup(method, span, &Types::new());
+        let params = (0..self.methods[method])
+            .map(|_| self.fresh())
+            .collect::<Vec<_>>();
+        let ret = self.fresh();
+        let expected = params
+            .iter()
+            .rev()
+            .fold(Type::func(receiver.clone(), ret.clone()), |ret, param| {
+                Type::func(param.clone(), ret)
+            });
+        self.unify(&expected, &ty, span);
+        params
+            .into_iter()
+            .rev()
+            .fold(ret, |ret, param| Type::func(param, ret))
+    }
+
+    /// The methods `field` could stand for on a value of type `ty`. A field
+    /// of a record comes first and when the type is not known yet, every
+    /// type with such a method is a candidate.
+    fn methods(&self, ty: &Type, field: &str) -> Vec<String> {
+        let has_field = |record: &Record| record.fields.iter().any(|f| f == field);
+        match self.resolve(ty) {
+            Type::Con(name, _) if !self.records.get(&name).is_some_and(has_field) => {
+                let method = format!("{name}.{field}");
+                match self.methods.contains_key(&method) {
+                    true => vec![method],
+                    false => vec![],
+                }
+            }
+            Type::Var(_) if !self.records.values().any(has_field) => self
+                .methods
+                .keys()
+                .filter(|name| method_name(name).is_some_and(|(_, m)| m == field))
+                .cloned()
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Every constructor becomes a function from its fields to the enum,
+    /// `Some : a -> Option a`.
+    fn define_enum(
+        &mut self,
+        name: &str,
+        params: &[String],
+        variants: &[(String, Vec<String>)],
+        span: Span,
+    ) {
+        let args = params.iter().map(|_| self.fresh()).collect::<Vec<_>>();
+        let params = params.iter().cloned().zip(args.iter().cloned()).collect();
+        let ret = Type::Con(name.into(), args);
+        for (variant, fields) in variants.iter() {
+            let ty = fields.iter().rev().fold(ret.clone(), |ret, field| {
+                let field = self.field(field, &params, span);
+                Type::func(field, ret)
+            });
+            let scheme = self.generalize(&ty);
+            self.globals.insert(variant.clone(), scheme);
+        }
+    }
+
+    fn define_data(
+        &mut self,
+        name: &str,
+        params: &[String],
+        fields: &[(String, String)],
+        span: Span,
+    ) {
+        let args = params.iter().map(|_| self.fresh()).collect::<Vec<_>>();
+        let params = params.iter().cloned().zip(args.iter().cloned()).collect();
+        let types = fields
+            .iter()
+            .map(|(_, field)| self.field(field, &params, span))
+            .collect();
+        let ty = Type::func(Type::Con(name.into(), args), Type::Tuple(types));
+        let record = Record {
+            fields: fields.iter().map(|(field, _)| field.clone()).collect(),
+            scheme: self.generalize(&ty),
+        };
+        self.records.insert(name.into(), record);
+    }
+
+    /// Aliases can not refer to themselves, `type Grid = Array<Grid>` would
+    /// never finish expanding.
+    fn check_alias(&mut self, name: &str, span: Span) {
+        if self.aliases[name].is_cyclic {
+            return;
+        }
+        let mut path = vec![name.to_string()];
+        if !self.alias_cycle(&mut path) {
+            return;
+        }
+        for alias in path.iter() {
+            if let Some(alias) = self.aliases.get_mut(alias) {
+                alias.is_cyclic = true;
+            }
+        }
+        let cycle = path
+            .iter()
+            .map(|name| format!("'{name}'"))
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        let error = TypeError::CyclicAlias(name.into(), cycle, span);
+        self.errors.push(error);
+    }
+
+    /// Looks for a way back to the first alias of `path` from the last one,
+    /// leaving the aliases on the way in `path`.
+    fn alias_cycle(&self, path: &mut Vec<String>) -> bool {
+        let mut names = vec![];
+        let last = &path[path.len() - 1];
+        type_names(&self.aliases[last].ty, &mut names);
+        for name in names {
+            if name == path[0] {
+                path.push(name);
+                return true;
+            }
+            if !self.aliases.contains_key(&name) || path.contains(&name) {
+                continue;
+            }
+            path.push(name);
+            if self.alias_cycle(path) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    /// Replaces an alias by the type it stands for.
+    fn expand(&mut self, name: &str, args: Vec<Type>, span: Span) -> Type {
+        let alias = &self.aliases[name];
+        if alias.is_cyclic {
+            return self.fresh();
+        }
+        if alias.params.len() != args.len() {
+            let expected = alias.params.len();
+            let error = TypeError::TypeArguments(name.into(), expected, args.len(), span);
+            self.errors.push(error);
+            return self.fresh();
+        }
+        let ty = alias.ty.clone();
+        let mut vars = alias.params.iter().cloned().zip(args.clone()).collect();
+        let ty = self.type_info(&ty, &mut vars, span);
+        Type::Alias(name.into(), args, Box::new(ty))
+    }
+
+    fn field(&mut self, field: &str, params: &HashMap<String, Type>, span: Span) -> Type {
+        let tokens = field_tokens(field);
+        let mut tokens = tokens.iter().map(String::as_str).peekable();
+        self.field_type(&mut tokens, params, span)
+    }
+
+    /// `field ::= name atom* ("->" field)? | atom ("->" field)?`
+    fn field_type<'a>(
+        &mut self,
+        tokens: &mut Peekable<impl Iterator<Item = &'a str>>,
+        params: &HashMap<String, Type>,
+        span: Span,
+    ) -> Type {
+        let ty = match tokens.peek() {
+            Some(&name) if name != "(" && name != "Array" => {
+                tokens.next();
+                let mut args = vec![];
+                while !matches!(tokens.peek(), None | Some(&(")" | ">" | "->" | ","))) {
+                    args.push(self.field_atom(tokens, params, span));
+                }
+                self.field_name(name, args, params, span)
+            }
+            _ => self.field_atom(tokens, params, span),
+        };
+        if tokens.next_if_eq(&"->").is_some() {
+            return Type::func(ty, self.field_type(tokens, params, span));
+        }
+        ty
+    }
+
+    /// `atom ::= "(" field ("," field)* ")" | "Array" "<" field ">" | name`
+    fn field_atom<'a>(
+        &mut self,
+        tokens: &mut Peekable<impl Iterator<Item = &'a str>>,
+        params: &HashMap<String, Type>,
+        span: Span,
+    ) -> Type {
+        match tokens.next() {
+            Some("(") => {
+                let mut items = vec![self.field_type(tokens, params, span)];
+                while tokens.next_if_eq(&",").is_some() {
+                    items.push(self.field_type(tokens, params, span));
+                }
+                tokens.next_if_eq(&")");
+                match items.len() {
+                    1 => items.remove(0),
+                    _ => Type::Tuple(items),
+                }
+            }
+            Some("Array") if tokens.next_if_eq(&"<").is_some() => {
+                let item = self.field_type(tokens, params, span);
+                tokens.next_if_eq(&">");
+                Type::array(item)
+            }
+            Some(name) => self.field_name(name, vec![], params, span),
+            None => {
+                self.errors
+                    .push(TypeError::UnknownType(String::new(), span));
+                self.fresh()
+            }
+        }
+    }
+
+    fn field_name(
+        &mut self,
+        name: &str,
+        args: Vec<Type>,
+        params: &HashMap<String, Type>,
+        span: Span,
+    ) -> Type {
+        if let Some(ty) = params.get(name) {
+            return ty.clone();
+        }
+        if self.aliases.contains_key(name) {
+            return self.expand(name, args, span);
+        }
+        match self.type_name(name, span) {
+            Type::Con(name, params) if params.len() != args.len() => {
+                let error =
+                    TypeError::TypeArguments(name, params.len(), args.len(), span);
+                self.errors.push(error);
+                self.fresh()
+            }
+            Type::Con(name, _) => Type::Con(name, args),
+            Type::IO(_) if args.len() == 1 => Type::io(args[0].clone()),
+            Type::IO(_) => {
+                let error = TypeError::TypeArguments("IO".into(), 1, args.len(), span);
+                self.errors.push(error);
+                self.fresh()
+            }
+            ty => ty,
+        }
+    }
+
+    fn type_name(&mut self, name: &str, span: Span) -> Type {
+        match name {
+            "Int" => Type::Int,
+            "Float" => Type::Float,
+            "Bool" => Type::Bool,
+            "String" => Type::String,
+            "Char" => Type::Char,
+            "IO" => Type::io(self.fresh()),
+            name if self.aliases.contains_key(name) => {
+                let arity = self.aliases[name].params.len();
+                let args = (0..arity).map(|_| self.fresh()).collect();
+                self.expand(name, args, span)
+            }
+            name => match self.enums.get(name) {
+                Some(&arity) => {
+                    let args = (0..arity).map(|_| self.fresh()).collect();
+                    Type::Con(name.into(), args)
+                }
+                None => {
+                    self.errors.push(TypeError::UnknownType(name.into(), span));
+                    self.fresh()
+                }
+            },
+        }
+    }
+
+    /// Turns `Int -> Array<a> -> a` into a type, giving back the variables
+    /// that were named in it.
+    fn signature(
+        &mut self,
+        type_info: &TypeInfo,
+        span: Span,
+    ) -> (Type, HashMap<String, Type>) {
+        let mut vars = HashMap::new();
+        let ty = self.type_info(type_info, &mut vars, span);
+        (ty, vars)
+    }
+
+    fn type_info(
+        &mut self,
+        type_info: &TypeInfo,
+        vars: &mut HashMap<String, Type>,
+        span: Span,
+    ) -> Type {
+        match type_info {
+            TypeInfo::Int => Type::Int,
+            TypeInfo::Float => Type::Float,
+            TypeInfo::Bool => Type::Bool,
+            TypeInfo::String => Type::String,
+            TypeInfo::Char => Type::Char,
+            TypeInfo::Array(item) => Type::array(self.type_info(item, vars, span)),
+            TypeInfo::Tuple(items) => Type::Tuple(
+                items
+                    .iter()
+                    .map(|item| self.type_info(item, vars, span))
+                    .collect(),
+            ),
+            TypeInfo::Var(name) => match vars.get(name) {
+                Some(ty) => ty.clone(),
+                None => {
+                    let ty = self.fresh();
+                    vars.insert(name.clone(), ty.clone());
+                    ty
+                }
+            },
+            TypeInfo::Fn(param, ret) => {
+                let param = self.type_info(param, vars, span);
+                Type::func(param, self.type_info(ret, vars, span))
+            }
+            // `Option` on its own leaves the arguments to be inferred
+            TypeInfo::App(name, args) if args.is_empty() => self.type_name(name, span),
+            TypeInfo::App(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.type_info(arg, vars, span))
+                    .collect();
+                self.field_name(name, args, &HashMap::new(), span)
+            }
+        }
+    }
+
+    /// The variables named in a signature have to stay distinct variables,
+    /// `id x : a -> a = x + 1` is not allowed.
+    fn is_rigid(&mut self, vars: &HashMap<String, Type>) -> bool {
+        let mut seen = vec![];
+        for ty in vars.values() {
+            match self.zonk(ty) {
+                Type::Var(var) if !seen.contains(&var) => seen.push(var),
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Checks the operators of a group of definitions, giving back the ones
+    /// whose operands are still a variable in the type of a definition.
+    fn check_operands(
+        &mut self,
+        signatures: &[(Type, HashMap<String, Type>)],
+    ) -> Vec<(usize, Operand, Op)> {
+        let mut vars = vec![];
+        for (ty, _) in signatures {
+            self.zonk(ty).vars(&mut vars);
+        }
+        let mut open = vec![];
+        for (operand, op, ty, span) in std::mem::take(&mut self.operands) {
+            let ty = self.resolve(&ty);
+            if let Type::Var(var) = ty {
+                if vars.contains(&var) {
+                    open.push((var, operand, op));
+                } else {
+                    self.unify(&Type::Int, &ty, span);
+                }
+                continue;
+            }
+            if !operand.accepts(&ty) {
+                let [ty] = &Type::rename(&[&self.zonk(&ty)])[..] else {
+                    unreachable!();
+                };
+                self.errors
+                    .push(TypeError::InvalidOperand(op, ty.clone(), span));
+            }
+        }
+        open
+    }
+
+    fn check(&mut self, expr: &Expr, expected: &Type, env: &Types) {
+        self.check_with(expr, expected, None, env);
+    }
+
+    /// Checks `expr` against `expected`, where `origin` is the expression
+    /// that made us expect that type.
+    fn check_with(
+        &mut self,
+        expr: &Expr,
+        expected: &Type,
+        origin: Option<Origin>,
+        env: &Types,
+    ) {
+        match (expr, self.resolve(expected)) {
+            (Expr::Closure(head, body, ..), Type::Func(param, ret)) => {
+                let mut env = env.clone();
+                if let Expr::Atom(Atom::Id(name, ..)) = head.as_ref() {
+                    env.insert(name.clone(), Scheme::mono(*param));
+                }
+                self.check_with(body, &ret, origin, &env);
+            }
+            (Expr::IfElse(condition, then, r#else, ..), _) => {
+                self.check(condition, &Type::Bool, env);
+                self.check_with(then, expected, origin, env);
+                let origin = origin.or(Some(Origin::IfBranch(then.span())));
+                self.check_with(r#else, expected, origin, env);
+            }
+            (Expr::Match(expr, arms, ..), _) => {
+                let ty = self.infer(expr, env);
+                let first = arms.first().map(|(_, arm)| Origin::MatchArm(arm.span()));
+                for (idx, (pattern, arm)) in arms.iter().enumerate() {
+                    let mut env = env.clone();
+                    self.check_pattern(pattern, &ty, &mut env);
+                    let origin = if idx == 0 { origin } else { origin.or(first) };
+                    self.check_with(arm, expected, origin, &env);
+                }
+            }
+            (Expr::Clauses(args, clauses, ..), _) => {
+                let types = args
+                    .iter()
+                    .map(|arg| self.infer(arg, env))
+                    .collect::<Vec<_>>();
+                let first = clauses
+                    .first()
+                    .and_then(|clause| clause.bodies.first())
+                    .map(|(_, body)| Origin::Clause(body.span()));
+                let mut is_first = true;
+                for clause in clauses.iter() {
+                    let mut env = env.clone();
+                    for (pattern, ty) in clause.patterns.iter().zip(types.iter()) {
+                        self.check_pattern(pattern, ty, &mut env);
+                    }
+                    let env = self.let_bindings(&clause.bindings, &env);
+                    for (guard, body) in clause.bodies.iter() {
+                        if let Some(guard) = guard {
+                            self.check(guard, &Type::Bool, &env);
+                        }
+                        let origin = if is_first { origin } else { origin.or(first) };
+                        self.check_with(body, expected, origin, &env);
+                        is_first = false;
+                    }
+                }
+            }
+            (Expr::Let(bindings, body, ..), _) => {
+                let env = self.let_bindings(bindings, env);
+                self.check_with(body, expected, origin, &env);
+            }
+            _ => {
+                let found = self.infer(expr, env);
+                self.unify_with(expected, &found, expr.span(), origin);
+            }
+        }
+    }
+
+    fn infer(&mut self, expr: &Expr, env: &Types) -> Type {
+        match expr {
+            Expr::Atom(Atom::Id(name, _, span)) => self.lookup(name, *span, env),
+            Expr::Atom(atom) => atom_type(atom),
+            Expr::Unary(Unary { op, expr, span, .. }) => {
+                let ty = self.infer(expr, env);
+                match op {
+                    Op::Not => {
+                        self.unify(&Type::Bool, &ty, expr.span());
+                        Type::Bool
+                    }
+                    op => {
+                        self.operands
+                            .push((Operand::Numeric, *op, ty.clone(), *span));
+                        ty
+                    }
+                }
+            }
+            Expr::Binary(binary) => self.infer_binary(binary, env),
+            Expr::IfElse(..)
+            | Expr::Match(..)
+            | Expr::Clauses(..)
+            | Expr::Let(..)
+            | Expr::Closure(..) => {
+                let ty = self.fresh();
+                if let Expr::Closure(..) = expr {
+                    let param = self.fresh();
+                    let ret = self.fresh();
+                    self.unify(&ty, &Type::func(param, ret), expr.span());
+                }
+                self.check(expr, &ty, env);
+                ty
+            }
+            Expr::App(App { name, args, .. }) => {
+                if let Expr::Atom(Atom::Id(id, ..)) = name.as_ref() {
+                    // print takes any number of values, giving back the first
+                    if id == "print" && self.is_builtin(id, env) {
+                        let types = args
+                            .iter()
+                            .map(|arg| self.infer(arg, env))
+                            .collect::<Vec<_>>();
+                        return Type::io(types[0].clone());
+                    }
+                }
+                let func = self.infer(name, env);
+                args.iter()
+                    .fold(func, |func, arg| self.apply(&func, arg, env))
+            }
+            Expr::Array(items, ..) => {
+                let item = self.fresh();
+                let first = items.first().map(|expr| Origin::ArrayItem(expr.span()));
+                for (idx, expr) in items.iter().enumerate() {
+                    let origin = if idx == 0 { None } else { first };
+                    self.check_with(expr, &item, origin, env);
+                }
+                Type::array(item)
+            }
+            Expr::Tuple(items, ..) => {
+                Type::Tuple(items.iter().map(|item| self.infer(item, env)).collect())
+            }
+            Expr::Record(name, fields, span) => {
+                self.infer_record(name, fields, *span, env)
+            }
+            Expr::Field(expr, field, span) => {
+                let ty = self.infer(expr, env);
+                match &self.methods(&ty, field)[..] {
+                    [method] => return self.bind_self(method, &ty, *span),
+                    [_, _, ..] => {
+                        let error = TypeError::AmbiguousField(field.clone(), *span);
+                        self.errors.push(error);
+                        return self.fresh();
+                    }
+                    [] => {}
+                }
+                let Some((names, types)) = self.record_type(&ty, field, *span) else {
+                    return self.fresh();
+                };
+                match names.iter().position(|name| name == field) {
+                    Some(idx) => types[idx].clone(),
+                    None => {
+                        self.unknown_field(field, &ty, *span);
+                        self.fresh()
+                    }
+                }
+            }
+            Expr::Update(expr, fields, span) => {
+                let ty = self.infer(expr, env);
+                let Some((field, _)) = fields.first() else {
+                    return ty;
+                };
+                let Some((names, types)) = self.record_type(&ty, field, *span) else {
+                    return ty;
+                };
+                for (field, value) in fields.iter() {
+                    match names.iter().position(|name| name == field) {
+                        Some(idx) => self.check(value, &types[idx], env),
+                        None => self.unknown_field(field, &ty, value.span()),
+                    }
+                }
+                ty
+            }
+            Expr::Enum(..)
+            | Expr::Data(..)
+            | Expr::Alias(..)
+            | Expr::Func(..)
+            | Expr::Error(..) => self.fresh(),
+        }
+    }
+
+    /// `Point { x = 1, y = 2 }` has to give every field of `Point` once.
+    fn infer_record(
+        &mut self,
+        name: &str,
+        fields: &[(String, Expr)],
+        span: Span,
+        env: &Types,
+    ) -> Type {
+        let Some(record) = self.records.get(name) else {
+            self.errors.push(TypeError::Undefined(name.into(), span));
+            return self.fresh();
+        };
+        let names = record.fields.clone();
+        let (ty, types) = self.instantiate_record(name);
+        for (field, value) in fields.iter() {
+            match names.iter().position(|name| name == field) {
+                Some(idx) => self.check(value, &types[idx], env),
+                None => self.unknown_field(field, &ty, value.span()),
+            }
+        }
+        for field in names.iter() {
+            if !fields.iter().any(|(name, _)| name == field) {
+                let error = TypeError::MissingField(name.into(), field.clone(), span);
+                self.errors.push(error);
+            }
+        }
+        ty
+    }
+
+    /// The record `ty` stands for, along with its fields. When the type is
+    /// not known yet the record is found from the field being used.
+    fn record_type(
+        &mut self,
+        ty: &Type,
+        field: &str,
+        span: Span,
+    ) -> Option<(Vec<String>, Vec<Type>)> {
+        let name = match self.resolve(ty) {
+            Type::Con(name, _) if self.records.contains_key(&name) => name,
+            Type::Var(_) => {
+                let mut candidates = self
+                    .records
+                    .iter()
+                    .filter(|(_, record)| record.fields.iter().any(|f| f == field))
+                    .map(|(name, _)| name.clone());
+                match (candidates.next(), candidates.next()) {
+                    (Some(name), None) => name,
+                    (Some(_), Some(_)) => {
+                        let error = TypeError::AmbiguousField(field.into(), span);
+                        self.errors.push(error);
+                        return None;
+                    }
+                    _ => {
+                        self.unknown_field(field, ty, span);
+                        return None;
+                    }
+                }
+            }
+            _ => {
+                self.unknown_field(field, ty, span);
+                return None;
+            }
+        };
+        let (record, types) = self.instantiate_record(&name);
+        self.unify(&record, ty, span);
+        Some((self.records[&name].fields.clone(), types))
+    }
+
+    fn instantiate_record(&mut self, name: &str) -> (Type, Vec<Type>) {
+        let scheme = self.records[name].scheme.clone();
+        let Type::Func(record, fields) = self.instantiate(&scheme) else {
+            unreachable!("record '{name}'");
+        };
+        let Type::Tuple(fields) = *fields else {
+            unreachable!("record '{name}'");
+        };
+        (*record, fields)
+    }
+
+    fn unknown_field(&mut self, field: &str, ty: &Type, span: Span) {
+        let [ty] = &Type::rename(&[&self.zonk(ty)])[..] else {
+            unreachable!();
+        };
+        let error = TypeError::UnknownField(field.into(), ty.clone(), span);
+        self.errors.push(error);
+    }
+
+    /// The bindings of a `let` are checked together, like a group of top
+    /// level definitions, and generalized over the variables `env` does not
+    /// know about.
+    fn let_bindings(&mut self, bindings: &[Expr], env: &Types) -> Types {
+        let mut inner = env.clone();
+        let mut signatures = vec![];
+        for binding in bindings.iter() {
+            let Expr::Func(name, types, _, _, span) = binding else {
+                unreachable!("let binding '{binding}'");
+            };
+            let ty = self.fresh();
+            let vars = match types {
+                None => HashMap::new(),
+                Some(types) => {
+                    let (sig, vars) = self.signature(types, *span);
+                    self.unify(&sig, &ty, *span);
+                    vars
+                }
+            };
+            inner.insert(name.clone(), Scheme::mono(ty.clone()));
+            signatures.push((ty, vars));
+        }
+        for (binding, (ty, _)) in bindings.iter().zip(signatures.iter()) {
+            let Expr::Func(_, _, body, ..) = binding else {
+                unreachable!("let binding '{binding}'");
+            };
+            self.check(body, ty, &inner);
+        }
+        let mut env = env.clone();
+        for (binding, (ty, vars)) in bindings.iter().zip(signatures.iter()) {
+            let Expr::Func(name, _, _, _, span) = binding else {
+                unreachable!("let binding '{binding}'");
+            };
+            if !self.is_rigid(vars) {
+                let [ty] = &Type::rename(&[&self.zonk(ty)])[..] else {
+                    unreachable!();
+                };
+                let error =
+                    TypeError::SignatureTooGeneral(name.clone(), ty.clone(), *span);
+                self.errors.push(error);
+            }
+            let scheme = self.generalize_in(ty, &env);
+            env.insert(name.clone(), scheme);
+        }
+        env
+    }
+
+    fn infer_binary(&mut self, binary: &Binary, env: &Types) -> Type {
+        let Binary {
+            op,
+            left,
+            right,
+            span,
+            ..
+        } = binary;
+        let operand = match op {
+            Op::And | Op::Or => {
+                self.check(left, &Type::Bool, env);
+                self.check(right, &Type::Bool, env);
+                return Type::Bool;
+            }
+            Op::Eq | Op::Neq => None,
+            Op::Grt | Op::Les | Op::GrtEq | Op::LesEq => Some(Operand::Ordered),
+            Op::Plus => Some(Operand::Addable),
+            Op::Minus | Op::Mult | Op::Div | Op::Mod => Some(Operand::Numeric),
+            // pipes are turned into applications by the parser
+            Op::Not | Op::Equals | Op::LRPipe | Op::RLPipe => return self.fresh(),
+        };
+        let ty = self.infer(left, env);
+        self.check_with(right, &ty, Some(Origin::Operand(left.span())), env);
+        if let Some(operand) = operand {
+            self.operands.push((operand, *op, ty.clone(), *span));
+        }
+        match op {
+            Op::Plus | Op::Minus | Op::Mult | Op::Div | Op::Mod => ty,
+            _ => Type::Bool,
+        }
+    }
+
+    fn apply(&mut self, func: &Type, arg: &Expr, env: &Types) -> Type {
+        if let Type::Func(param, ret) = self.resolve(func) {
+            self.check(arg, &param, env);
+            return *ret;
+        }
+        let param = self.infer(arg, env);
+        let ret = self.fresh();
+        self.unify(&Type::func(param, ret.clone()), func, arg.span());
+        ret
+    }
+
+    fn check_pattern(&mut self, pattern: &Pattern, expected: &Type, env: &mut Types) {
+        match pattern {
+            Pattern::Wildcard(..) => {}
+            Pattern::Id(name, ..) => {
+                env.insert(name.clone(), Scheme::mono(expected.clone()));
+            }
+            Pattern::Literal(atom) => self.unify(expected, &atom_type(atom), atom.span()),
+            Pattern::Constructor(name, args, span) => {
+                let Some(scheme) = self.globals.get(name).cloned() else {
+                    self.errors.push(TypeError::Undefined(name.clone(), *span));
+                    return;
+                };
+                let mut ty = self.instantiate(&scheme);
+                let mut fields = vec![];
+                while let Type::Func(param, ret) = self.resolve(&ty) {
+                    fields.push(*param);
+                    ty = *ret;
+                }
+                if fields.len() != args.len() {
+                    let error = TypeError::ConstructorArguments(
+                        name.clone(),
+                        fields.len(),
+                        args.len(),
+                        *span,
+                    );
+                    self.errors.push(error);
+                    fields = args.iter().map(|_| self.fresh()).collect();
+                }
+                for (arg, field) in args.iter().zip(fields.iter()) {
+                    self.check_pattern(arg, field, env);
+                }
+                self.unify(expected, &ty, *span);
+            }
+            Pattern::Tuple(items, span) => {
+                let types = items.iter().map(|_| self.fresh()).collect::<Vec<_>>();
+                self.unify(expected, &Type::Tuple(types.clone()), *span);
+                for (pattern, ty) in items.iter().zip(types.iter()) {
+                    self.check_pattern(pattern, ty, env);
+                }
+            }
+            Pattern::Array(items, rest, span) => {
+                let item = self.fresh();
+                let array = Type::array(item.clone());
+                self.unify(expected, &array, *span);
+                for pattern in items.iter() {
+                    self.check_pattern(pattern, &item, env);
+                }
+                if let Some(rest) = rest {
+                    self.check_pattern(rest, &array, env);
+                }
+            }
+        }
+    }
+
+    fn is_builtin(&self, name: &str, env: &Types) -> bool {
+        !env.contains_key(name)
+            && !self.globals.contains_key(name)
+            && BUILTINS.contains(&name)
+    }
+
+    fn lookup(&mut self, name: &str, span: Span, env: &Types) -> Type {
+        let primitive = is_primitive(name).then(|| self.builtins.get(name));
+        let scheme = primitive
+            .flatten()
+            .or_else(|| env.get(name))
+            .or_else(|| self.globals.get(name))
+            .or_else(|| self.builtins.get(name))
+            .cloned();
+        match scheme {
+            Some(scheme) => self.instantiate_at(&scheme, span),
+            None => {
+                self.errors.push(TypeError::Undefined(name.into(), span));
+                self.fresh()
+            }
+        }
+    }
+
+    fn fresh(&mut self) -> Type {
+        self.bindings.push(None);
+        Type::Var(self.bindings.len() - 1)
+    }
+
+    /// Follows the variable bindings and aliases until reaching a type that
+    /// is neither.
+    fn resolve(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        loop {
+            match self.follow(&ty) {
+                Type::Alias(.., aliased) => ty = *aliased,
+                followed => return followed,
+            }
+        }
+    }
+
+    /// Follows the variable bindings until reaching a type that is not a
+    /// bound variable.
+    fn follow(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        while let Type::Var(var) = ty {
+            match &self.bindings[var] {
+                Some(bound) => ty = bound.clone(),
+                None => break,
+            }
+        }
+        ty
+    }
+
+    /// Resolves every variable inside of `ty`, keeping the aliases.
+    fn zonk(&self, ty: &Type) -> Type {
+        match self.follow(ty) {
+            Type::Array(item) => Type::array(self.zonk(&item)),
+            Type::IO(ty) => Type::io(self.zonk(&ty)),
+            Type::Func(param, ret) => Type::func(self.zonk(&param), self.zonk(&ret)),
+            Type::Con(name, args) => {
+                Type::Con(name, args.iter().map(|arg| self.zonk(arg)).collect())
+            }
+            Type::Tuple(items) => {
+                Type::Tuple(items.iter().map(|item| self.zonk(item)).collect())
+            }
+            Type::Alias(name, args, ty) => Type::Alias(
+                name,
+                args.iter().map(|arg| self.zonk(arg)).collect(),
+                Box::new(self.zonk(&ty)),
+            ),
+            ty => ty,
+        }
+    }
+
+    fn generalize(&self, ty: &Type) -> Scheme {
+        let ty = self.zonk(ty);
+        let mut vars = vec![];
+        ty.vars(&mut vars);
+        Scheme::poly(vars, ty)
+    }
+
+    /// Like `generalize` but keeps the variables that are still in use by
+    /// `env`, the definitions being checked and the operators waiting to be
+    /// checked.
+    fn generalize_in(&self, ty: &Type, env: &Types) -> Scheme {
+        let ty = self.zonk(ty);
+        let mut fixed = vec![];
+        let schemes = env.values().chain(self.globals.values());
+        for scheme in schemes.filter(|scheme| scheme.vars.is_empty()) {
+            self.zonk(&scheme.ty).vars(&mut fixed);
+        }
+        for (.., operand, _) in self.operands.iter() {
+            self.zonk(operand).vars(&mut fixed);
+        }
+        let mut vars = vec![];
+        ty.vars(&mut vars);
+        vars.retain(|var| !fixed.contains(var));
+        Scheme::poly(vars, ty)
+    }
+
+    fn instantiate(&mut self, scheme: &Scheme) -> Type {
+        let map = self.fresh_vars(scheme);
+        scheme.ty.substitute(&map)
+    }
+
+    /// Instantiates a definition along with the operators it still has to
+    /// check, reported at `span` where it is used.
+    fn instantiate_at(&mut self, scheme: &Scheme, span: Span) -> Type {
+        let map = self.fresh_vars(scheme);
+        for &(var, operand, op) in scheme.operands.iter() {
+            self.operands.push((operand, op, map[&var].clone(), span));
+        }
+        scheme.ty.substitute(&map)
+    }
+
+    fn fresh_vars(&mut self, scheme: &Scheme) -> HashMap<usize, Type> {
+        scheme.vars.iter().map(|var| (*var, self.fresh())).collect()
+    }
+
+    fn unify(&mut self, expected: &Type, found: &Type, span: Span) {
+        self.unify_with(expected, found, span, None);
+    }
+
+    fn unify_with(
+        &mut self,
+        expected: &Type,
+        found: &Type,
+        span: Span,
+        origin: Option<Origin>,
+    ) {
+        let error = match self.unify_inner(expected, found) {
+            Ok(()) => return,
+            Err(Failure::Mismatch) => {
+                let types = Type::rename(&[&self.zonk(expected), &self.zonk(found)]);
+                let [expected, found] = &types[..] else {
+                    unreachable!();
+                };
+                TypeError::Mismatch {
+                    expected: expected.clone(),
+                    found: found.clone(),
+                    span,
+                    origin,
+                }
+            }
+            Err(Failure::Occurs(var, ty)) => {
+                let types = Type::rename(&[&Type::Var(var), &self.zonk(&ty)]);
+                let [var, ty] = &types[..] else {
+                    unreachable!();
+                };
+                TypeError::InfiniteType(var.clone(), ty.clone(), span)
+            }
+        };
+        self.errors.push(error);
+    }
+
+    fn unify_inner(&mut self, lhs: &Type, rhs: &Type) -> Result<(), Failure> {
+        match (self.follow(lhs), self.follow(rhs)) {
+            (Type::Var(lhs), Type::Var(rhs)) if lhs == rhs => Ok(()),
+            (Type::Var(var), ty) | (ty, Type::Var(var)) => {
+                let ty = self.zonk(&ty);
+                if ty.occurs(var) {
+                    return Err(Failure::Occurs(var, ty));
+                }
+                self.bindings[var] = Some(ty);
+                Ok(())
+            }
+            (Type::Alias(.., lhs), rhs) => self.unify_inner(&lhs, &rhs),
+            (lhs, Type::Alias(.., rhs)) => self.unify_inner(&lhs, &rhs),
+            (Type::Array(lhs), Type::Array(rhs)) | (Type::IO(lhs), Type::IO(rhs)) => {
+                self.unify_inner(&lhs, &rhs)
+            }
+            (Type::Func(lp, lr), Type::Func(rp, rr)) => {
+                self.unify_inner(&lp, &rp)?;
+                self.unify_inner(&lr, &rr)
+            }
+            (Type::Con(lhs, largs), Type::Con(rhs, rargs))
+                if lhs == rhs && largs.len() == rargs.len() =>
+            {
+                largs
+                    .iter()
+                    .zip(rargs.iter())
+                    .try_for_each(|(lhs, rhs)| self.unify_inner(lhs, rhs))
+            }
+            (Type::Tuple(lhs), Type::Tuple(rhs)) if lhs.len() == rhs.len() => lhs
+                .iter()
+                .zip(rhs.iter())
+                .try_for_each(|(lhs, rhs)| self.unify_inner(lhs, rhs)),
+            (lhs, rhs) if lhs == rhs => Ok(()),
+            _ => Err(Failure::Mismatch),
+        }
+    }
+}
+
+fn atom_type(atom: &Atom) -> Type {
+    match atom {
+        Atom::Int(..) => Type::Int,
+        Atom::Float(..) => Type::Float,
+        Atom::Bool(..) => Type::Bool,
+        Atom::String(..) => Type::String,
+        Atom::Char(..) => Type::Char,
+        Atom::Id(..) => unreachable!("identifiers are looked up"),
+    }
+}
+
+/// Splits a constructor field like `Array<(List a)>` into its parts.
+fn field_tokens(field: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut chars = field.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' | '<' | '>' | ',' => tokens.push(c.to_string()),
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                tokens.push("->".into());
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                tokens.push(name);
+            }
+        }
+    }
+    tokens
+}
+
+/// Collects the names used in `expr` that are not bound inside of it.
+fn references(expr: &Expr, bound: &mut Vec<String>, out: &mut Vec<String>) {
+    match expr {
+        Expr::Atom(Atom::Id(name, ..)) if !bound.contains(name) => out.push(name.clone()),
+        Expr::Atom(..)
+        | Expr::Enum(..)
+        | Expr::Data(..)
+        | Expr::Alias(..)
+        | Expr::Error(..) => {}
+        Expr::Unary(unary) => references(&unary.expr, bound, out),
+        Expr::Binary(binary) => {
+            references(&binary.left, bound, out);
+            references(&binary.right, bound, out);
+        }
+        Expr::IfElse(condition, then, r#else, ..) => {
+            references(condition, bound, out);
+            references(then, bound, out);
+            references(r#else, bound, out);
+        }
+        Expr::Match(expr, arms, ..) => {
+            references(expr, bound, out);
+            for (pattern, arm) in arms.iter() {
+                let len = bound.len();
+                pattern_names(pattern, bound);
+                references(arm, bound, out);
+                bound.truncate(len);
+            }
+        }
+        Expr::Clauses(args, clauses, ..) => {
+            args.iter().for_each(|arg| references(arg, bound, out));
+            for clause in clauses.iter() {
+                let len = bound.len();
+                clause
+                    .patterns
+                    .iter()
+                    .for_each(|pattern| pattern_names(pattern, bound));
+                for binding in clause.bindings.iter() {
+                    if let Expr::Func(name, ..) = binding {
+                        bound.push(name.clone());
+                    }
+                }
+                clause
+                    .bindings
+                    .iter()
+                    .for_each(|b| references(b, bound, out));
+                for (guard, body) in clause.bodies.iter() {
+                    guard.iter().for_each(|guard| references(guard, bound, out));
+                    references(body, bound, out);
+                }
+                bound.truncate(len);
+            }
+        }
+        Expr::Closure(head, body, ..) => {
+            let len = bound.len();
+            if let Expr::Atom(Atom::Id(name, ..)) = head.as_ref() {
+                bound.push(name.clone());
+            }
+            references(body, bound, out);
+            bound.truncate(len);
+        }
+        Expr::App(app) => {
+            references(&app.name, bound, out);
+            app.args.iter().for_each(|arg| references(arg, bound, out));
+        }
+        Expr::Array(items, ..) | Expr::Tuple(items, ..) => {
+            items.iter().for_each(|item| references(item, bound, out))
+        }
+        Expr::Record(_, fields, _) => fields
+            .iter()
+            .for_each(|(_, value)| references(value, bound, out)),
+        // `opt.map` could be the `map` of any type
+        Expr::Field(expr, field, _) => {
+            out.push(format!(".{field}"));
+            references(expr, bound, out);
+        }
+        Expr::Update(expr, fields, _) => {
+            references(expr, bound, out);
+            fields
+                .iter()
+                .for_each(|(_, value)| references(value, bound, out));
+        }
+        Expr::Let(bindings, body, ..) => {
+            let len = bound.len();
+            for binding in bindings.iter() {
+                if let Expr::Func(name, ..) = binding {
+                    bound.push(name.clone());
+                }
+            }
+            bindings.iter().for_each(|b| references(b, bound, out));
+            references(body, bound, out);
+            bound.truncate(len);
+        }
+        Expr::Func(_, _, body, ..) => references(body, bound, out),
+    }
+}
+
+/// Whether a name collected by [`references`] refers to the definition
+/// `name`, `.map` refers to every method called `map`.
+fn refers_to(reference: &str, name: &str) -> bool {
+    match reference.strip_prefix('.') {
+        Some(field) => method_name(name).is_some_and(|(_, method)| method == field),
+        None => reference == name,
+    }
+}
+
+/// The types named in `ty`, `Option` and `Grid` in `Array<Option Grid>`.
+fn type_names(ty: &TypeInfo, out: &mut Vec<String>) {
+    match ty {
+        TypeInfo::App(name, args) => {
+            out.push(name.clone());
+            args.iter().for_each(|arg| type_names(arg, out));
+        }
+        TypeInfo::Array(item) => type_names(item, out),
+        TypeInfo::Tuple(items) => items.iter().for_each(|item| type_names(item, out)),
+        TypeInfo::Fn(param, ret) => {
+            type_names(param, out);
+            type_names(ret, out);
+        }
+        TypeInfo::Int
+        | TypeInfo::Float
+        | TypeInfo::Bool
+        | TypeInfo::String
+        | TypeInfo::Char
+        | TypeInfo::Var(_) => {}
+    }
+}
+
+fn pattern_names(pattern: &Pattern, out: &mut Vec<String>) {
+    match pattern {
+        Pattern::Id(name, ..) => out.push(name.clone()),
+        Pattern::Wildcard(..) | Pattern::Literal(..) => {}
+        Pattern::Constructor(_, args, ..) | Pattern::Tuple(args, ..) => {
+            args.iter().for_each(|arg| pattern_names(arg, out));
+        }
+        Pattern::Array(items, rest, ..) => {
+            items.iter().for_each(|item| pattern_names(item, out));
+            if let Some(rest) = rest {
+                pattern_names(rest, out);
+            }
+        }
+    }
+}
+
+/// Tarjan's algorithm, the groups come out with every group after the
+/// groups it depends on.
+fn components(deps: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct State<'a> {
+        deps: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        next: usize,
+        groups: Vec<Vec<usize>>,
+    }
+
+    fn visit(state: &mut State, node: usize) {
+        state.index[node] = Some(state.next);
+        state.low[node] = state.next;
+        state.next += 1;
+        state.stack.push(node);
+        state.on_stack[node] = true;
+        for &dep in state.deps[node].iter() {
+            match state.index[dep] {
+                None => {
+                    visit(state, dep);
+                    state.low[node] = state.low[node].min(state.low[dep]);
+                }
+                Some(index) if state.on_stack[dep] => {
+                    state.low[node] = state.low[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+        if Some(state.low[node]) != state.index[node] {
+            return;
+        }
+        let mut group = vec![];
+        while let Some(member) = state.stack.pop() {
+            state.on_stack[member] = false;
+            group.push(member);
+            if member == node {
+                break;
+            }
+        }
+        group.reverse();
+        state.groups.push(group);
+    }
+
+    let mut state = State {
+        deps,
+        index: vec![None; deps.len()],
+        low: vec![0; deps.len()],
+        stack: vec![],
+        on_stack: vec![false; deps.len()],
+        next: 0,
+        groups: vec![],
+    };
+    for node in 0..deps.len() {
+        if state.index[node].is_none() {
+            visit(&mut state, node);
+        }
+    }
+    state.groups
+}
@@ -1,349 +1,464 @@
-use snowc_parse::{Atom, Expr, Op, Span};
-
-type Types = std::collections::HashMap<String, Item>;
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Type {
-    Int,
-    Float,
-    Bool,
-    String,
-    Char,
-    IO,
-    Custom(String),
-}
+mod error;
+mod infer;
+mod types;
+pub use error::{Origin, TypeError};
+use snowc_parse::Expr;
+use std::collections::HashMap;
+pub use types::{Scheme, Type};
 
-impl TryFrom<(&String, &Types)> for Type {
-    type Error = String;
-    fn try_from((t, types): (&String, &Types)) -> Result<Self, Self::Error> {
-        match t.as_str() {
-            "Int" => Ok(Self::Int),
-            "Float" => Ok(Self::Float),
-            "Bool" => Ok(Self::Bool),
-            "String" => Ok(Self::String),
-            "Char" => Ok(Self::Char),
-            "IO" => Ok(Self::IO),
-            _ => match types.get(t.as_str()) {
-                Some(item) => Ok(item.ret_type()),
-                None => Err(format!("unknown type '{t}'")),
-            },
-        }
-    }
-}
+/// The inferred type of every top level definition and constructor.
+pub type Types = HashMap<String, Scheme>;
 
-enum Item {
-    Func(TypedFunc),
-    Enum(TypedEnum),
+/// Infers the types of the program, checking them against any signatures
+/// that were given.
+pub fn type_check(ast: &[Expr]) -> Result<Types, Vec<TypeError>> {
+    infer::Infer::default().program(ast)
 }
 
-impl Item {
-    fn ret_type(&self) -> Type {
-        match self {
-            Self::Func(typed_func) => typed_func.return_type.clone(),
-            Self::Enum(typed_enum) => typed_enum.ret_type(),
-        }
-    }
-    fn lookup(&self, name: impl Into<String>) -> Option<Type> {
-        match self {
-            Self::Func(typed_func) => typed_func.lookup(name),
-            Self::Enum(typed_enum) => typed_enum.lookup(name),
-        }
+#[test]
+fn test_type_check_samples() {
+    use snowc_parse::parse;
+    for src in [
+        include_str!("./../../../samples/hello_world.snow"),
+        include_str!("./../../../samples/other.snow"),
+        include_str!("./../../../samples/rule110.snow"),
+        include_str!("./../../../samples/std.snow"),
+    ] {
+        let ast = parse(src).unwrap();
+        assert_eq!(type_check(&ast).err(), None);
     }
 }
 
-#[derive(Debug, Clone, Hash)]
-struct Variant {
-    name: String,
-    memebers: Vec<Type>,
-}
+#[test]
+fn test_inferred_types() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
 
-#[derive(Debug, Clone, Hash)]
-struct TypedEnum {
-    return_type: Type,
-    variants: Vec<Variant>,
-}
+enum List a = Cons a (List a) | Nil
 
-impl TypedEnum {
-    fn ret_type(&self) -> Type {
-        self.return_type.clone()
-    }
+map f arr
+  = if length arr == 0
+    then []
+    else [f (head arr)] + map f (tail arr)
 
-    fn lookup(&self, name: impl Into<String>) -> Option<Type> {
-        let name = name.into();
-        if self.variants.iter().any(|v| v.name == name) {
-            return Some(Type::Custom(name));
-        }
-        None
-    }
-}
+compose f g x = f (g x)
 
-#[derive(Debug, Clone, Hash)]
-struct TypedFunc {
-    return_type: Type,
-    args: Vec<(Option<String>, Type)>,
-}
+unwrap d o : a -> Option -> a = match o on Some x -> x | None -> d
 
-impl TypedFunc {
-    // fn new(return_type: Type) -> Self {
-    //     Self::new_with_args(return_type, vec![])
-    // }
-    //
-    fn new_with_args(return_type: Type, args: Vec<(Option<String>, Type)>) -> Self {
-        Self { return_type, args }
-    }
+isEven n = if n == 0 then true else isOdd (n - 1)
 
-    fn push_arg(&mut self, name: impl Into<String>) {
-        for (param, _type) in self.args.iter_mut() {
-            if param.is_none() {
-                *param = Some(name.into());
-                break;
-            }
-        }
-    }
+isOdd n = if n == 0 then false else isEven (n - 1)
 
-    fn lookup(&self, name: impl Into<String>) -> Option<Type> {
-        let name = name.into();
-        self.args
-            .iter()
-            .find(|(arg, _)| arg.as_ref().map(|i| i == &name).unwrap_or(false))
-            .map(|(_, t)| t.clone())
-    }
+main = print (map isEven [1, 2]) (map (compose unwrap Some) [1])
+"#;
+    let ast = parse(src).unwrap();
+    let types = type_check(&ast).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("Some"), "a -> Option a");
+    assert_eq!(type_of("Cons"), "a -> List a -> List a");
+    assert_eq!(type_of("map"), "(a -> b) -> Array<a> -> Array<b>");
+    assert_eq!(type_of("compose"), "(a -> b) -> (c -> a) -> c -> b");
+    assert_eq!(type_of("unwrap"), "a -> Option a -> a");
+    assert_eq!(type_of("isOdd"), "Int -> Bool");
+    assert_eq!(type_of("main"), "IO Array<Bool>");
 }
 
-fn lookup(func_name: &str, env: &Types, id: &str) -> Type {
-    match env.get(id) {
-        Some(type_func) => type_func.ret_type(),
-        None => match env.get(func_name).and_then(|i| i.lookup(id)) {
-            Some(t) => t,
-            None => panic!("unbound error '{id}' has never been created"),
-        },
-    }
-}
+#[test]
+fn test_let_types() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+twice f x = go 2 x where go n x = if n == 0 then x else go (n - 1) (f x)
 
-fn type_check_binary<'a>(
-    func_name: &str,
-    env: &Types,
-    op: &'a Op,
-    lhs: &'a Expr,
-    rhs: &'a Expr,
-) -> Type {
-    let t1 = type_of(func_name, env, lhs);
-    let t2 = type_of(func_name, env, rhs);
-    if t1 != t2 {
-        panic!("type miss matched '{op:?}' lhs: '{t1:?}', rhs: '{t2:?}'");
-    }
-    match op {
-        Op::Plus | Op::Minus | Op::Mult | Op::Div | Op::Mod => t1,
-        Op::Grt
-        | Op::Les
-        | Op::GrtEq
-        | Op::LesEq
-        | Op::Eq
-        | Op::Neq
-        | Op::Not
-        | Op::And
-        | Op::Or => Type::Bool,
-        Op::Equals | Op::LRPipe | Op::RLPipe => {
-            panic!("not yet implemented for assignment or pipe")
-        }
-    }
+pick b = let id x = x in if id b then id 1 else id 2
+
+main = print (twice (\x -> x + 1) 1) (pick true)
+"#;
+    let ast = parse(src).unwrap();
+    let types = type_check(&ast).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("twice"), "(a -> a) -> a -> a");
+    assert_eq!(type_of("pick"), "Bool -> Int");
 }
 
-fn type_check_if_else<'a>(
-    func_name: &str,
-    env: &Types,
-    c: &'a Expr,
-    b1: &'a Expr,
-    b2: &'a Expr,
-) -> Type {
-    let tc = type_of(func_name, env, c);
-    let Type::Bool = tc else {
-        // c.span(),
-        panic!("if condition found '{tc:?}' but expected 'Bool' Type");
-    };
-    let t1 = type_of(func_name, env, b1);
-    let t2 = type_of(func_name, env, b2);
-    if t1 != t2 {
-        // b1.span().start..b2.span().end,
-        panic!("branch types do not match, expected '{t1:?}' but found '{t2:?}'");
-    }
-    t1
+#[test]
+fn test_signatures() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
+
+enum Either a b = Left a | Right b
+
+map f arr
+  : (a -> b) -> Array<a> -> Array<b>
+  = if length arr == 0
+    then []
+    else [f (head arr)] + map f (tail arr)
+
+orElse d o : a -> Option a -> a = match o on Some x -> x | None -> d
+
+lefts xs : Array<Either a (Option b)> -> Array<a> = []
+
+main = print (map (orElse 0) [Some 1, None])
+"#;
+    let ast = parse(src).unwrap();
+    let types = type_check(&ast).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("map"), "(a -> b) -> Array<a> -> Array<b>");
+    assert_eq!(type_of("orElse"), "a -> Option a -> a");
+    assert_eq!(type_of("lefts"), "Array<Either a (Option b)> -> Array<a>");
+
+    let errors = type_check(
+        &parse(
+            "enum Option a = Some a
+f : Option Int Int = f",
+        )
+        .unwrap(),
+    );
+    assert!(matches!(
+        &errors.unwrap_err()[..],
+        [TypeError::TypeArguments(_, 1, 2, _)]
+    ));
 }
 
-fn type_check_app(
-    func_name: &str,
-    env: &Types,
-    name: &Expr,
-    args: &[Expr],
-    _span: &Span,
-) -> Type {
-    let t = type_of(func_name, env, name);
-    let Expr::Atom(Atom::Id(name, _)) = name else {
-        return t;
+#[test]
+fn test_aliases() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+type Grid = Array<Array<Int>>
+
+type Pair a = (a, a)
+
+enum Option a = Some a | None
+
+type Maybe a = Option a
+
+first g : Grid -> Array<Int> = head g
+
+swap (a, b) : Pair a -> Pair a = (b, a)
+
+orZero o : Maybe Int -> Int = match o on Some x -> x | None -> 0
+
+main = print (first [[1, 2], [3]]) (swap (1, 2)) (orZero (Some 3))
+"#;
+    let ast = parse(src).unwrap();
+    let types = type_check(&ast).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("first"), "Grid -> Array<Int>");
+    assert_eq!(type_of("swap"), "Pair a -> Pair a");
+    assert_eq!(type_of("orZero"), "Maybe Int -> Int");
+
+    let check = |src: &str| type_check(&parse(src).unwrap()).unwrap_err();
+    let errors = check("type Grid = Array<Array<Int>>\nbad g : Grid -> Int = g");
+    let [TypeError::Mismatch { found, .. }] = &errors[..] else {
+        panic!("{errors:?}");
     };
-    let Some(Item::Func(tfunc)) = env.get(name) else {
-        // span.clone(),
-        panic!("undefined '{name}'");
+    assert_eq!(found.to_string(), "Grid");
+    let errors = check("type A = Array<B>\ntype B = (Int, A)\nf x : A -> A = x");
+    let [TypeError::CyclicAlias(name, cycle, _)] = &errors[..] else {
+        panic!("{errors:?}");
     };
-    if args.len() > tfunc.args.len() {
-        // span.clone(),
-        panic!("to many args given to '{name}'");
-    }
-    for (idx, arg) in args.iter().enumerate() {
-        let t = type_of(func_name, env, arg);
-        let (arg_name, pt) = &tfunc.args[idx];
-        if pt != &t {
-            // arg.span(),
-            panic!(
-                "expected '{pt:?}' for {} but found '{t:?}'",
-                arg_name.clone().unwrap_or_else(|| "<name>".to_string())
-            );
-        }
-    }
-    t
+    assert_eq!((name.as_str(), cycle.as_str()), ("A", "'A' -> 'B' -> 'A'"));
 }
 
-fn type_of(func_name: &str, env: &Types, e: &Expr) -> Type {
-    match e {
-        Expr::Atom(Atom::Int(..), ..) => Type::Int,
-        Expr::Atom(Atom::Float(..), ..) => Type::Float,
-        Expr::Atom(Atom::Bool(..), ..) => Type::Bool,
-        Expr::Atom(Atom::String(..), ..) => Type::String,
-        Expr::Atom(Atom::Char(..), ..) => Type::Char,
-        Expr::Atom(Atom::Id(id, ..)) => lookup(func_name, env, id),
-        Expr::Unary(unary) => type_of(func_name, env, &unary.expr),
-        Expr::Binary(op, lhs, rhs, ..) => type_check_binary(func_name, env, op, lhs, rhs),
-        Expr::IfElse(c, b1, b2, ..) => type_check_if_else(func_name, env, c, b1, b2),
-        // enum_var @ Expr::EnumVar(..) => {
-        //     let mut names = vec![];
-        //     get_names(enum_var, &mut names);
-        //     let name = &names[0];
-        //     let variant = &names[1];
-        //     let Some(typed_enum) = env.get(name) else {
-        //         panic!("use for before defining '{}'", name)
-        //     };
-        //     let Some(t) = typed_enum.lookup(variant) else {
-        //         panic!("'{}' does not have a variant named '{}'", name, variant);
-        //     };
-        //     t
-        // }
-        Expr::App(name, args, span) => type_check_app(func_name, env, name, args, span),
-        // e.span(),
-        _ => panic!("not implemented yet for expr: '{e:?}'"),
-    }
+#[test]
+fn test_clause_types() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
+
+fromOption d None = d
+fromOption _ (Some x) = x
+
+sign n
+  | n > 0 = 1
+  | true = 0
+
+main = print (fromOption 1 None) (sign 2)
+"#;
+    let ast = parse(src).unwrap();
+    let types = type_check(&ast).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("fromOption"), "a -> Option a -> a");
+    assert_eq!(type_of("sign"), "Int -> Int");
 }
 
-fn pair_up_params<'a>(
-    _func_name: String,
-    type_func: &mut TypedFunc,
-    expr: &'a Expr,
-) -> &'a Expr {
-    if !expr.is_clouser() {
-        return expr;
-    }
-    let Expr::Closure(head, tail, _) = expr else {
-        // expr.span(),
-        panic!("unimplemented yet for '{expr}'");
-    };
-    let Expr::Atom(Atom::Id(name, _)) = &**head else {
-        // head.span(),
-        panic!("unimplemented yet for '{expr}'");
-    };
-    type_func.push_arg(name);
-    pair_up_params(_func_name, type_func, tail)
+#[test]
+fn test_record_types() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+data Point = { x: Int, y: Int }
+
+data Pair a b = { first: a, second: b }
+
+norm p = p.x * p.x + p.y * p.y
+
+swap (a, b) : (a, b) -> (b, a) = (b, a)
+
+setFirst v p = p { first = v }
+
+main = print (norm (Point { x = 1, y = 2 })) (swap (1, "a"))
+"#;
+    let ast = parse(src).unwrap();
+    let types = type_check(&ast).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("norm"), "Point -> Int");
+    assert_eq!(type_of("swap"), "(a, b) -> (b, a)");
+    assert_eq!(type_of("setFirst"), "a -> Pair a b -> Pair a b");
+
+    let check = |src: &str| type_check(&parse(src).unwrap()).unwrap_err();
+    let point = "data Point = { x: Int, y: Int }\n";
+    assert!(matches!(
+        &check(&format!("{point}p = Point {{ x = 1 }}"))[..],
+        [TypeError::MissingField(_, field, _)] if field == "y"
+    ));
+    assert!(matches!(
+        &check(&format!("{point}z p = p.z"))[..],
+        [TypeError::UnknownField(field, Type::Var(_), _)] if field == "z"
+    ));
+    assert!(matches!(
+        &check(&format!("{point}data Size = {{ x: Int }}\nx p = p.x"))[..],
+        [TypeError::AmbiguousField(..)]
+    ));
+    assert!(matches!(
+        &check(&format!(
+            "{point}p = (Point {{ x = 1, y = 2 }}) {{ x = true }}"
+        ))[..],
+        [TypeError::Mismatch {
+            expected: Type::Int,
+            found: Type::Bool,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_overloaded_operators() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+cat a b = a + b
+
+`++` xs ys = xs + ys
+
+minus a b = a - b
+
+hidden x = let double y = y + y in x
+
+main = print (cat "a" "b") ([1] ++ [2]) (minus 1.5 0.5) (hidden true)
+"#;
+    let types = type_check(&parse(src).unwrap()).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("cat"), "a -> a -> a");
+    assert_eq!(type_of("(++)"), "a -> a -> a");
+    assert_eq!(type_of("minus"), "a -> a -> a");
+    assert_eq!(type_of("hidden"), "a -> a");
+    let src = "minus a b = a - b\n\nmain = print (minus \"a\" \"b\")";
+    assert!(matches!(
+        &type_check(&parse(src).unwrap()).unwrap_err()[..],
+        [TypeError::InvalidOperand(snowc_parse::Op::Minus, Type::String, span)]
+            if span.idx_start == src.rfind("minus").unwrap()
+    ));
 }
 
-fn default_types() -> Types {
-    let mut env = Types::new();
-    let func = Item::Func(TypedFunc::new_with_args(
-        Type::IO,
-        vec![(Some("x".into()), Type::String)],
+#[test]
+fn test_type_errors() {
+    use snowc_parse::parse;
+    let check = |src: &str| type_check(&parse(src).unwrap()).unwrap_err();
+    assert!(matches!(
+        &check("bad x : Int -> Bool = x + 1")[..],
+        [TypeError::Mismatch {
+            expected: Type::Bool,
+            found: Type::Int,
+            origin: None,
+            ..
+        }]
     ));
-    env.insert("print_str".into(), func);
-    let func = Item::Func(TypedFunc::new_with_args(
-        Type::IO,
-        vec![(Some("x".into()), Type::Int)],
+    assert!(matches!(
+        &check("branches x = if x then 1 else \"one\"")[..],
+        [TypeError::Mismatch {
+            expected: Type::Int,
+            found: Type::String,
+            origin: Some(Origin::IfBranch(..)),
+            ..
+        }]
     ));
-    env.insert("print_int".into(), func);
-    let func = Item::Func(TypedFunc::new_with_args(
-        Type::IO,
-        vec![(Some("x".into()), Type::Float)],
+    assert!(matches!(
+        &check("same x : a -> a = x + 1")[..],
+        [TypeError::SignatureTooGeneral(..)]
     ));
-    env.insert("print_float".into(), func);
-    let func = Item::Func(TypedFunc::new_with_args(
-        Type::IO,
-        vec![(Some("x".into()), Type::Char)],
+    assert!(matches!(
+        &check("main = print (foo 1)")[..],
+        [TypeError::Undefined(name, _)] if name == "foo"
     ));
-    env.insert("print_char".into(), func);
-    let func = Item::Func(TypedFunc::new_with_args(
-        Type::IO,
-        vec![(Some("x".into()), Type::Bool)],
+    assert!(matches!(
+        &check("omega f = f f")[..],
+        [TypeError::InfiniteType(..)]
+    ));
+    assert!(matches!(
+        &check("strs = \"a\" - \"b\"")[..],
+        [TypeError::InvalidOperand(
+            snowc_parse::Op::Minus,
+            Type::String,
+            _
+        )]
+    ));
+    assert!(matches!(
+        &check(
+            "enum Option a = Some a | None\nf o = match o on Some a b -> a | None -> 0"
+        )[..],
+        [TypeError::ConstructorArguments(_, 1, 2, _)]
+    ));
+    assert!(matches!(
+        &check("clauses 0 = 1\nclauses _ = \"one\"")[..],
+        [TypeError::Mismatch {
+            expected: Type::Int,
+            found: Type::String,
+            origin: Some(Origin::Clause(..)),
+            ..
+        }]
+    ));
+    assert!(matches!(
+        &check("guard x\n  | x = 1\n  | 2 = 2")[..],
+        [TypeError::Mismatch {
+            expected: Type::Bool,
+            found: Type::Int,
+            ..
+        }]
     ));
-    env.insert("print_bool".into(), func);
-    env
 }
 
-pub fn type_check(ast: &[Expr]) -> Result<(), Vec<String>> {
-    let mut env = default_types();
-    let errors = Vec::new();
-    for def in ast.iter() {
-        match def {
-            Expr::Func(name, _type_info, body, _) => {
-                let Some(Item::Func(type_func)) = env.get_mut(name) else {
-                    // span.clone(),
-                    panic!("function '{name}' missing type declaration");
-                };
-                let body = pair_up_params(name.into(), type_func, body);
-                let dec_return_type = type_func.return_type.clone();
-                let return_type = type_of(name, &env, body);
-                if return_type != dec_return_type {
-                    // FIXME: add return error
-
-                    // body.span(),
-                    panic!(
-                        "miss matched return types: found '{return_type:?}' \
-                        but expected '{dec_return_type:?}'"
-                    );
-                }
-            }
-            // Expr::TypeDec(name, body, _) => {
-            //     let mut body = body.clone();
-            //     let Some(t) = body.pop() else {
-            //         // span.clone(),
-            //         panic!("missing return type from type declaration '{name}'");
-            //     };
-            //     let mut args = vec![];
-            //     for string_type in body.iter() {
-            //         args.push((None, Type::try_from((string_type, &env)).unwrap()));
-            //     }
-            //     let return_type = Type::try_from((&t, &env)).unwrap();
-            //     let typed_func = TypedFunc::new_with_args(return_type, args);
-            //     env.insert(name.into(), Item::Func(typed_func));
-            // }
-            Expr::Enum(name, var, _) => {
-                let mut variants = vec![];
-                for (name, memb) in var.iter() {
-                    variants.push(Variant {
-                        name: name.to_string(),
-                        memebers: memb
-                            .iter()
-                            .map(|i| {
-                                Type::try_from((i, &env)).expect("failed to get type")
-                            })
-                            .collect(),
-                    })
-                }
-                let typed_enum = TypedEnum {
-                    return_type: Type::Custom(name.to_string()),
-                    variants,
-                };
-                env.insert(name.into(), Item::Enum(typed_enum));
-            }
-            _ => unimplemented!("for '{def}'"),
-        }
-    }
-    if !errors.is_empty() {
-        return Err(errors);
-    }
-    Ok(())
+#[test]
+fn test_numeric_types() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = "half n = toFloat n / 2.0
+
+round f = toInt (f + 0.5)
+
+hex = 0xff_ff";
+    let types = type_check(&parse(src).unwrap()).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("half"), "Int -> Float");
+    assert_eq!(type_of("round"), "Float -> Int");
+    assert_eq!(type_of("hex"), "Int");
+    let errors = type_check(&parse("mixed = 1 + 1.5").unwrap()).unwrap_err();
+    assert!(matches!(
+        &errors[..],
+        [TypeError::Mismatch {
+            expected: Type::Int,
+            found: Type::Float,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn test_method_types() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = r#"
+enum Option a = Some a | None
+
+enum List a = Cons a (List a) | Nil
+
+Option.map f = match self on Some x -> Some (f x) | None -> None
+
+List.map f = match self on Cons x xs -> Cons (f x) (xs.map f) | Nil -> Nil
+
+Option.isSome = true
+
+inc o : Option Int -> Option Int = o.map (\x -> x + 1)
+"#;
+    let types = type_check(&parse(src).unwrap()).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("Option.map"), "(a -> b) -> Option a -> Option b");
+    assert_eq!(type_of("List.map"), "(a -> b) -> List a -> List b");
+    assert_eq!(type_of("Option.isSome"), "Option a -> Bool");
+    assert_eq!(type_of("inc"), "Option Int -> Option Int");
+    let errors = type_check(&parse("Foo.bar = 1").unwrap()).unwrap_err();
+    assert!(matches!(&errors[..], [TypeError::UnknownType(name, _)] if name == "Foo"));
+    let src = format!("{src}\nlengths xs = xs.map length");
+    let errors = type_check(&parse(&src).unwrap()).unwrap_err();
+    assert!(
+        matches!(&errors[..], [TypeError::AmbiguousField(field, _)] if field == "map")
+    );
+}
+
+#[test]
+fn test_comprehension_types() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = "evens n = [x | x <- [0..n], x mod 2 == 0]
+
+labels xs = [(x, toFloat y) | x <- xs, y <- [1, 3 .. 9]]";
+    let types = type_check(&parse(src).unwrap()).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("evens"), "Int -> Array<Int>");
+    assert_eq!(type_of("labels"), "Array<a> -> Array<(a, Float)>");
+    let src = "bad xs = [x | x <- xs, x + 1]";
+    assert!(type_check(&parse(src).unwrap()).is_err());
+    let src = "__map f xs = 0\n\nmain = [x * 2 | x <- [1..3]]";
+    let types = type_check(&parse(src).unwrap()).unwrap();
+    assert_eq!(types["main"].to_string(), "Array<Int>");
+}
+
+#[test]
+fn test_io_types() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse;
+    let src = "greet name = do
+  print \"hello\"
+  let loud = name + \"!\"
+  print loud
+
+twice action = do
+  x <- action
+  y <- action
+  pure (x + y)
+
+main : IO = twice (pure 1)";
+    let types = type_check(&parse(src).unwrap()).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("greet"), "String -> IO String");
+    assert_eq!(type_of("twice"), "IO a -> IO a");
+    assert_eq!(type_of("main"), "IO Int");
+    // printing has to show up in the type
+    let src = "double x : Int -> Int = do\n  print x\n  pure (x * 2)";
+    let errors = type_check(&parse(src).unwrap()).unwrap_err();
+    assert!(matches!(&errors[..], [TypeError::Mismatch { .. }]));
+    let src = "main : IO Int String = print 1";
+    let errors = type_check(&parse(src).unwrap()).unwrap_err();
+    assert!(matches!(&errors[..], [TypeError::TypeArguments(..)]));
+}
+
+#[test]
+fn test_report_points_at_both_branches() {
+    use snowc_parse::parse;
+    let src = "pick x = if x then 1 else \"one\"";
+    let errors = type_check(&parse(src).unwrap()).unwrap_err();
+    let report = errors[0].report("pick.snow", src);
+    assert!(report.contains("mismatched types"));
+    assert!(report.contains("expected 'Int' but found 'String'"));
+    assert!(report.contains("expected because of this branch"));
+}
+
+#[test]
+fn test_partial_program() {
+    use pretty_assertions::assert_eq;
+    use snowc_parse::parse_with_recovery;
+    let src = "double x = x * 2\n\nbroken x = x +\n\ntype = 1\n\nmain = print (double 3)";
+    let (ast, errors) = parse_with_recovery(src);
+    assert_eq!(errors.len(), 2);
+    let types = type_check(&ast).unwrap();
+    let type_of = |name: &str| types[name].to_string();
+    assert_eq!(type_of("double"), "Int -> Int");
+    assert_eq!(type_of("broken"), "a -> b");
+    assert_eq!(type_of("main"), "IO Int");
 }
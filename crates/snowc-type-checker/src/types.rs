@@ -0,0 +1,187 @@
+use crate::infer::Operand;
+use snowc_parse::Op;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Int,
+    Float,
+    Bool,
+    String,
+    Char,
+    /// An action giving back a value of the inner type once it is run,
+    /// `print : a -> IO a`.
+    IO(Box<Self>),
+    Array(Box<Self>),
+    Func(Box<Self>, Box<Self>),
+    Tuple(Vec<Self>),
+    /// A user defined enum or record along with the types given to its parameters.
+    Con(String, Vec<Self>),
+    /// A type written through an alias along with the type it stands for,
+    /// kept so messages can show the alias.
+    Alias(String, Vec<Self>, Box<Self>),
+    Var(usize),
+}
+
+impl Type {
+    pub fn func(param: Self, ret: Self) -> Self {
+        Self::Func(Box::new(param), Box::new(ret))
+    }
+
+    pub fn array(item: Self) -> Self {
+        Self::Array(Box::new(item))
+    }
+
+    pub fn io(ty: Self) -> Self {
+        Self::IO(Box::new(ty))
+    }
+
+    pub(crate) fn occurs(&self, var: usize) -> bool {
+        match self {
+            Self::Var(v) => *v == var,
+            Self::Array(item) | Self::IO(item) => item.occurs(var),
+            Self::Func(param, ret) => param.occurs(var) || ret.occurs(var),
+            Self::Con(_, args) | Self::Tuple(args) => {
+                args.iter().any(|arg| arg.occurs(var))
+            }
+            // a parameter the alias does not use can not make the type infinite
+            Self::Alias(.., ty) => ty.occurs(var),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn vars(&self, out: &mut Vec<usize>) {
+        match self {
+            Self::Var(v) if !out.contains(v) => out.push(*v),
+            Self::Array(item) | Self::IO(item) => item.vars(out),
+            Self::Func(param, ret) => {
+                param.vars(out);
+                ret.vars(out);
+            }
+            Self::Con(_, args) | Self::Tuple(args) => {
+                args.iter().for_each(|arg| arg.vars(out))
+            }
+            Self::Alias(_, args, ty) => {
+                args.iter().for_each(|arg| arg.vars(out));
+                ty.vars(out);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn substitute(&self, map: &HashMap<usize, Self>) -> Self {
+        match self {
+            Self::Var(v) => map.get(v).cloned().unwrap_or(Self::Var(*v)),
+            Self::Array(item) => Self::array(item.substitute(map)),
+            Self::IO(ty) => Self::io(ty.substitute(map)),
+            Self::Func(param, ret) => {
+                Self::func(param.substitute(map), ret.substitute(map))
+            }
+            Self::Con(name, args) => Self::Con(
+                name.clone(),
+                args.iter().map(|arg| arg.substitute(map)).collect(),
+            ),
+            Self::Tuple(items) => {
+                Self::Tuple(items.iter().map(|item| item.substitute(map)).collect())
+            }
+            Self::Alias(name, args, ty) => Self::Alias(
+                name.clone(),
+                args.iter().map(|arg| arg.substitute(map)).collect(),
+                Box::new(ty.substitute(map)),
+            ),
+            t => t.clone(),
+        }
+    }
+
+    /// Renames the type variables in `types` to `a`, `b`, `c` and so on, in
+    /// the order they show up, so they read well in an error message.
+    pub(crate) fn rename(types: &[&Self]) -> Vec<Self> {
+        let mut vars = Vec::new();
+        types.iter().for_each(|t| t.vars(&mut vars));
+        let map = vars
+            .into_iter()
+            .enumerate()
+            .map(|(idx, var)| (var, Self::Var(idx)))
+            .collect();
+        types.iter().map(|t| t.substitute(&map)).collect()
+    }
+
+    fn is_simple(&self) -> bool {
+        !matches!(self, Self::Func(..) | Self::IO(..))
+            && !matches!(self, Self::Con(_, args) | Self::Alias(_, args, _) if !args.is_empty())
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int => write!(f, "Int"),
+            Self::Float => write!(f, "Float"),
+            Self::Bool => write!(f, "Bool"),
+            Self::String => write!(f, "String"),
+            Self::Char => write!(f, "Char"),
+            Self::IO(ty) if ty.is_simple() => write!(f, "IO {ty}"),
+            Self::IO(ty) => write!(f, "IO ({ty})"),
+            Self::Array(item) => write!(f, "Array<{item}>"),
+            Self::Func(param, ret) if matches!(**param, Self::Func(..)) => {
+                write!(f, "({param}) -> {ret}")
+            }
+            Self::Func(param, ret) => write!(f, "{param} -> {ret}"),
+            Self::Tuple(items) => {
+                let items = items.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "({})", items.join(", "))
+            }
+            Self::Con(name, args) | Self::Alias(name, args, _) => {
+                write!(f, "{name}")?;
+                for arg in args.iter() {
+                    match arg.is_simple() {
+                        true => write!(f, " {arg}")?,
+                        false => write!(f, " ({arg})")?,
+                    }
+                }
+                Ok(())
+            }
+            Self::Var(var) => {
+                let letter = (b'a' + (var % 26) as u8) as char;
+                match var / 26 {
+                    0 => write!(f, "{letter}"),
+                    n => write!(f, "{letter}{n}"),
+                }
+            }
+        }
+    }
+}
+
+/// A type which is polymorphic over `vars`, `map : (a -> b) -> Array<a> -> Array<b>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scheme {
+    pub vars: Vec<usize>,
+    pub ty: Type,
+    /// The operators still waiting on one of `vars`, `cat a b = a + b` is
+    /// `a -> a -> a` for any `a` that can be added.
+    pub(crate) operands: Vec<(usize, Operand, Op)>,
+}
+
+impl Scheme {
+    pub fn mono(ty: Type) -> Self {
+        Self::poly(vec![], ty)
+    }
+
+    pub fn poly(vars: Vec<usize>, ty: Type) -> Self {
+        Self {
+            vars,
+            ty,
+            operands: vec![],
+        }
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [ty] = &Type::rename(&[&self.ty])[..] else {
+            unreachable!();
+        };
+        write!(f, "{ty}")
+    }
+}
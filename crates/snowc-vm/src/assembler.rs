@@ -27,7 +27,7 @@ impl Header {
         self.0[Self::ENTRY_OFFSET + 3] = d;
     }
 
-    fn into_bytes(mut self) -> [u8; Header::SIZE] {
+    fn into_bytes(self) -> [u8; Header::SIZE] {
         self.0
     }
 }
@@ -124,7 +124,7 @@ pub fn assemble_from_ast(ast: &Vec<Item>) -> Result<Vec<u8>, Error> {
     let mut bytes = vec![];
     let mut header = Header::default();
 
-    let symbol_table = create_symbol_table(&ast);
+    let symbol_table = create_symbol_table(ast);
 
     for item in ast {
         match item {
@@ -36,11 +36,13 @@ pub fn debug_opcode(chunk: &[u8]) -> String {
         OpCode::Leq => format!("leq %{b} {c}"),
         OpCode::Inc => format!("inc %{b}"),
         OpCode::Dec => format!("dec %{b}"),
-        OpCode::Ret => format!("ret"),
-        OpCode::Hlt => format!("hlt"),
+        OpCode::Ret => "ret".to_string(),
+        OpCode::Hlt => "hlt".to_string(),
         OpCode::Prts => format!("prts {addr}"),
         OpCode::Prti => format!("prti {b}"),
-        OpCode::Nop => format!("nop"),
+        OpCode::Prtn => format!("prtn %{b}"),
+        OpCode::Prtsr => format!("prtsr %{b}"),
+        OpCode::Nop => "nop".to_string(),
         OpCode::Ige => format!("ige {a} {c} {c} {d}"),
     }
 }
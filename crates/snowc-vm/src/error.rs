@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RuntimeError {
+    #[error("division by zero")]
+    DivisionByZero,
+    #[error("Int overflow")]
+    IntOverflow,
+}
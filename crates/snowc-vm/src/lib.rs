@@ -1,10 +1,12 @@
 mod assembler;
 mod debug;
+mod error;
 mod machine;
 mod opcode;
 mod parse;
 
 pub use assembler::{assemble_from_ast, assembler, SymbolTable};
 pub use debug::{debug_opcode, debug_program, hex_dump};
+pub use error::RuntimeError;
 pub use machine::Machine;
 pub use parse::*;
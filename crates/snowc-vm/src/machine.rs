@@ -1,4 +1,4 @@
-use super::{debug_opcode, debug_program, opcode::OpCode};
+use super::{debug_program, opcode::OpCode, RuntimeError};
 pub struct Machine {
     program: Vec<u8>,
     registers: [u32; 32],
@@ -111,46 +111,60 @@ impl Machine {
         self.get_next_u8();
     }
 
-    fn add(&mut self) {
-        let lhs = self.registers[self.get_next_u8() as usize];
-        let rhs = self.registers[self.get_next_u8() as usize];
+    fn add(&mut self) -> Result<(), RuntimeError> {
+        let lhs = self.registers[self.get_next_u8() as usize] as i32;
+        let rhs = self.registers[self.get_next_u8() as usize] as i32;
         let des = self.get_next_u8() as usize;
-        self.registers[des] = lhs + rhs;
+        let value = lhs.checked_add(rhs).ok_or(RuntimeError::IntOverflow)?;
+        self.registers[des] = value as u32;
+        Ok(())
     }
 
-    fn sub(&mut self) {
-        let lhs = self.registers[self.get_next_u8() as usize];
-        let rhs = self.registers[self.get_next_u8() as usize];
+    fn sub(&mut self) -> Result<(), RuntimeError> {
+        let lhs = self.registers[self.get_next_u8() as usize] as i32;
+        let rhs = self.registers[self.get_next_u8() as usize] as i32;
         let des = self.get_next_u8() as usize;
-        self.registers[des] = lhs - rhs;
+        let value = lhs.checked_sub(rhs).ok_or(RuntimeError::IntOverflow)?;
+        self.registers[des] = value as u32;
+        Ok(())
     }
 
-    fn div(&mut self) {
+    fn div(&mut self) -> Result<(), RuntimeError> {
         let lhs = self.registers[self.get_next_u8() as usize];
         let rhs = self.registers[self.get_next_u8() as usize];
         let des = self.get_next_u8() as usize;
-        self.registers[des] = lhs / rhs;
+        if rhs == 0 {
+            return Err(RuntimeError::DivisionByZero);
+        }
+        self.registers[des] = (lhs as i32).wrapping_div(rhs as i32) as u32;
+        Ok(())
     }
 
-    fn r#mod(&mut self) {
+    fn r#mod(&mut self) -> Result<(), RuntimeError> {
         let lhs = self.registers[self.get_next_u8() as usize];
         let rhs = self.registers[self.get_next_u8() as usize];
         let des = self.get_next_u8() as usize;
-        self.registers[des] = lhs % rhs;
+        if rhs == 0 {
+            return Err(RuntimeError::DivisionByZero);
+        }
+        self.registers[des] = (lhs as i32).wrapping_rem(rhs as i32) as u32;
+        Ok(())
     }
 
-    fn mult(&mut self) {
-        let lhs = self.registers[self.get_next_u8() as usize];
-        let rhs = self.registers[self.get_next_u8() as usize];
+    fn mult(&mut self) -> Result<(), RuntimeError> {
+        let lhs = self.registers[self.get_next_u8() as usize] as i32;
+        let rhs = self.registers[self.get_next_u8() as usize] as i32;
         let des = self.get_next_u8() as usize;
-        self.registers[des] = lhs * rhs;
+        let value = lhs.checked_mul(rhs).ok_or(RuntimeError::IntOverflow)?;
+        self.registers[des] = value as u32;
+        Ok(())
     }
 
     fn jmp(&mut self) {
         let v0 = self.get_next_u8() as u32;
         let v1 = self.get_next_u8() as u32;
         let v2 = self.get_next_u8() as u32;
-        let value = ((v0 << 8) | (v1 << 4) | v2) as usize;
+        let value = ((v0 << 16) | (v1 << 8) | v2) as usize;
         self.pc = value;
     }
 
@@ -190,28 +204,28 @@ impl Machine {
         let lhs = self.registers[self.get_next_u8() as usize];
         let rhs = self.registers[self.get_next_u8() as usize];
         self.get_next_u8();
-        self.compare = lhs > rhs;
+        self.compare = (lhs as i32) > (rhs as i32);
     }
 
     fn geq(&mut self) {
         let lhs = self.registers[self.get_next_u8() as usize];
         let rhs = self.registers[self.get_next_u8() as usize];
         self.get_next_u8();
-        self.compare = lhs >= rhs;
+        self.compare = (lhs as i32) >= (rhs as i32);
     }
 
     fn lt(&mut self) {
         let lhs = self.registers[self.get_next_u8() as usize];
         let rhs = self.registers[self.get_next_u8() as usize];
         self.get_next_u8();
-        self.compare = lhs < rhs;
+        self.compare = (lhs as i32) < (rhs as i32);
     }
 
     fn leq(&mut self) {
         let lhs = self.registers[self.get_next_u8() as usize];
         let rhs = self.registers[self.get_next_u8() as usize];
         self.get_next_u8();
-        self.compare = lhs <= rhs;
+        self.compare = (lhs as i32) <= (rhs as i32);
     }
 
     fn inc(&mut self) {
@@ -229,11 +243,23 @@ impl Machine {
     }
 
     fn prts(&mut self) {
-        use std::io::Write;
         let v0 = self.get_next_u8() as u32;
         let v1 = self.get_next_u8() as u32;
         let v2 = self.get_next_u8() as u32;
-        let ptr = ((v0 << 8) | (v1 << 4) | v2) as usize;
+        let ptr = ((v0 << 16) | (v1 << 8) | v2) as usize;
+        self.print_string_at(ptr);
+    }
+
+    fn prtsr(&mut self) {
+        let src = self.get_next_u8() as usize;
+        let ptr = self.registers[src] as usize;
+        self.print_string_at(ptr);
+        self.get_next_u8();
+        self.get_next_u8();
+    }
+
+    fn print_string_at(&self, ptr: usize) {
+        use std::io::Write;
         let byte_string = self.program[ptr..]
             .iter()
             .take_while(|i| **i != 0)
@@ -250,17 +276,27 @@ impl Machine {
 
     fn prti(&mut self) {
         let src = self.get_next_u8() as usize;
-        let value = self.registers[src];
+        let value = self.registers[src] as i32;
         println!("{value}");
         self.get_next_u8();
         self.get_next_u8();
     }
 
+    fn prtn(&mut self) {
+        use std::io::Write;
+        let src = self.get_next_u8() as usize;
+        let value = self.registers[src] as i32;
+        print!("{value}");
+        std::io::stdout().flush().expect("failed to flush");
+        self.get_next_u8();
+        self.get_next_u8();
+    }
+
     fn call(&mut self) {
         let v0 = self.get_next_u8() as u32;
         let v1 = self.get_next_u8() as u32;
         let v2 = self.get_next_u8() as u32;
-        let des = ((v0 << 8) | (v1 << 4) | v2) as usize;
+        let des = ((v0 << 16) | (v1 << 8) | v2) as usize;
         self.stack.push(self.pc as u32);
         self.stack.push(self.bp as u32);
         self.bp = self.sp;
@@ -303,11 +339,11 @@ impl Machine {
         opcode
     }
 
-    pub fn run_once(&mut self) {
+    pub fn run_once(&mut self) -> Result<(), RuntimeError> {
         let Self { program, .. } = self;
         if program.is_empty() || !self.running {
             eprintln!("nothing to run");
-            return;
+            return Ok(());
         }
         match self.get_opcode() {
             OpCode::Load => self.load(),
@@ -316,11 +352,11 @@ impl Machine {
             OpCode::Pop => self.pop(),
             OpCode::Aloc => self.aloc(),
             OpCode::Setm => self.setm(),
-            OpCode::Add => self.add(),
-            OpCode::Sub => self.sub(),
-            OpCode::Div => self.div(),
-            OpCode::Mod => self.r#mod(),
-            OpCode::Mul => self.mult(),
+            OpCode::Add => self.add()?,
+            OpCode::Sub => self.sub()?,
+            OpCode::Div => self.div()?,
+            OpCode::Mod => self.r#mod()?,
+            OpCode::Mul => self.mult()?,
             OpCode::Call => self.call(),
             OpCode::Jmp => self.jmp(),
             OpCode::Jeq => self.jeq(),
@@ -335,15 +371,21 @@ impl Machine {
             OpCode::Dec => self.dec(),
             OpCode::Prts => self.prts(),
             OpCode::Prti => self.prti(),
+            OpCode::Prtn => self.prtn(),
+            OpCode::Prtsr => self.prtsr(),
             OpCode::Ret => self.ret(),
             OpCode::Hlt => self.hlt(),
-            OpCode::Nop => {}
+            OpCode::Nop => self.pc += 3,
             OpCode::Ige => panic!("unknown opcode {}", self.program[self.pc - 1]),
         }
+        Ok(())
     }
 
-    pub fn run(&mut self) {
+    /// Runs the program until it halts, stopping early on the first
+    /// runtime error.
+    pub fn run(&mut self) -> Result<(), RuntimeError> {
         self.read_header();
+        let mut result = Ok(());
         // debug_program(&self.program);
         // std::process::exit(1);
         while self.running {
@@ -354,11 +396,15 @@ impl Machine {
             // eprintln!("pc: {}: {}", self.pc,  debug_opcode(&[a, b, c, d]));
             // eprintln!("{:?}", self.heap);
             // std::io::stdin().read_line(&mut "".into()).expect("");
-            self.run_once();
+            if let Err(error) = self.run_once() {
+                self.running = false;
+                result = Err(error);
+            }
         }
         if self.debug {
             self.debug();
         }
+        result
     }
 }
 
@@ -38,7 +38,9 @@ fn main() {
             .expect("filed to read bin file");
         remove_she_bang_bin(&mut program);
         let mut vm = Machine::new(program, settings.debug);
-        vm.run();
+        if let Err(error) = vm.run() {
+            eprintln!("error: {error}");
+        }
         return;
     }
     let mut src = std::fs::read_to_string(filename)
@@ -56,7 +58,9 @@ fn main() {
             // file.write_all(&program).expect("failed to write to file");
 
             let mut vm = Machine::new(program, settings.debug);
-            vm.run();
+            if let Err(error) = vm.run() {
+                eprintln!("error: {error}");
+            }
         }
         Err(error) => {
             snowc_error_messages::report(filename, &src, &error);
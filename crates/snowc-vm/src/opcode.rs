@@ -30,6 +30,8 @@ pub enum OpCode {
     Prti,
     Hlt,
     Nop,
+    Prtn,
+    Prtsr,
     Ige,
 }
 
@@ -61,6 +63,8 @@ impl From<&TokenOp> for OpCode {
             TokenOp::Call(..) => Self::Call,
             TokenOp::Prts(..) => Self::Prts,
             TokenOp::Prti(..) => Self::Prti,
+            TokenOp::Prtn(..) => Self::Prtn,
+            TokenOp::Prtsr(..) => Self::Prtsr,
             TokenOp::Ret => Self::Ret,
             TokenOp::Hlt => Self::Hlt,
             TokenOp::Nop => Self::Nop,
@@ -98,6 +102,8 @@ impl From<u8> for OpCode {
             25 => Self::Prti,
             26 => Self::Hlt,
             27 => Self::Nop,
+            28 => Self::Prtn,
+            29 => Self::Prtsr,
             _ => Self::Ige,
         }
     }
@@ -12,18 +12,7 @@ impl Directive {
     pub fn as_bytes(&self) -> Vec<u8> {
         match self {
             Self::Ascii(string) => {
-                let mut b = vec![];
-                let mut chars = string.chars().peekable();
-                // FIXME: Not a so happy about this.
-                while let Some(c) = chars.next() {
-                    match c {
-                        '\\' if chars.peek() == Some(&'n') => {
-                            chars.next();
-                            b.push(10);
-                        }
-                        _ => b.push(c as u8),
-                    }
-                }
+                let mut b = string.as_bytes().to_vec();
                 b.push(0);
                 b
             }
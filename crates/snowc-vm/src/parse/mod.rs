@@ -15,6 +15,7 @@ pub use location::Location;
 pub use reg::Reg;
 use snowc_error_messages::Error;
 use snowc_error_messages::ErrorCode as ErrCode;
+use snowc_lexer::unescape;
 pub use swrt_lexer::{LexerDebug, Scanner, Span, Token};
 pub use token_op::TokenOp;
 
@@ -55,7 +56,7 @@ impl<'a> Parser<'a> {
         let keywords = vec![
             "call", "ret", "load", "loadm", "push", "pop", "inc", "dec", "prti", "aloc",
             "setm", "eq", "neq", "gt", "geq", "lt", "leq", "add", "sub", "div", "mod",
-            "mul", "prts", "jmp", "jeq", "jne", "hlt", "nop",
+            "mul", "prts", "prtn", "prtsr", "jmp", "jeq", "jne", "hlt", "nop",
         ];
         let line_comment = (';', None);
         Self {
@@ -81,6 +82,8 @@ impl<'a> Parser<'a> {
             "inc" => self.parse_1reg(TokenOp::Inc, &name),
             "dec" => self.parse_1reg(TokenOp::Dec, &name),
             "prti" => self.parse_1reg(TokenOp::Prti, &name),
+            "prtn" => self.parse_1reg(TokenOp::Prtn, &name),
+            "prtsr" => self.parse_1reg(TokenOp::Prtsr, &name),
             "aloc" => self.parse_1reg(TokenOp::Aloc, &name),
             "loadm" => self.parse_2reg(TokenOp::LoadM, &name),
             "setm" => self.parse_2reg(TokenOp::Setm, &name),
@@ -137,7 +140,14 @@ impl<'a> Parser<'a> {
         match value {
             "ascii" if self.peek().is_string() => {
                 let token = self.next();
-                Ok(Directive::Ascii(token.value().to_string()))
+                match unescape(token.value()) {
+                    Ok(string) => Ok(Directive::Ascii(string)),
+                    Err(range) => {
+                        let escape = &token.value()[range];
+                        let label = format!("invalid escape '{escape}'");
+                        Err(error("E0013", &label, self.last_span))
+                    }
+                }
             }
             _ => {
                 let label = format!("unknown directive call '{token:?}'");
@@ -165,7 +175,7 @@ impl<'a> Parser<'a> {
 
     fn parse_label(&mut self) -> Result<Label, Error> {
         if !self.peek().is_id() {
-            let label = format!("missing label for data'");
+            let label = "missing label for data'".to_string();
             return Err(error("E0009", &label, self.last_span));
         }
 
@@ -195,7 +205,7 @@ impl<'a> Parser<'a> {
             let label = format!("regester missing number value found '{token:?}'");
             return Err(error("E0000", &label, self.last_span));
         }
-        let span = Span::new(line, start, end);
+        let _span = Span::new(line, start, end);
         Ok(Reg(token.value().parse().unwrap()))
     }
 
@@ -330,7 +340,45 @@ load %1 100
 hlt
 "#,
     )
-    .parse();
-    dbg!(&ast);
-    assert!(false);
+    .parse()
+    .expect("failed to parse program");
+    assert_eq!(ast.len(), 3);
+    let Item::Data(data) = &ast[1] else {
+        panic!("expected .data section but found {:?}", ast[1]);
+    };
+    assert_eq!(data[0].name, "name");
+    assert_eq!(data[0].directive, Directive::Ascii("Hello World".into()));
+    let Item::Text(text) = &ast[2] else {
+        panic!("expected .text section but found {:?}", ast[2]);
+    };
+    let opcodes = text.iter().map(|t| t.opcode.clone()).collect::<Vec<_>>();
+    assert_eq!(opcodes, vec![TokenOp::Load(1, 0, 100), TokenOp::Hlt]);
+}
+
+#[test]
+fn parse_ascii_escapes() {
+    let ast = Parser::new(
+        r#"
+.entry main
+.data
+name: .ascii "say \"hi\"\n\u{263A}"
+.text
+main:
+hlt
+"#,
+    )
+    .parse()
+    .expect("failed to parse program");
+    let Item::Data(data) = &ast[1] else {
+        panic!("expected .data section but found {:?}", ast[1]);
+    };
+    let directive = Directive::Ascii("say \"hi\"\n\u{263A}".into());
+    assert_eq!(data[0].directive, directive);
+    assert_eq!(directive.size(), "say \"hi\"\n\u{263A}".len() + 1);
+    let error =
+        Parser::new(".entry main\n.data\nname: .ascii \"\\q\"\n.text\nmain:\nhlt\n")
+            .parse()
+            .map(|_| ())
+            .map_err(|error| error.id);
+    assert_eq!(error, Err("E0013".to_string()));
 }
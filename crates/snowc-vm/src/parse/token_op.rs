@@ -15,6 +15,8 @@ pub enum TokenOp {
     Jne(Label),
     Prts(Label),
     Prti(u8),
+    Prtn(u8),
+    Prtsr(u8),
     LoadM(u8, u8),
     Setm(u8, u8),
     Eq(u8, u8),
@@ -62,6 +64,8 @@ impl TokenOp {
             | Self::Gt(a, b) => Ok([code, *a, *b, 0]),
             Self::Leq(a, b) | Self::Lt(a, b) => Ok([code, *a, *b, 0]),
             Self::Prti(a)
+            | Self::Prtn(a)
+            | Self::Prtsr(a)
             | Self::Inc(a)
             | Self::Push(a)
             | Self::Pop(a)
@@ -11,16 +11,13 @@ pub use crate::scanner::Scanner;
 pub use crate::token::Token;
 
 #[derive(Debug, Clone, Copy)]
+#[derive(Default)]
 pub enum LexerDebug {
     On,
+    #[default]
     Off,
 }
 
-impl Default for LexerDebug {
-    fn default() -> Self {
-        Self::Off
-    }
-}
 
 impl From<bool> for LexerDebug {
     fn from(toggle: bool) -> Self {
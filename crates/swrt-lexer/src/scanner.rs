@@ -50,14 +50,14 @@ impl<'a> Scanner<'a> {
     fn advance(&mut self) {
         match self.current {
             Some(c) => {
-                self.span.end += c.to_string().as_bytes().len();
+                self.span.end += c.to_string().len();
             }
             None => {
                 let Some(c) = self.previous else {
                     self.span.end += 1;
                     return;
                 };
-                self.span.end += c.to_string().as_bytes().len();
+                self.span.end += c.to_string().len();
             }
         }
     }
@@ -129,7 +129,7 @@ impl<'a> Scanner<'a> {
         }
         let span = self.span();
         self.lookup(&ident)
-            .map_or(Token::Id(ident, span), |i| Token::KeyWord(i.into(), span))
+            .map_or(Token::Id(ident, span), |i| Token::KeyWord(i, span))
     }
 
     fn line_comment(&mut self) -> Option<Token> {
@@ -141,6 +141,10 @@ impl<'a> Scanner<'a> {
         let mut string = String::new();
         while let Some(ch) = self.next_if(|c| c != &'"') {
             string.push(ch);
+            // The escape is kept for the parser, only so `\"` does not end it.
+            if ch == '\\' {
+                string.extend(self.next_char());
+            }
         }
         self.next_char();
         Some(Token::String(string, self.span()))
@@ -7,8 +7,25 @@ pub enum Target {
     VM
 }
 
+/// `snowc fmt [--check] files...`
+#[derive(Debug, Default)]
+pub struct Format {
+    pub check: bool,
+    pub files: Vec<String>,
+}
+
+/// `snowc doc [--out dir] [--test] file`
+#[derive(Debug, Default)]
+pub struct Doc {
+    pub out: String,
+    pub test: bool,
+    pub filename: String,
+}
+
 #[derive(Debug, Default)]
 pub struct Settings {
+    pub format: Option<Format>,
+    pub doc: Option<Doc>,
     pub filename: Option<String>,
     pub target: Option<Target>,
     pub debug_token: bool,
@@ -27,6 +44,39 @@ pub fn cargs() -> Settings {
         .author("Cowboy8625, Hexaredecimal (JS Backend)")
         .about(crate_description!())
         .arg(Arg::new("filename"))
+        .subcommand(
+            Command::new("fmt")
+                .about("Formats Snow source files in place")
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .required(false)
+                        .action(clap::ArgAction::SetTrue)
+                        .help("Lists the files that are not formatted instead of formatting them"),
+                )
+                .arg(Arg::new("files").required(true).num_args(1..)),
+        )
+        .subcommand(
+            Command::new("doc")
+                .about("Writes the docs of a program and its modules as Markdown and HTML")
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .short('o')
+                        .required(false)
+                        .default_value("doc")
+                        .action(clap::ArgAction::Set)
+                        .help("Directory the pages are written to"),
+                )
+                .arg(
+                    Arg::new("test")
+                        .long("test")
+                        .required(false)
+                        .action(clap::ArgAction::SetTrue)
+                        .help("Runs the code examples in the docs instead"),
+                )
+                .arg(Arg::new("file").required(true)),
+        )
         .arg(
             Arg::new("debug-token")
                 .long("debug-token")
@@ -89,6 +139,24 @@ pub fn cargs() -> Settings {
         .get_matches();
 
     let mut setting = Settings::default();
+    if let Some(("fmt", matches)) = matches.subcommand() {
+        setting.format = Some(Format {
+            check: *matches.get_one::<bool>("check").expect("check failed"),
+            files: matches
+                .get_many::<String>("files")
+                .map(|files| files.cloned().collect())
+                .unwrap_or_default(),
+        });
+        return setting;
+    }
+    if let Some(("doc", matches)) = matches.subcommand() {
+        setting.doc = Some(Doc {
+            out: matches.get_one::<String>("out").expect("out failed").clone(),
+            test: *matches.get_one::<bool>("test").expect("test failed"),
+            filename: matches.get_one::<String>("file").expect("file failed").clone(),
+        });
+        return setting;
+    }
     if let Some(filename) = matches.get_one::<String>("filename") {
         setting.filename = Some(filename.to_string());
     }
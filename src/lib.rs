@@ -3,4 +3,4 @@ pub use snowc_code_gen::*;
 pub use snowc_parse::*;
 pub use snowc_tree_walker::*;
 pub use snowc_vm::{debug_program, Machine};
-// pub use snowc_type_checker::*;
+pub use snowc_type_checker::*;
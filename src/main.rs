@@ -2,16 +2,20 @@ mod args;
 use args::Target;
 use clap::error::Result;
 use snowc::error::Error;
-use snowc::{debug_program, gen_code, parse, walk, Expr, Machine, Scanner};
-use snowc_repl::repl;
+use snowc::java::java_gen_code;
 use snowc::js::js_gen_code;
-use snowc::java::java_gen_code; 
+use snowc::{
+    debug_program, gen_code, type_check, walk, CodeGenError, Expr, Machine, Modules,
+    Scanner, Span, TypeError,
+};
+use snowc_repl::repl;
 
 #[derive(Debug)]
 enum CompilerError {
     NoFileGive,
     Parse(Vec<Error>),
-    Type(Vec<String>),
+    Type(Vec<TypeError>),
+    CodeGen(CodeGenError),
 }
 
 impl From<Vec<Error>> for CompilerError {
@@ -20,8 +24,8 @@ impl From<Vec<Error>> for CompilerError {
     }
 }
 
-impl From<Vec<String>> for CompilerError {
-    fn from(value: Vec<String>) -> Self {
+impl From<Vec<TypeError>> for CompilerError {
+    fn from(value: Vec<TypeError>) -> Self {
         Self::Type(value)
     }
 }
@@ -48,24 +52,36 @@ fn debug_ast(flag: bool) -> impl FnOnce(Vec<Expr>) -> Result<Vec<Expr>, Compiler
     }
 }
 
-fn handle_compiler_errors(filename: impl Into<String>) -> impl FnOnce(CompilerError) {
+/// Errors are reported against the module their span points into.
+fn source(modules: &Modules, span: Span) -> (&str, &str) {
+    let source = modules
+        .source(span)
+        .expect("failed to get file source for error report");
+    (&source.filename, &source.src)
+}
+
+fn handle_compiler_errors(modules: &Modules) -> impl FnOnce(CompilerError) + '_ {
     move |error_type| match error_type {
         CompilerError::Parse(ref errors) => {
-            let filename = filename.into();
-            let src = std::fs::read_to_string(&filename)
-                .expect("failed to get file source for error report");
             for error in errors.iter() {
-                let msg = error.report(&filename, &src);
+                let (filename, src) = source(modules, error.span());
+                let msg = error.report(filename, src);
                 eprintln!("{msg}");
             }
         }
-        CompilerError::Type(errors) => {
-            for error in errors {
-                eprintln!("{error}");
+        CompilerError::Type(ref errors) => {
+            for error in errors.iter() {
+                let (filename, src) = source(modules, error.span());
+                let msg = error.report(filename, src);
+                eprintln!("{msg}");
             }
         }
+        CompilerError::CodeGen(ref error) => {
+            let (filename, src) = source(modules, error.span().unwrap_or_default());
+            eprintln!("{}", error.report(filename, src));
+        }
         CompilerError::NoFileGive => {
-            println!("");
+            println!();
         }
     }
 }
@@ -81,11 +97,119 @@ fn get_src(flag: bool) -> impl FnOnce(String) -> Result<String, CompilerError> {
     }
 }
 
+/// Formats every file in place, with `--check` the files that are not
+/// formatted are listed instead. A file that does not parse is reported and
+/// left alone.
+fn format_files(format: &args::Format) {
+    let mut failed = false;
+    for filename in format.files.iter() {
+        let Ok(src) = std::fs::read_to_string(filename) else {
+            eprintln!("failed to read {filename}");
+            failed = true;
+            continue;
+        };
+        // imports are resolved by the compiler, the file only has to parse
+        let (_, errors) = snowc::parse_with_recovery(&src);
+        let errors = errors
+            .iter()
+            .filter(|error| !matches!(error, Error::ImportOutsideModule(..)))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            for error in errors {
+                eprintln!("{}", error.report(filename, &src));
+            }
+            failed = true;
+            continue;
+        }
+        let formatted = snowc::format(&src);
+        if formatted == src {
+            continue;
+        }
+        if format.check {
+            println!("{filename}");
+            failed = true;
+        } else if std::fs::write(filename, formatted).is_err() {
+            eprintln!("failed to write {filename}");
+            failed = true;
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Writes a Markdown and an HTML page for every module of the program, with
+/// `--test` the examples in the docs are run instead.
+fn document(doc: &args::Doc) {
+    let mut modules = Modules::default();
+    let ast = std::fs::read_to_string(&doc.filename)
+        .map_err(|_| CompilerError::NoFileGive)
+        .and_then(|src| modules.load(&doc.filename, &src).map_err(Into::into));
+    let ast = match ast {
+        Ok(ast) => ast,
+        Err(CompilerError::NoFileGive) => {
+            eprintln!("failed to read {}", doc.filename);
+            std::process::exit(1);
+        }
+        Err(error) => {
+            handle_compiler_errors(&modules)(error);
+            std::process::exit(1);
+        }
+    };
+    if doc.test {
+        let mut failed = 0;
+        let examples = snowc_doc::examples(&modules, &ast);
+        for example in examples.iter() {
+            // Examples print without a line break at the end.
+            println!("example {}::{}", example.module, example.name);
+            let result = example.run();
+            let status = if result.is_ok() { "ok" } else { "FAILED" };
+            println!("\n... {status}");
+            if let Err(reports) = result {
+                reports.iter().for_each(|report| eprintln!("{report}"));
+                failed += 1;
+            }
+        }
+        println!("{} examples, {failed} failed", examples.len());
+        if failed > 0 {
+            std::process::exit(1);
+        }
+        return;
+    }
+    let types = type_check(&ast).ok();
+    let pages = snowc_doc::pages(&modules, &ast, types.as_ref());
+    let out = std::path::Path::new(&doc.out);
+    if std::fs::create_dir_all(out).is_err() {
+        eprintln!("failed to create {}", doc.out);
+        std::process::exit(1);
+    }
+    for page in pages.iter() {
+        let markdown = out.join(format!("{}.md", page.name));
+        let html = out.join(format!("{}.html", page.name));
+        let written = std::fs::write(&markdown, snowc_doc::markdown(page, &pages))
+            .and_then(|_| std::fs::write(&html, snowc_doc::html(page, &pages)));
+        if written.is_err() {
+            eprintln!("failed to write the docs of {}", page.name);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
     let setting = args::cargs();
+    if let Some(format) = setting.format.as_ref() {
+        format_files(format);
+        return;
+    }
+    if let Some(doc) = setting.doc.as_ref() {
+        document(doc);
+        return;
+    }
     if setting.debug_graph {
         unimplemented!("graphviz is not working just yet");
     }
+    let mut modules = Modules::default();
+    let filename = setting.filename.clone().unwrap_or_default();
     setting
         .filename
         .clone()
@@ -95,106 +219,116 @@ fn main() {
         })
         .and_then(get_src(setting.option_compile_string))
         .and_then(debug_tokens(setting.debug_token))
-        .and_then(|src| timer("Parsing", || parse(&src)).map_err(Into::into))
+        .and_then(|src| {
+            timer("Parsing", || modules.load(&filename, &src)).map_err(Into::into)
+        })
         .and_then(debug_ast(setting.debug_ast))
-        // .and_then(|ast| {
-        //     if !setting.option_no_type_check {
-        //         timer("Type Checking", || type_check(&ast))
-        //             .map_err(Into::<CompilerError>::into)?;
-        //     }
-        //     Ok(ast)
-        // })
-        // .map_or_else(
-        //     handle_compiler_errors(setting.filename.clone().unwrap_or_default()),
-        //     |ast| {
-        //         let msg = format_compiler_message("Running");
-        //         let filename = setting.filename.unwrap_or_default();
-        //         eprintln!("{msg} {filename}");
-        //         let result = walk(&ast);
-        //         match result {
-        //             Ok(_) => {}
-        //             Err(errors) => {
-        //                 let src =
-        //                     get_src(setting.option_compile_string)(filename.clone())
-        //                         .expect("failed to get file source for error report");
-        //                 for err in errors.iter() {
-        //                     let msg = err.report(&filename, &src);
-        //                     eprintln!("{msg}");
-        //                 }
-        //             }
-        //         }
-        //     },
-        // );
-        .map_or_else(
-            handle_compiler_errors(setting.filename.clone().unwrap_or_default()),
-            |ast| {
-                let program = timer("Codegen", || -> Result<String, CompilerError> {
-                    if let Some(target) = setting.target {
-                        let program = match target {
-                            Target::JS => js_gen_code(&ast).unwrap(), 
-                            Target::Java => java_gen_code(&ast).unwrap(),
-                            Target::VM => gen_code(&ast)
-                        };
-                        Ok(program)
-                    } else {
-                        Ok(gen_code(&ast))
+        .and_then(|ast| {
+            if !setting.option_no_type_check {
+                timer("Type Checking", || type_check(&ast))
+                    .map_err(Into::<CompilerError>::into)?;
+            }
+            Ok(ast)
+        })
+        .map_or_else(handle_compiler_errors(&modules), |ast| {
+            match setting.target {
+                None => run_tree_walker(&ast, &setting, &modules),
+                Some(Target::VM) => match timer("Codegen", || gen_code(&ast)) {
+                    Ok(program) => {
+                        if setting.verbose {
+                            debug_program(&program);
+                        }
+                        if setting.run {
+                            running_message(&setting);
+                            if let Err(error) = Machine::new(program, false).run() {
+                                eprintln!("error: {error}");
+                            }
+                        }
+                    }
+                    Err(error) => {
+                        handle_compiler_errors(&modules)(CompilerError::CodeGen(error))
+                    }
+                },
+                Some(ref target) => {
+                    let program = timer("Codegen", || match target {
+                        Target::Java => java_gen_code(&ast),
+                        _ => js_gen_code(&ast),
+                    });
+                    let program = match program {
+                        Ok(program) => program,
+                        Err(error) => {
+                            return handle_compiler_errors(&modules)(
+                                CompilerError::CodeGen(error),
+                            )
+                        }
+                    };
+                    if setting.verbose {
+                        println!("{program}");
+                    }
+                    if setting.run {
+                        running_message(&setting);
+                        run_js(&program);
                     }
-                })
-                .unwrap();
-                if setting.verbose {
-                    println!("{program}");
                 }
+            }
+        });
+}
 
-                if setting.run {
-                    let msg = format_compiler_message("Running");
-                    let filename = setting.filename.unwrap_or_default();
-                    eprintln!("{msg} {filename}");
-                    use rquickjs::{
-                        CatchResultExt, Context, Function, Object, Result, Runtime, Value,
-                    };
+fn running_message(setting: &args::Settings) {
+    let msg = format_compiler_message("Running");
+    let filename = setting.filename.clone().unwrap_or_default();
+    eprintln!("{msg} {filename}");
+}
 
-                    let rt = Runtime::new().unwrap();
-                    let ctx = Context::full(&rt).unwrap();
-
-                    let _ = ctx.with(|ctx| -> Result<()> {
-                        let global = ctx.globals();
-                        global.set(
-                            "__print",
-                            Function::new(ctx.clone(), print)?.with_name("__print")?,
-                        )?;
-                        ctx.eval::<(), _>(
-                            r#"
-                                    globalThis.console = {
-                                        log(...v) {
-                                            globalThis.__print(`${v.join(" ")}`)
-                                        }
-                                    }
-                                "#,
-                        )
-                        .unwrap();
-
-                        let console: Object = global.get("console")?;
-                        let js_log: Function = console.get("log")?;
-                        match ctx.eval::<Value, _>(program.as_bytes()).catch(&ctx) {
-                            Ok(ret) => match js_log.call::<(Value<'_>,), ()>((ret,)) {
-                                Err(err) => {
-                                    println!("{err}")
-                                }
-                                Ok(_) => {}
-                            },
-                            Err(err) => {
-                                println!("{err}");
-                            }
+fn run_tree_walker(ast: &[Expr], setting: &args::Settings, modules: &Modules) {
+    running_message(setting);
+    let Err(errors) = walk(ast) else {
+        return;
+    };
+    for err in errors.iter() {
+        let (filename, src) = source(modules, err.span());
+        let msg = err.report(filename, src);
+        eprintln!("{msg}");
+    }
+}
+
+fn run_js(program: &str) {
+    use rquickjs::{CatchResultExt, Context, Function, Object, Result, Runtime, Value};
+
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    let _ = ctx.with(|ctx| -> Result<()> {
+        let global = ctx.globals();
+        global.set(
+            "__print",
+            Function::new(ctx.clone(), print)?.with_name("__print")?,
+        )?;
+        ctx.eval::<(), _>(
+            r#"
+                    globalThis.console = {
+                        log(...v) {
+                            globalThis.__print(`${v.join(" ")}`)
                         }
-                        Ok(())
-                    });
-                }
+                    }
+                "#,
+        )
+        .unwrap();
 
-                /*debug_program(&program);
-                let mut vm = Machine::new(program, false);
-                vm.run();*/
-            },
-        );
+        let console: Object = global.get("console")?;
+        let js_log: Function = console.get("log")?;
+        match ctx.eval::<Value, _>(program.as_bytes()).catch(&ctx) {
+            Ok(ret) => {
+                if let Err(err) = js_log.call::<(Value<'_>,), ()>((ret,)) {
+                    println!("{err}")
+                }
+            }
+            Err(err) => {
+                println!("{err}");
+            }
+        }
+        Ok(())
+    });
 }
 
 fn print(s: String) {
@@ -0,0 +1,53 @@
+use std::process::Command;
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_snowc"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("failed to run snowc");
+    String::from_utf8(output.stdout).expect("output is not utf-8")
+}
+
+#[test]
+fn test_std_sample() {
+    let interpreted = run(&["samples/std.snow"]);
+    assert_eq!(interpreted, "Some 11 Err oops \n");
+    let js = run(&["samples/std.snow", "--target", "js", "--run"]);
+    assert!(js.starts_with("Some 11 Err oops \n"), "{js}");
+}
+
+#[test]
+fn test_js_int_errors() {
+    let js = run(&["-s", "main = print (7 / 0)", "--target", "js", "--run"]);
+    assert!(js.starts_with("Error: division by zero"), "{js}");
+    let js = run(&["-s", "main = print (7 % 0)", "--target", "js", "--run"]);
+    assert!(js.starts_with("Error: division by zero"), "{js}");
+    let src = "main = print (9_223_372_036_854_775_807 + 1)";
+    let js = run(&["-s", src, "--target", "js", "--run"]);
+    assert!(js.starts_with("Error: Int overflow"), "{js}");
+}
+
+#[test]
+fn test_js_tuples() {
+    let src = "swap t = match t on (a, b) -> (b, a)\n\nmain = print (1, \"a\") [(1, 2.0)] (swap (1, 'c'))";
+    let interpreted = run(&["-s", src]);
+    assert!(interpreted.starts_with("(1, a) [(1, 2.0)] (c, 1)"), "{interpreted}");
+    let js = run(&["-s", src, "--target", "js", "--run"]);
+    assert!(js.starts_with("(1, a) [(1, 2.0)] (c, 1)"), "{js}");
+}
+
+#[test]
+fn test_fmt_parse_error() {
+    let path = std::env::temp_dir().join("snowc_fmt_parse_error.snow");
+    std::fs::write(&path, "f x = (x +\n").expect("failed to write file");
+    let output = Command::new(env!("CARGO_BIN_EXE_snowc"))
+        .arg("fmt")
+        .arg(&path)
+        .output()
+        .expect("failed to run snowc");
+    assert!(!output.status.success());
+    assert!(!output.stderr.is_empty());
+    let src = std::fs::read_to_string(&path).expect("failed to read file");
+    assert_eq!(src, "f x = (x +\n");
+}